@@ -0,0 +1,291 @@
+use crate::memflow_process_wrapper::MemflowProcessWrapper;
+use crate::{MEMFLOW_CACHED_PROCESS_TYPE, MEMFLOW_PROCESS_TYPE};
+
+use memflow::prelude::v1::*;
+use shards::ref_counted_object_type_impl;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, ClonedVar, Context, ExposedTypes, InstanceData, ParamVar, Type, Types, Var,
+};
+use shards::{shlog_debug, shlog_error};
+
+use std::time::Duration;
+
+// Smallest page cache, a single page
+const MIN_CACHE_SIZE: usize = 0x1000;
+
+// The process object a cached view reads through. It holds a reference to the object, so
+// the object stays alive and in place, and borrows the process only for each call.
+#[derive(Clone)]
+pub struct SharedProcess {
+    object: ClonedVar,
+    wrapper: *mut MemflowProcessWrapper,
+}
+
+// Process objects are only used from the thread running the wire that holds them
+unsafe impl Send for SharedProcess {}
+
+impl SharedProcess {
+    fn new(var: &Var) -> std::result::Result<Self, &'static str> {
+        let wrapper =
+            Var::from_ref_counted_object::<MemflowProcessWrapper>(var, &*MEMFLOW_PROCESS_TYPE)?;
+        Ok(Self {
+            object: var.into(),
+            wrapper,
+        })
+    }
+
+    fn wrapper(&mut self) -> &mut MemflowProcessWrapper {
+        unsafe { &mut *self.wrapper }
+    }
+
+    // Number of writes made to the process through any plain or cached object
+    fn writes(&self) -> u64 {
+        unsafe { (*self.wrapper).1 }
+    }
+}
+
+impl MemoryView for SharedProcess {
+    fn read_raw_iter(&mut self, data: ReadRawMemOps) -> Result<()> {
+        self.wrapper().0.read_raw_iter(data)
+    }
+
+    fn write_raw_iter(&mut self, data: WriteRawMemOps) -> Result<()> {
+        let wrapper = self.wrapper();
+        wrapper.1 = wrapper.1.wrapping_add(1);
+        wrapper.0.write_raw_iter(data)
+    }
+
+    fn metadata(&self) -> MemoryViewMetadata {
+        unsafe { (*self.wrapper).0.metadata() }
+    }
+}
+
+// Memory view of a process that serves reads from memflow's page cache. A cached page
+// is used for the validator's validity time after it was read, and writes through the
+// view drop the pages they touch from the cache.
+pub type CachedProcessView = CachedView<'static, SharedProcess, DefaultCacheValidator>;
+
+fn build_view(
+    process: SharedProcess,
+    arch: ArchitectureIdent,
+    cache_size: usize,
+    validity: Duration,
+) -> Result<CachedProcessView> {
+    CachedView::builder(process)
+        .arch(arch.into_obj())
+        .cache_size(cache_size)
+        .validator(DefaultCacheValidator::new(validity))
+        .build()
+}
+
+// Cached process wrapper, holding a cached view of the wrapped process object. The view
+// is rebuilt with an empty cache when the process was written to other than through it.
+pub struct MemflowCachedProcessWrapper {
+    view: CachedProcessView,
+    process: SharedProcess,
+    writes_seen: u64,
+    arch: ArchitectureIdent,
+    cache_size: usize,
+    validity: Duration,
+}
+
+ref_counted_object_type_impl!(MemflowCachedProcessWrapper);
+
+impl MemflowCachedProcessWrapper {
+    // Drop every cached page if a write bypassed the view since it last looked
+    fn refresh(&mut self) -> Result<()> {
+        let writes = self.process.writes();
+        if writes != self.writes_seen {
+            self.view = build_view(
+                self.process.clone(),
+                self.arch,
+                self.cache_size,
+                self.validity,
+            )?;
+            self.writes_seen = writes;
+        }
+        Ok(())
+    }
+
+    fn read_raw_into(&mut self, address: umem, out: &mut [u8]) -> Result<()> {
+        self.refresh()?;
+        self.view.read_raw_into(Address::from(address), out)
+    }
+
+    fn write_raw(&mut self, address: umem, data: &[u8]) -> Result<()> {
+        self.refresh()?;
+        let result = self.view.write_raw(Address::from(address), data);
+        // The view already dropped the pages it wrote, so its own write needs no rebuild
+        self.writes_seen = self.process.writes();
+        result
+    }
+}
+
+// A process object received by a read, write or scan shard, either plain or cached
+pub enum ProcessHandle<'a> {
+    Plain(&'a mut MemflowProcessWrapper),
+    Cached(&'a mut MemflowCachedProcessWrapper),
+}
+
+impl ProcessHandle<'_> {
+    // The underlying process, for anything that must not go through the cache. Memory
+    // written through it must be reported with note_write.
+    pub fn process(&mut self) -> &mut ProcessInstanceArcBox<'static> {
+        match self {
+            ProcessHandle::Plain(wrapper) => &mut wrapper.0,
+            ProcessHandle::Cached(cached) => &mut cached.process.wrapper().0,
+        }
+    }
+
+    pub fn is_cached(&self) -> bool {
+        matches!(self, ProcessHandle::Cached(_))
+    }
+
+    // Records a write made through process(), so cached views drop their stale pages
+    pub fn note_write(&mut self) {
+        let wrapper = match self {
+            ProcessHandle::Plain(wrapper) => &mut **wrapper,
+            ProcessHandle::Cached(cached) => cached.process.wrapper(),
+        };
+        wrapper.1 = wrapper.1.wrapping_add(1);
+    }
+
+    pub fn read_raw_into(&mut self, address: umem, out: &mut [u8]) -> Result<()> {
+        match self {
+            ProcessHandle::Plain(wrapper) => wrapper.0.read_raw_into(Address::from(address), out),
+            ProcessHandle::Cached(cached) => cached.read_raw_into(address, out),
+        }
+    }
+
+    // Writes to a cached process go through its view, so the cache never serves the
+    // bytes that were there before the write
+    pub fn write_raw(&mut self, address: umem, data: &[u8]) -> Result<()> {
+        match self {
+            ProcessHandle::Plain(_) => {
+                self.note_write();
+                self.process().write_raw(Address::from(address), data)
+            }
+            ProcessHandle::Cached(cached) => cached.write_raw(address, data),
+        }
+    }
+}
+
+// Helper function to get the process behind a plain or cached process object
+pub fn process_handle(var: &Var) -> std::result::Result<ProcessHandle<'_>, &'static str> {
+    if let Ok(process) =
+        Var::from_ref_counted_object::<MemflowProcessWrapper>(var, &*MEMFLOW_PROCESS_TYPE)
+    {
+        return Ok(ProcessHandle::Plain(unsafe { &mut *process }));
+    }
+
+    let cached = Var::from_ref_counted_object::<MemflowCachedProcessWrapper>(
+        var,
+        &*MEMFLOW_CACHED_PROCESS_TYPE,
+    )?;
+    Ok(ProcessHandle::Cached(unsafe { &mut *cached }))
+}
+
+// Define the CachedProcess Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.CachedProcess",
+    "Wraps a process in a page cache to speed up repeated reads of the same memory."
+)]
+pub struct MemflowCachedProcessShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("CacheSize", "Maximum number of bytes kept in the page cache (default: 2 MiB).", [common_type::int, common_type::int_var])]
+    cache_size: ParamVar,
+
+    #[shard_param("Validity", "How long a cached page is used before it is read again, in milliseconds (default: 1000).", [common_type::int, common_type::int_var])]
+    validity: ParamVar,
+
+    // Store the output cached process object
+    output_process: ClonedVar,
+}
+
+impl Default for MemflowCachedProcessShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            cache_size: ParamVar::new((2 * 1024 * 1024).into()),
+            validity: ParamVar::new(1000.into()),
+            output_process: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowCachedProcessShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_PROCESS_TYPES // Takes process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_CACHED_PROCESS_TYPES // Outputs our cached process object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the cached process (and our reference to the process) on cleanup
+        self.output_process = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Make sure the input really is a process before wrapping it
+        let mut process = SharedProcess::new(input)?;
+
+        let cache_size: i64 = self.cache_size.get().as_ref().try_into()?;
+        let validity: i64 = self.validity.get().as_ref().try_into()?;
+        if cache_size <= 0 {
+            return Err("CacheSize must be greater than 0");
+        }
+        if validity < 0 {
+            return Err("Validity must not be negative");
+        }
+
+        shlog_debug!(
+            "Creating cached process (cache size: {} bytes, validity: {} ms)",
+            cache_size,
+            validity
+        );
+
+        let arch = process.wrapper().0.info().proc_arch;
+        let cache_size = (cache_size as usize).max(MIN_CACHE_SIZE);
+        let validity = Duration::from_millis(validity as u64);
+        let view = build_view(process.clone(), arch, cache_size, validity).map_err(|e| {
+            shlog_error!("Failed to create cached process view: {}", e);
+            "Failed to create cached process."
+        })?;
+
+        let wrapper = MemflowCachedProcessWrapper {
+            view,
+            writes_seen: process.writes(),
+            process,
+            arch,
+            cache_size,
+            validity,
+        };
+
+        self.output_process = Var::new_ref_counted(wrapper, &MEMFLOW_CACHED_PROCESS_TYPE).into();
+        Ok(Some(self.output_process.0))
+    }
+}
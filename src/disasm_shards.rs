@@ -0,0 +1,567 @@
+use crate::cached_process::{process_handle, ProcessHandle};
+use crate::parse_address;
+use crate::xref_scanner::{init_capstone, Arch, MAX_INSN_LEN};
+
+use capstone::Capstone;
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, Context, ExposedTypes, InstanceData, ParamVar, Type,
+    Types, Var, ANYS_TYPES, ANY_TABLE_TYPES, INT_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Helper function to build a disassembler for a process, honoring an optional Arch param
+fn process_capstone(
+    arch_param: &ParamVar,
+    process: &ProcessInstanceArcBox<'static>,
+) -> std::result::Result<Capstone, &'static str> {
+    let arch = if arch_param.get().is_none() {
+        let proc_arch = process.info().proc_arch;
+        Arch::from_ident(proc_arch).ok_or_else(|| {
+            shlog_error!("Unsupported process architecture: {:?}", proc_arch);
+            "Unsupported process architecture."
+        })?
+    } else {
+        let name: &str = arch_param.get().as_ref().try_into()?;
+        Arch::from_name(name)?
+    };
+
+    init_capstone(arch).map_err(|e| {
+        shlog_error!("Failed to initialize disassembler: {}", e);
+        "Failed to initialize disassembler."
+    })
+}
+
+// Helper function to read code bytes from a process
+fn read_code(
+    process: &mut ProcessHandle,
+    address: umem,
+    size: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    let mut buffer = vec![0u8; size];
+    process.read_raw_into(address, &mut buffer).map_err(|e| {
+        shlog_error!("Failed to read code at 0x{:x}: {}", address, e);
+        "Failed to read memory from process."
+    })?;
+    Ok(buffer)
+}
+
+// Granularity at which code that fails to read at once is read again
+const CODE_PAGE_SIZE: umem = 0x1000;
+
+// Helper function to read up to `size` code bytes from a process. Code often runs up to
+// the end of its mapping, so when the whole range can't be read, the readable pages at
+// its start are returned instead. Fails only when nothing at `address` can be read.
+fn read_mapped_code(
+    process: &mut ProcessHandle,
+    address: umem,
+    size: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    let mut buffer = vec![0u8; size];
+    if process.read_raw_into(address, &mut buffer).is_ok() {
+        return Ok(buffer);
+    }
+
+    let mut readable = 0;
+    while readable < size {
+        let page_address = address + readable as umem;
+        let page_len =
+            ((CODE_PAGE_SIZE - page_address % CODE_PAGE_SIZE) as usize).min(size - readable);
+        if process
+            .read_raw_into(page_address, &mut buffer[readable..readable + page_len])
+            .is_err()
+        {
+            break;
+        }
+        readable += page_len;
+    }
+
+    if readable == 0 {
+        shlog_error!("Failed to read code at 0x{:x}", address);
+        return Err("Failed to read memory from process.");
+    }
+    buffer.truncate(readable);
+    Ok(buffer)
+}
+
+// Most instructions a single Disassemble run decodes
+const MAX_DISASM_COUNT: i64 = 10_000;
+
+// Define the Disassemble Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.Disassemble",
+    "Disassembles a number of instructions starting at an address."
+)]
+pub struct MemflowDisassembleShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Address of the first instruction.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Count", "Number of instructions to disassemble, at most 10000 (default: 10). Fewer are returned when the code runs into unreadable memory.", [common_type::int, common_type::int_var])]
+    count: ParamVar,
+
+    #[shard_param("Arch", "Architecture to decode as: 'x86' or 'x64' (default: the process architecture).", [common_type::none, common_type::string, common_type::string_var])]
+    arch: ParamVar,
+
+    // Output list of instructions as sequence of tables
+    instructions: AutoSeqVar,
+}
+
+impl Default for MemflowDisassembleShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            count: ParamVar::new(10.into()),
+            arch: ParamVar::default(),
+            instructions: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowDisassembleShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of instruction tables
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.instructions = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let address_umem = parse_address(self.address.get())?;
+        let count: i64 = self.count.get().as_ref().try_into()?;
+        if count <= 0 {
+            return Err("Count must be greater than 0");
+        }
+        if count > MAX_DISASM_COUNT {
+            return Err("Count must not be greater than 10000");
+        }
+        let count = count as usize;
+
+        let cs = process_capstone(&self.arch, process.process())?;
+
+        shlog_debug!(
+            "Disassembling {} instructions at 0x{:x}",
+            count,
+            address_umem
+        );
+
+        // Read enough bytes for the longest possible instructions, disasm_count only
+        // emits instructions that fully decode, so a short read just yields fewer of them
+        let code = read_mapped_code(&mut process, address_umem, count * MAX_INSN_LEN)?;
+        let insns = cs
+            .disasm_count(&code, address_umem as u64, count)
+            .map_err(|e| {
+                shlog_error!("Failed to disassemble at 0x{:x}: {}", address_umem, e);
+                "Failed to disassemble instructions."
+            })?;
+
+        self.instructions.0.clear();
+        for insn in insns.iter() {
+            let address_var: Var = (insn.address() as i64).into();
+            let bytes_var = Var::ephemeral_slice(insn.bytes());
+            let mnemonic_var = Var::ephemeral_string(insn.mnemonic().unwrap_or(""));
+            let op_str_var = Var::ephemeral_string(insn.op_str().unwrap_or(""));
+
+            let mut entry = AutoTableVar::new();
+            entry.0.insert_fast_static("address", &address_var);
+            entry.0.insert_fast_static("bytes", &bytes_var);
+            entry.0.insert_fast_static("mnemonic", &mnemonic_var);
+            entry.0.insert_fast_static("op_str", &op_str_var);
+            self.instructions.0.emplace_table(entry);
+        }
+
+        Ok(Some(self.instructions.0 .0))
+    }
+}
+
+// Largest MinBytes an InstructionLength run decodes
+const MAX_MIN_BYTES: i64 = 0x1000;
+
+// Define the InstructionLength Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.InstructionLength",
+    "Decodes the instructions at an address and returns how many bytes they cover."
+)]
+pub struct MemflowInstructionLengthShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Address of the first instruction.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("MinBytes", "Keep decoding whole instructions until at least this many bytes are covered, e.g. the size of a hook jump, at most 4096 (default: 1, the first instruction only).", [common_type::int, common_type::int_var])]
+    min_bytes: ParamVar,
+
+    #[shard_param("Arch", "Architecture to decode as: 'x86' or 'x64' (default: the process architecture).", [common_type::none, common_type::string, common_type::string_var])]
+    arch: ParamVar,
+
+    // Output table with the decoded length
+    result: AutoTableVar,
+}
+
+impl Default for MemflowInstructionLengthShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            min_bytes: ParamVar::new(1.into()),
+            arch: ParamVar::default(),
+            result: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowInstructionLengthShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {length, count, mnemonic}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.result = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let address_umem = parse_address(self.address.get())?;
+        let min_bytes: i64 = self.min_bytes.get().as_ref().try_into()?;
+        if min_bytes <= 0 {
+            return Err("MinBytes must be greater than 0");
+        }
+        if min_bytes > MAX_MIN_BYTES {
+            return Err("MinBytes must not be greater than 4096");
+        }
+        let min_bytes = min_bytes as usize;
+
+        let cs = process_capstone(&self.arch, process.process())?;
+
+        // The last instruction needed may start just before MinBytes and be of maximum length.
+        // Near the end of the mapping fewer bytes are read, enough for shorter instructions.
+        let code = read_mapped_code(&mut process, address_umem, min_bytes - 1 + MAX_INSN_LEN)?;
+
+        let mut length = 0;
+        let mut count = 0;
+        let mut mnemonic = String::new();
+        while length < min_bytes {
+            let insns = cs
+                .disasm_count(&code[length..], address_umem as u64 + length as u64, 1)
+                .map_err(|e| {
+                    shlog_error!("Failed to disassemble at 0x{:x}: {}", address_umem, e);
+                    "Failed to disassemble instructions."
+                })?;
+            let insn = insns.iter().next().ok_or_else(|| {
+                shlog_error!(
+                    "No valid instruction at 0x{:x}",
+                    address_umem + length as umem
+                );
+                "Failed to decode an instruction."
+            })?;
+
+            if count == 0 {
+                mnemonic = insn.mnemonic().unwrap_or("").to_string();
+            }
+            length += insn.bytes().len();
+            count += 1;
+        }
+
+        shlog_debug!(
+            "Decoded {} instructions covering {} bytes at 0x{:x}",
+            count,
+            length,
+            address_umem
+        );
+
+        let length_var: Var = (length as i64).into();
+        let count_var: Var = (count as i64).into();
+        let mnemonic_var = Var::ephemeral_string(&mnemonic);
+
+        self.result.0.clear();
+        self.result.0.insert_fast_static("length", &length_var);
+        self.result.0.insert_fast_static("count", &count_var);
+        self.result.0.insert_fast_static("mnemonic", &mnemonic_var);
+
+        Ok(Some(self.result.0 .0))
+    }
+}
+
+// Common function prologues: push ebp; mov ebp, esp / mov [rsp+x], rbx / push rbp; mov rbp, rsp
+const FUNCTION_PROLOGUES: &[&[u8]] = &[
+    &[0x55, 0x8B, 0xEC],
+    &[0x48, 0x89, 0x5C, 0x24],
+    &[0x55, 0x48, 0x89, 0xE5],
+];
+
+// Common first instructions of functions that follow padding: push ebp/ebx/esi/edi,
+// mov edi, edi, sub rsp, mov [rsp+x], reg, push rbx..rdi and r12..r15 (REX), mov rax, rsp
+// and endbr64/endbr32
+const PADDED_PROLOGUES: &[&[u8]] = &[
+    &[0x55],
+    &[0x53],
+    &[0x56],
+    &[0x57],
+    &[0x8B, 0xFF],
+    &[0x48, 0x83, 0xEC],
+    &[0x48, 0x81, 0xEC],
+    &[0x48, 0x89, 0x4C, 0x24],
+    &[0x48, 0x89, 0x54, 0x24],
+    &[0x48, 0x89, 0x5C, 0x24],
+    &[0x48, 0x89, 0x6C, 0x24],
+    &[0x48, 0x89, 0x74, 0x24],
+    &[0x48, 0x89, 0x7C, 0x24],
+    &[0x4C, 0x89, 0x44, 0x24],
+    &[0x4C, 0x89, 0x4C, 0x24],
+    &[0x40, 0x53],
+    &[0x40, 0x55],
+    &[0x40, 0x56],
+    &[0x40, 0x57],
+    &[0x41, 0x54],
+    &[0x41, 0x55],
+    &[0x41, 0x56],
+    &[0x41, 0x57],
+    &[0x48, 0x8B, 0xC4],
+    &[0xF3, 0x0F, 0x1E, 0xFA],
+    &[0xF3, 0x0F, 0x1E, 0xFB],
+];
+
+// Whether a byte is int3 or nop padding that compilers put between functions
+fn is_padding(byte: u8) -> bool {
+    byte == 0xCC || byte == 0x90
+}
+
+// Helper function to check whether a function plausibly starts at `offset` of `code`.
+// That is the case for a known prologue, for the first byte after a ret (0xC3, or 0xC2
+// with its 16-bit immediate) followed by padding, and for a common first instruction
+// after at least two bytes of padding. Padding bytes also occur inside instructions,
+// so padding alone is not taken as a function boundary.
+fn is_function_start(code: &[u8], offset: usize) -> bool {
+    let rest = &code[offset..];
+    if FUNCTION_PROLOGUES
+        .iter()
+        .any(|prologue| rest.starts_with(prologue))
+    {
+        return true;
+    }
+    match rest.first() {
+        Some(&byte) if !is_padding(byte) => {}
+        _ => return false,
+    }
+
+    let padding = code[..offset]
+        .iter()
+        .rev()
+        .take_while(|&&byte| is_padding(byte))
+        .count();
+    if padding == 0 {
+        return false;
+    }
+
+    let padding_start = offset - padding;
+    let after_ret = (padding_start >= 1 && code[padding_start - 1] == 0xC3)
+        || (padding_start >= 3 && code[padding_start - 3] == 0xC2);
+    if after_ret {
+        return true;
+    }
+
+    padding >= 2
+        && PADDED_PROLOGUES
+            .iter()
+            .any(|prologue| rest.starts_with(prologue))
+}
+
+// Define the FindFunctionStart Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.FindFunctionStart",
+    "Finds the start of the function containing an address by scanning backwards for a prologue."
+)]
+pub struct MemflowFindFunctionStartShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Address inside the function.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("MaxBack", "Maximum number of bytes to scan backwards (default: 0x1000).", [common_type::int, common_type::int_var])]
+    max_back: ParamVar,
+}
+
+impl Default for MemflowFindFunctionStartShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            max_back: ParamVar::new(0x1000.into()),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowFindFunctionStartShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &INT_TYPES // Outputs the function start address
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let address_umem = parse_address(self.address.get())?;
+        let max_back: i64 = self.max_back.get().as_ref().try_into()?;
+        if max_back < 0 {
+            return Err("MaxBack must not be negative");
+        }
+
+        // Read the window before the address plus enough to match a prologue at it.
+        // The window may reach into an unmapped page, then only the address' page is used.
+        let longest_prologue = FUNCTION_PROLOGUES
+            .iter()
+            .map(|p| p.len())
+            .max()
+            .unwrap_or(0);
+        let end = address_umem.saturating_add(longest_prologue as umem);
+        let mut start = address_umem.saturating_sub(max_back as umem);
+        let code = match read_code(&mut process, start, (end - start) as usize) {
+            Ok(code) => code,
+            Err(_) => {
+                start = start.max(address_umem - address_umem % 0x1000);
+                read_code(&mut process, start, (end - start) as usize)?
+            }
+        };
+
+        shlog_debug!(
+            "Searching function start for 0x{:x} from 0x{:x}",
+            address_umem,
+            start
+        );
+
+        let address_offset = (address_umem - start) as usize;
+        let offset = (0..=address_offset)
+            .rev()
+            .find(|offset| is_function_start(&code, *offset))
+            .ok_or_else(|| {
+                shlog_error!("No function start found before 0x{:x}", address_umem);
+                "No function start found within MaxBack."
+            })?;
+
+        Ok(Some(((start + offset as umem) as i64).into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prologues_start_functions() {
+        assert!(is_function_start(&[0x55, 0x8B, 0xEC], 0));
+        assert!(is_function_start(&[0x00, 0x55, 0x48, 0x89, 0xE5], 1));
+        assert!(!is_function_start(&[0x00, 0x48, 0x83, 0xEC, 0x28], 1));
+    }
+
+    #[test]
+    fn padding_needs_a_prologue() {
+        // sub rsp, 0x28 after int3 padding
+        assert!(is_function_start(
+            &[0x00, 0xCC, 0xCC, 0x48, 0x83, 0xEC, 0x28],
+            3
+        ));
+        // xor eax, eax after int3 padding, no return before it
+        assert!(!is_function_start(&[0x00, 0xCC, 0xCC, 0x33, 0xC0], 3));
+        // A single padding byte is too weak
+        assert!(!is_function_start(&[0x00, 0xCC, 0x48, 0x83, 0xEC, 0x28], 2));
+        // Inside the padding
+        assert!(!is_function_start(&[0x00, 0xCC, 0xCC, 0xCC, 0x55], 2));
+    }
+
+    #[test]
+    fn ret_followed_by_padding_is_a_boundary() {
+        assert!(is_function_start(&[0xC3, 0xCC, 0x33, 0xC0], 2));
+        assert!(is_function_start(&[0xC3, 0x90, 0x90, 0x33, 0xC0], 3));
+        // ret 8
+        assert!(is_function_start(
+            &[0xC2, 0x08, 0x00, 0xCC, 0xCC, 0x33, 0xC0],
+            5
+        ));
+        // ret without padding is not enough
+        assert!(!is_function_start(&[0xC3, 0x33, 0xC0], 1));
+    }
+}
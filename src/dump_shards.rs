@@ -0,0 +1,535 @@
+use crate::cached_process::{process_handle, ProcessHandle};
+use crate::memflow_module_wrapper::MemflowModuleWrapper;
+use crate::protection_filter::{page_type_to_rwx, protection_filter_matches};
+use crate::{
+    chunk_size_param, parse_address, DEFAULT_CHUNK_SIZE, MEMFLOW_MODULE_TYPE,
+    MEMFLOW_MODULE_TYPE_VAR,
+};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, ANY_TABLE_TYPES, INT_TYPES, STRING_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+// Granularity at which unreadable parts of a dumped range are zero-filled
+const DUMP_PAGE_SIZE: umem = 0x1000;
+
+// Bytes shown on each line of a hexdump
+const HEXDUMP_LINE_SIZE: usize = 16;
+
+// Largest range a Hexdump formats, the string is about five times the size of the range
+const MAX_HEXDUMP_SIZE: i64 = 0x10_0000;
+
+// Helper function to format bytes read at `address` as a classic hexdump, 16 bytes per
+// line: `offset  hex bytes  |ascii|`. Bytes inside one of the `gaps` are shown as "??".
+pub fn format_hexdump(
+    data: &[u8],
+    address: umem,
+    gaps: &[(umem, usize)],
+    relative: bool,
+) -> String {
+    let unreadable = |offset: usize| {
+        let byte_address = address + offset as umem;
+        gaps.iter().any(|(gap_address, gap_size)| {
+            byte_address >= *gap_address && byte_address - gap_address < *gap_size as umem
+        })
+    };
+
+    let mut out = String::with_capacity(data.len() / HEXDUMP_LINE_SIZE * 80 + 80);
+    for (line_index, line) in data.chunks(HEXDUMP_LINE_SIZE).enumerate() {
+        let line_offset = line_index * HEXDUMP_LINE_SIZE;
+        if relative {
+            out.push_str(&format!("{:08x} ", line_offset));
+        } else {
+            out.push_str(&format!("{:016x} ", address + line_offset as umem));
+        }
+
+        let mut ascii = String::with_capacity(HEXDUMP_LINE_SIZE);
+        for column in 0..HEXDUMP_LINE_SIZE {
+            // Split the bytes in two groups of eight
+            if column % 8 == 0 {
+                out.push(' ');
+            }
+            match line.get(column) {
+                Some(_) if unreadable(line_offset + column) => {
+                    out.push_str("?? ");
+                    ascii.push('?');
+                }
+                Some(byte) => {
+                    out.push_str(&format!("{:02x} ", byte));
+                    ascii.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                        *byte as char
+                    } else {
+                        '.'
+                    });
+                }
+                None => out.push_str("   "),
+            }
+        }
+
+        out.push_str(" |");
+        out.push_str(&ascii);
+        out.push_str("|\n");
+    }
+    out.pop(); // No newline after the last line
+    out
+}
+
+// Helper function to read [address, address + size) chunk by chunk, passing each chunk to
+// `sink`. When a chunk can't be read it is retried page by page and the unreadable pages
+// are zero-filled. Returns the zero-filled gaps as merged (address, size) ranges.
+pub fn read_zero_filled(
+    process: &mut ProcessHandle,
+    address: umem,
+    size: usize,
+    chunk_size: usize,
+    mut sink: impl FnMut(&[u8]) -> std::result::Result<(), &'static str>,
+) -> std::result::Result<Vec<(umem, usize)>, &'static str> {
+    let mut gaps: Vec<(umem, usize)> = Vec::new();
+    let mut add_gap = |gap_address: umem, gap_size: usize| match gaps.last_mut() {
+        Some((last_address, last_size)) if *last_address + *last_size as umem == gap_address => {
+            *last_size += gap_size;
+        }
+        _ => gaps.push((gap_address, gap_size)),
+    };
+
+    let mut buffer = vec![0u8; chunk_size.min(size)];
+    let mut offset = 0;
+    while offset < size {
+        let chunk_address = address + offset as umem;
+        let chunk = &mut buffer[..chunk_size.min(size - offset)];
+
+        if process.read_raw_into(chunk_address, chunk).is_err() {
+            // Find the unreadable pages of the chunk
+            let mut page_offset = 0;
+            while page_offset < chunk.len() {
+                let page_address = chunk_address + page_offset as umem;
+                let page_len = ((DUMP_PAGE_SIZE - page_address % DUMP_PAGE_SIZE) as usize)
+                    .min(chunk.len() - page_offset);
+                let page = &mut chunk[page_offset..page_offset + page_len];
+                if process.read_raw_into(page_address, page).is_err() {
+                    page.fill(0);
+                    add_gap(page_address, page_len);
+                }
+                page_offset += page_len;
+            }
+        }
+
+        sink(chunk)?;
+        offset += chunk.len();
+    }
+
+    Ok(gaps)
+}
+
+// Helper function to format gaps as a JSON array of {address, size} objects
+pub fn gaps_to_json(gaps: &[(umem, usize)]) -> String {
+    let entries: Vec<String> = gaps
+        .iter()
+        .map(|(address, size)| format!("{{\"address\": {}, \"size\": {}}}", address, size))
+        .collect();
+    format!("[{}]", entries.join(", "))
+}
+
+// Helper function to write a dump file, mapping I/O errors to shard errors
+pub fn write_dump(file: &mut impl Write, data: &[u8]) -> std::result::Result<(), &'static str> {
+    file.write_all(data).map_err(|e| {
+        shlog_error!("Failed to write dump file: {}", e);
+        "Failed to write dump file."
+    })
+}
+
+// Helper function to create a dump file
+pub fn create_dump(path: &str) -> std::result::Result<BufWriter<File>, &'static str> {
+    let file = File::create(path).map_err(|e| {
+        shlog_error!("Failed to create dump file '{}': {}", path, e);
+        "Failed to create dump file."
+    })?;
+    Ok(BufWriter::new(file))
+}
+
+// Define the DumpProcess Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.DumpProcess",
+    "Dumps the mapped memory of a process to a file, with a JSON sidecar describing the regions."
+)]
+pub struct MemflowDumpProcessShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Path", "File to write the memory to. The region list is written to the same path with '.json' appended.", [common_type::string, common_type::string_var])]
+    path: ParamVar,
+
+    #[shard_param("Protection", "Memory protection to filter by (e.g., 'r-x'). Regions that are not readable are never dumped.", [common_type::none, common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("GapSize", "Size of gaps to ignore between memory regions (in bytes).", [common_type::int, common_type::int_var])]
+    gap_size: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+}
+
+impl Default for MemflowDumpProcessShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            path: ParamVar::default(),
+            protection: ParamVar::default(),
+            gap_size: ParamVar::new(0.into()),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowDumpProcessShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &INT_TYPES // Outputs the number of bytes dumped
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let path: &str = self.path.get().as_ref().try_into()?;
+        let gap_size: i64 = self.gap_size.get().as_ref().try_into()?;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+        let protection_filter = if self.protection.get().is_none() {
+            None
+        } else {
+            let prot_str: &str = self.protection.get().as_ref().try_into()?;
+            Some(prot_str)
+        };
+
+        let maps: Vec<_> = process
+            .process()
+            .mapped_mem_vec(gap_size)
+            .into_iter()
+            .filter(|map| match protection_filter {
+                Some(prot_filter) => protection_filter_matches(map.2, prot_filter),
+                None => true,
+            })
+            .collect();
+
+        shlog_debug!("Dumping {} memory regions to '{}'", maps.len(), path);
+
+        // Regions are written back to back, the sidecar records where each one went
+        let mut file = create_dump(path)?;
+        let mut regions = Vec::new();
+        let mut file_offset: usize = 0;
+        for map in maps {
+            let address = map.0.to_umem();
+            let size = map.1.to_umem() as usize;
+            let rwx = page_type_to_rwx(map.2);
+
+            if !rwx.starts_with('r') {
+                regions.push(format!(
+                    "{{\"address\": {}, \"size\": {}, \"protection\": \"{}\", \"dumped\": false}}",
+                    address, size, rwx
+                ));
+                continue;
+            }
+
+            let gaps = read_zero_filled(&mut process, address, size, chunk_size, |chunk| {
+                write_dump(&mut file, chunk)
+            })?;
+            regions.push(format!(
+                "{{\"address\": {}, \"size\": {}, \"protection\": \"{}\", \"dumped\": true, \"file_offset\": {}, \"unreadable\": {}}}",
+                address,
+                size,
+                rwx,
+                file_offset,
+                gaps_to_json(&gaps)
+            ));
+            file_offset += size;
+        }
+
+        file.flush().map_err(|e| {
+            shlog_error!("Failed to write dump file: {}", e);
+            "Failed to write dump file."
+        })?;
+
+        let sidecar = format!("{{\"regions\": [\n  {}\n]}}\n", regions.join(",\n  "));
+        std::fs::write(format!("{}.json", path), sidecar).map_err(|e| {
+            shlog_error!("Failed to write dump sidecar for '{}': {}", path, e);
+            "Failed to write dump sidecar file."
+        })?;
+
+        Ok(Some((file_offset as i64).into()))
+    }
+}
+
+// Define the DumpModule Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.DumpModule",
+    "Reads the whole image of a loaded module, zero-filling unreadable pages."
+)]
+pub struct MemflowDumpModuleShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The module to dump.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("Path", "File to write the module image to. When set, the output has no 'data' field.", [common_type::none, common_type::string, common_type::string_var])]
+    path: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output table with the image and its unreadable gaps
+    output: AutoTableVar,
+}
+
+impl Default for MemflowDumpModuleShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            path: ParamVar::default(),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            output: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowDumpModuleShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {size, gaps, data}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get the module from parameter
+        let module = unsafe {
+            &*Var::from_ref_counted_object::<MemflowModuleWrapper>(
+                self.module.get(),
+                &*MEMFLOW_MODULE_TYPE,
+            )?
+        };
+        let base = module.0.base.to_umem();
+        let size = module.0.size as usize;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+
+        shlog_debug!(
+            "Dumping module {} at 0x{:x}, size: {} bytes",
+            module.0.name,
+            base,
+            size
+        );
+
+        // Either stream the image to a file or collect it for the output
+        let mut data = Vec::new();
+        let gaps = if self.path.get().is_none() {
+            data.reserve(size);
+            read_zero_filled(&mut process, base, size, chunk_size, |chunk| {
+                data.extend_from_slice(chunk);
+                Ok(())
+            })?
+        } else {
+            let path: &str = self.path.get().as_ref().try_into()?;
+            let mut file = create_dump(path)?;
+            let gaps = read_zero_filled(&mut process, base, size, chunk_size, |chunk| {
+                write_dump(&mut file, chunk)
+            })?;
+            file.flush().map_err(|e| {
+                shlog_error!("Failed to write dump file: {}", e);
+                "Failed to write dump file."
+            })?;
+            gaps
+        };
+
+        if !gaps.is_empty() {
+            shlog_debug!(
+                "Module {} has {} unreadable ranges, zero-filled",
+                module.0.name,
+                gaps.len()
+            );
+        }
+
+        let mut gaps_seq = AutoSeqVar::new();
+        for (address, gap_size) in gaps {
+            let address_var: Var = (address as i64).into();
+            let size_var: Var = (gap_size as i64).into();
+            let mut gap = AutoTableVar::new();
+            gap.0.insert_fast_static("address", &address_var);
+            gap.0.insert_fast_static("size", &size_var);
+            gaps_seq.0.emplace_table(gap);
+        }
+
+        let size_var: Var = (size as i64).into();
+        self.output.0.clear();
+        self.output.0.insert_fast_static("size", &size_var);
+        self.output.0.insert_fast_static("gaps", &gaps_seq.0 .0);
+        if self.path.get().is_none() {
+            let data_var = Var::ephemeral_slice(data.as_slice());
+            self.output.0.insert_fast_static("data", &data_var);
+        }
+
+        Ok(Some(self.output.0 .0))
+    }
+}
+
+// Define the Hexdump Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.Hexdump",
+    "Reads a range of process memory and formats it as a classic hexdump string, 16 bytes per line."
+)]
+pub struct MemflowHexdumpShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Start address of the range to dump.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Size", "Number of bytes to dump, at most 1 MiB (default: 256).", [common_type::int, common_type::int_var])]
+    size: ParamVar,
+
+    #[shard_param("Relative", "Whether to show offsets from Address instead of absolute addresses (default: false).", [common_type::bool, common_type::bool_var])]
+    relative: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output hexdump string
+    hexdump: ClonedVar,
+}
+
+impl Default for MemflowHexdumpShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            size: ParamVar::new(256.into()),
+            relative: ParamVar::new(false.into()),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            hexdump: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowHexdumpShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &STRING_TYPES // Outputs the hexdump
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.hexdump = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let address = parse_address(self.address.get())?;
+        let size: i64 = self.size.get().as_ref().try_into()?;
+        if size <= 0 {
+            return Err("Size must be greater than 0");
+        }
+        if size > MAX_HEXDUMP_SIZE {
+            return Err("Size must be at most 1 MiB");
+        }
+        let size = size as usize;
+        let relative: bool = self.relative.get().as_ref().try_into()?;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+
+        shlog_debug!("Hexdumping {} bytes at address: 0x{:x}", size, address);
+
+        // Read the range chunk by chunk, unreadable pages show up as gaps
+        let mut data = Vec::with_capacity(size);
+        let gaps = read_zero_filled(&mut process, address, size, chunk_size, |chunk| {
+            data.extend_from_slice(chunk);
+            Ok(())
+        })?;
+
+        let hexdump = format_hexdump(&data, address, &gaps, relative);
+        self.hexdump = Var::ephemeral_string(&hexdump).into();
+        Ok(Some(self.hexdump.0))
+    }
+}
@@ -0,0 +1,740 @@
+use crate::cached_process::process_handle;
+use crate::dump_shards::read_zero_filled;
+use crate::memflow_module_wrapper::MemflowModuleWrapper;
+use crate::pe_image::{PeImage, IMAGE_SCN_MEM_EXECUTE};
+use crate::protection_filter::{page_type_to_rwx, protection_filter_matches};
+use crate::region_hash::RegionHasher;
+use crate::{
+    chunk_size_param, parse_address, DEFAULT_CHUNK_SIZE, MEMFLOW_MODULE_TYPE,
+    MEMFLOW_MODULE_TYPE_VAR,
+};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, ANYS_TYPES, STRING_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Helper function to check whether [address, address + size) lies within a single module
+fn backed_by_module(modules: &[ModuleInfo], address: umem, size: umem) -> bool {
+    modules.iter().any(|module| {
+        let base = module.base.to_umem();
+        address >= base && address + size <= base + module.size as umem
+    })
+}
+
+// Define the SuspiciousRegions Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.SuspiciousRegions",
+    "Lists executable memory of a process that is not backed by any loaded module."
+)]
+pub struct MemflowSuspiciousRegionsShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Output list of regions as sequence of tables
+    regions: AutoSeqVar,
+}
+
+impl Default for MemflowSuspiciousRegionsShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            regions: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowSuspiciousRegionsShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {address, size, protection, reason}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.regions = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let modules = process.process().module_list().map_err(|e| {
+            shlog_error!("Failed to list modules: {}", e);
+            "Failed to list modules."
+        })?;
+        let maps = process.process().mapped_mem_vec(0);
+
+        shlog_debug!(
+            "Checking {} memory regions against {} modules",
+            maps.len(),
+            modules.len()
+        );
+
+        self.regions.0.clear();
+        for map in maps {
+            let address = map.0.to_umem();
+            let size = map.1.to_umem();
+            let rwx = page_type_to_rwx(map.2);
+
+            // Code outside of every module was allocated at runtime
+            if !rwx.ends_with('x') || backed_by_module(&modules, address, size) {
+                continue;
+            }
+            let reason = if rwx == "rwx" {
+                "unbacked_rwx"
+            } else {
+                "unbacked_exec"
+            };
+
+            let address_var: Var = (address as i64).into();
+            let size_var: Var = (size as i64).into();
+            let protection_var = Var::ephemeral_string(&rwx);
+            let reason_var = Var::ephemeral_string(reason);
+
+            let mut region = AutoTableVar::new();
+            region.0.insert_fast_static("address", &address_var);
+            region.0.insert_fast_static("size", &size_var);
+            region.0.insert_fast_static("protection", &protection_var);
+            region.0.insert_fast_static("reason", &reason_var);
+            self.regions.0.emplace_table(region);
+        }
+
+        Ok(Some(self.regions.0 .0))
+    }
+}
+
+// Helper function to compute the Shannon entropy, in bits per byte, of a byte histogram
+fn histogram_entropy(histogram: &[u64; 256]) -> f64 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    histogram
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// Define the RegionEntropy Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.RegionEntropy",
+    "Computes the Shannon entropy of each mapped memory region of a process."
+)]
+pub struct MemflowRegionEntropyShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("MinEntropy", "Only list regions with at least this entropy, in bits per byte from 0 to 8 (default: 0).", [common_type::float, common_type::float_var])]
+    min_entropy: ParamVar,
+
+    #[shard_param("Protection", "Memory protection to filter by (e.g., 'r-x').", [common_type::none, common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output list of regions as sequence of tables
+    regions: AutoSeqVar,
+}
+
+impl Default for MemflowRegionEntropyShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            min_entropy: ParamVar::new(0.0.into()),
+            protection: ParamVar::default(),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            regions: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowRegionEntropyShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {address, size, entropy}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.regions = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let min_entropy: f64 = self.min_entropy.get().as_ref().try_into()?;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+        let protection_filter = if self.protection.get().is_none() {
+            None
+        } else {
+            let prot_str: &str = self.protection.get().as_ref().try_into()?;
+            Some(prot_str)
+        };
+
+        let maps: Vec<_> = process
+            .process()
+            .mapped_mem_vec(0)
+            .into_iter()
+            .filter(|map| match protection_filter {
+                Some(prot_filter) => protection_filter_matches(map.2, prot_filter),
+                None => true,
+            })
+            .collect();
+
+        shlog_debug!("Computing entropy of {} memory regions", maps.len());
+
+        self.regions.0.clear();
+        for map in maps {
+            let address = map.0.to_umem();
+            let size = map.1.to_umem() as usize;
+
+            let mut histogram = [0u64; 256];
+            let gaps = read_zero_filled(&mut process, address, size, chunk_size, |chunk| {
+                for byte in chunk {
+                    histogram[*byte as usize] += 1;
+                }
+                Ok(())
+            })?;
+
+            // Unreadable pages are zero-filled, they must not count as data
+            let unreadable: usize = gaps.iter().map(|(_, gap_size)| gap_size).sum();
+            histogram[0] -= unreadable as u64;
+            if unreadable == size {
+                continue;
+            }
+
+            let entropy = histogram_entropy(&histogram);
+            if entropy < min_entropy {
+                continue;
+            }
+
+            let address_var: Var = (address as i64).into();
+            let size_var: Var = (size as i64).into();
+            let entropy_var = Var::new_float(entropy);
+
+            let mut region = AutoTableVar::new();
+            region.0.insert_fast_static("address", &address_var);
+            region.0.insert_fast_static("size", &size_var);
+            region.0.insert_fast_static("entropy", &entropy_var);
+            self.regions.0.emplace_table(region);
+        }
+
+        Ok(Some(self.regions.0 .0))
+    }
+}
+
+// Define the HashRegion Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.HashRegion",
+    "Hashes a range of process memory, reading it in chunks, and returns the hex digest."
+)]
+pub struct MemflowHashRegionShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Start address of the range to hash.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Size", "Number of bytes to hash.", [common_type::int, common_type::int_var])]
+    size: ParamVar,
+
+    #[shard_param("Algo", "Hash algorithm: 'crc32', 'fnv1a' (64-bit) or 'sha256' (default: 'sha256').", [common_type::string, common_type::string_var])]
+    algo: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output digest
+    digest: ClonedVar,
+}
+
+impl Default for MemflowHashRegionShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            size: ParamVar::new(1.into()),
+            algo: ParamVar::new(Var::ephemeral_string("sha256")),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            digest: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowHashRegionShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &STRING_TYPES // Outputs the hex digest
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.digest = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let address_umem = parse_address(self.address.get())?;
+        let size: i64 = self.size.get().as_ref().try_into()?;
+        if size <= 0 {
+            return Err("Size must be greater than 0");
+        }
+        let size = size as usize;
+        let algo: &str = self.algo.get().as_ref().try_into()?;
+        let mut hasher = RegionHasher::from_name(algo)?;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+
+        shlog_debug!(
+            "Hashing {} bytes at address: 0x{:x} with {}",
+            size,
+            address_umem,
+            algo
+        );
+
+        // Stream the range through the hasher one chunk at a time
+        let mut buffer = vec![0u8; chunk_size.min(size)];
+        let mut offset = 0;
+        while offset < size {
+            let chunk_address = address_umem + offset as umem;
+            let chunk = &mut buffer[..chunk_size.min(size - offset)];
+            process.read_raw_into(chunk_address, chunk).map_err(|e| {
+                shlog_error!("Failed to read memory at 0x{:x}: {}", chunk_address, e);
+                "Failed to read memory from process."
+            })?;
+            hasher.update(chunk);
+            offset += chunk.len();
+        }
+
+        self.digest = Var::ephemeral_string(&hasher.finish_hex()).into();
+        Ok(Some(self.digest.0))
+    }
+}
+
+// Define the DiffRegion Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.DiffRegion",
+    "Compares process memory against a previously captured snapshot and lists the changed byte runs."
+)]
+pub struct MemflowDiffRegionShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Address the baseline was captured at.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Baseline", "Previously captured bytes to compare the current memory against.", [common_type::bytes, common_type::bytes_var])]
+    baseline: ParamVar,
+
+    #[shard_param("MaxDiffs", "Maximum number of changed runs to return (default: 1000).", [common_type::int, common_type::int_var])]
+    max_diffs: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output list of changed runs as sequence of tables
+    diffs: AutoSeqVar,
+}
+
+impl Default for MemflowDiffRegionShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            baseline: ParamVar::default(),
+            max_diffs: ParamVar::new(1000.into()),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            diffs: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowDiffRegionShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {offset, old, new}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.diffs = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let address_umem = parse_address(self.address.get())?;
+        let baseline: &[u8] = self.baseline.get().as_ref().try_into()?;
+        let max_diffs: i64 = self.max_diffs.get().as_ref().try_into()?;
+        if max_diffs < 0 {
+            return Err("MaxDiffs must not be negative");
+        }
+        let max_diffs = max_diffs as usize;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+
+        shlog_debug!(
+            "Diffing {} bytes at address: 0x{:x} against the baseline",
+            baseline.len(),
+            address_umem
+        );
+
+        // Collect runs of consecutive changed bytes as (offset, old, new), a run may
+        // continue from one chunk into the next
+        let mut runs: Vec<(usize, Vec<u8>, Vec<u8>)> = Vec::new();
+        let mut in_run = false;
+        let mut offset = 0;
+        let gaps = read_zero_filled(
+            &mut process,
+            address_umem,
+            baseline.len(),
+            chunk_size,
+            |chunk| {
+                for byte in chunk {
+                    let old = baseline[offset];
+                    if *byte == old {
+                        in_run = false;
+                    } else if in_run {
+                        let (_, old_bytes, new_bytes) = runs.last_mut().unwrap();
+                        old_bytes.push(old);
+                        new_bytes.push(*byte);
+                    } else if runs.len() < max_diffs {
+                        runs.push((offset, vec![old], vec![*byte]));
+                        in_run = true;
+                    }
+                    offset += 1;
+                }
+                Ok(())
+            },
+        )?;
+
+        // Zero-filled pages would show up as bogus changes
+        if let Some((gap_address, _)) = gaps.first() {
+            shlog_error!("Failed to read memory at 0x{:x}", gap_address);
+            return Err("Failed to read memory from process.");
+        }
+
+        self.diffs.0.clear();
+        for (offset, old, new) in runs {
+            let offset_var: Var = (offset as i64).into();
+            let old_var = Var::ephemeral_slice(old.as_slice());
+            let new_var = Var::ephemeral_slice(new.as_slice());
+
+            let mut diff = AutoTableVar::new();
+            diff.0.insert_fast_static("offset", &offset_var);
+            diff.0.insert_fast_static("old", &old_var);
+            diff.0.insert_fast_static("new", &new_var);
+            self.diffs.0.emplace_table(diff);
+        }
+
+        Ok(Some(self.diffs.0 .0))
+    }
+}
+
+// Define the DetectHooks Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.DetectHooks",
+    "Compares the code sections of a loaded module with its file on disk to find inline hooks and patches."
+)]
+pub struct MemflowDetectHooksShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The loaded module to check.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("FilePath", "Path to the PE file of the module on disk.", [common_type::string, common_type::string_var])]
+    file_path: ParamVar,
+
+    #[shard_param("MaxResults", "Maximum number of modified ranges to return (default: 1000).", [common_type::int, common_type::int_var])]
+    max_results: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output list of modified ranges as sequence of tables
+    hooks: AutoSeqVar,
+}
+
+impl Default for MemflowDetectHooksShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            file_path: ParamVar::default(),
+            max_results: ParamVar::new(1000.into()),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            hooks: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowDetectHooksShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {address, section, disk_bytes, mem_bytes, ...}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.hooks = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get the module from parameter
+        let module = unsafe {
+            &*Var::from_ref_counted_object::<MemflowModuleWrapper>(
+                self.module.get(),
+                &*MEMFLOW_MODULE_TYPE,
+            )?
+        };
+        let base = module.0.base.to_umem();
+
+        // Get parameters
+        let file_path: &str = self.file_path.get().as_ref().try_into()?;
+        let max_results: i64 = self.max_results.get().as_ref().try_into()?;
+        if max_results < 0 {
+            return Err("MaxResults must not be negative");
+        }
+        let max_results = max_results as usize;
+        let chunk_size = chunk_size_param(&self.chunk_size, 0)?;
+
+        let file = std::fs::read(file_path).map_err(|e| {
+            shlog_error!("Failed to read module file '{}': {}", file_path, e);
+            "Failed to read module file."
+        })?;
+        let image = PeImage::parse(&file)?;
+
+        // Exports name the functions a modified range belongs to, sorted by offset
+        let mut exports: Vec<(String, umem)> = match process.process().module_export_list(&module.0)
+        {
+            Ok(exports) => exports
+                .iter()
+                .map(|export| (export.name.to_string(), export.offset))
+                .collect(),
+            Err(e) => {
+                shlog_debug!("Failed to get exports of module '{}': {}", module.0.name, e);
+                Vec::new()
+            }
+        };
+        exports.sort_by_key(|(_, offset)| *offset);
+
+        let (iat_rva, iat_size) = image.iat_dir;
+
+        // Modified ranges as (section, rva, disk bytes, memory bytes)
+        let mut hooks: Vec<(&str, u32, Vec<u8>, Vec<u8>)> = Vec::new();
+        for section in image
+            .sections
+            .iter()
+            .filter(|section| section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0)
+        {
+            // Lay the file section out as the loader would, at the module's actual base
+            let mut disk = image.map_section(section);
+            image.relocate_section(section, &mut disk, base as u64)?;
+
+            let section_address = base + section.virtual_address as umem;
+            shlog_debug!(
+                "Comparing section {} at 0x{:x}, size: {} bytes",
+                section.name,
+                section_address,
+                disk.len()
+            );
+
+            let mut mem = Vec::with_capacity(disk.len());
+            let gaps = read_zero_filled(
+                &mut process,
+                section_address,
+                disk.len(),
+                chunk_size,
+                |chunk| {
+                    mem.extend_from_slice(chunk);
+                    Ok(())
+                },
+            )?;
+
+            // Unreadable pages and the import address table, which the loader fills in,
+            // are not compared
+            for (gap_address, gap_size) in gaps {
+                let start = (gap_address - section_address) as usize;
+                mem[start..start + gap_size].copy_from_slice(&disk[start..start + gap_size]);
+            }
+            let iat_start = iat_rva.saturating_sub(section.virtual_address) as usize;
+            let iat_end = iat_rva
+                .checked_add(iat_size)
+                .ok_or("PE import address table is out of range")?
+                .saturating_sub(section.virtual_address) as usize;
+            let iat_end = iat_end.min(disk.len());
+            if iat_size > 0 && iat_start < iat_end {
+                disk[iat_start..iat_end].copy_from_slice(&mem[iat_start..iat_end]);
+            }
+
+            let mut offset = 0;
+            while offset < disk.len() && hooks.len() < max_results {
+                if disk[offset] == mem[offset] {
+                    offset += 1;
+                    continue;
+                }
+                let start = offset;
+                while offset < disk.len() && disk[offset] != mem[offset] {
+                    offset += 1;
+                }
+                hooks.push((
+                    &section.name,
+                    section.virtual_address + start as u32,
+                    disk[start..offset].to_vec(),
+                    mem[start..offset].to_vec(),
+                ));
+            }
+        }
+
+        self.hooks.0.clear();
+        for (section, rva, disk_bytes, mem_bytes) in hooks {
+            let address_var: Var = ((base + rva as umem) as i64).into();
+            let section_var = Var::ephemeral_string(section);
+            let disk_var = Var::ephemeral_slice(disk_bytes.as_slice());
+            let mem_var = Var::ephemeral_slice(mem_bytes.as_slice());
+
+            let mut hook = AutoTableVar::new();
+            hook.0.insert_fast_static("address", &address_var);
+            hook.0.insert_fast_static("section", &section_var);
+            hook.0.insert_fast_static("disk_bytes", &disk_var);
+            hook.0.insert_fast_static("mem_bytes", &mem_var);
+
+            // Name the closest export at or before the modified range
+            let index = exports.partition_point(|(_, offset)| *offset <= rva as umem);
+            if index > 0 {
+                let (name, export_offset) = &exports[index - 1];
+                let export_var = Var::ephemeral_string(name);
+                let export_offset_var: Var = ((rva as umem - export_offset) as i64).into();
+                hook.0.insert_fast_static("export", &export_var);
+                hook.0
+                    .insert_fast_static("export_offset", &export_offset_var);
+            }
+
+            self.hooks.0.emplace_table(hook);
+        }
+
+        Ok(Some(self.hooks.0 .0))
+    }
+}
@@ -0,0 +1,221 @@
+use lazy_static::lazy_static;
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData, Type,
+    Types, Var, ANY_TABLE_TYPES, NONE_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// The shared inventory along with the extra plugin directories added to it
+#[derive(Default)]
+struct SharedInventory {
+    inventory: Option<Inventory>,
+    plugin_dirs: Vec<PathBuf>,
+}
+
+lazy_static! {
+    // Scanning the plugin directories is slow, so the inventory is built once and shared.
+    // Inventory isn't Sync, hence the mutex.
+    static ref INVENTORY: Mutex<SharedInventory> = Mutex::new(SharedInventory::default());
+}
+
+// Helper function to scan a plugin directory into an inventory
+fn add_plugin_dir(inventory: &mut Inventory, dir: &Path) -> std::result::Result<(), &'static str> {
+    shlog_debug!("Scanning for memflow plugins in {}", dir.display());
+    inventory.add_dir(dir.to_path_buf()).map_err(|e| {
+        shlog_error!("Failed to scan plugin directory {}: {}", dir.display(), e);
+        "Failed to scan plugin directory."
+    })?;
+    Ok(())
+}
+
+// Helper function to run `f` with the shared inventory, scanning for plugins on first
+// use or when `rescan` is set. A `plugin_path` is scanned in addition to the default
+// plugin directories the first time it is seen, and again on every rescan.
+pub fn with_inventory<R>(
+    rescan: bool,
+    plugin_path: Option<&str>,
+    f: impl FnOnce(&mut Inventory) -> R,
+) -> std::result::Result<R, &'static str> {
+    let mut shared = INVENTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let shared = &mut *shared;
+
+    if rescan || shared.inventory.is_none() {
+        shlog_debug!("Scanning for memflow plugins");
+        let mut inventory = Inventory::scan();
+        for dir in &shared.plugin_dirs {
+            add_plugin_dir(&mut inventory, dir)?;
+        }
+        shared.inventory = Some(inventory);
+    }
+    let inventory = shared.inventory.as_mut().unwrap();
+
+    if let Some(plugin_path) = plugin_path.filter(|path| !path.is_empty()) {
+        let dir = PathBuf::from(plugin_path);
+        if !shared.plugin_dirs.contains(&dir) {
+            add_plugin_dir(inventory, &dir)?;
+            shared.plugin_dirs.push(dir);
+        }
+    }
+
+    Ok(f(inventory))
+}
+
+// Helper function to build the sequence of plugin entries for a list of plugin names.
+// The inventory only exposes plugin names, so each entry is a table with a "name" field.
+fn plugins_to_seq(names: Vec<String>) -> AutoSeqVar {
+    let mut plugins = AutoSeqVar::new();
+    for name in names {
+        let name_var = Var::ephemeral_string(&name);
+        let mut entry = AutoTableVar::new();
+        entry.0.insert_fast_static("name", &name_var);
+        plugins.0.emplace_table(entry);
+    }
+    plugins
+}
+
+// Helper function to build a table listing the plugins known to an inventory
+pub fn inventory_to_table(inventory: &Inventory) -> AutoTableVar {
+    let connectors = plugins_to_seq(inventory.available_connectors());
+    let os_plugins = plugins_to_seq(inventory.available_os());
+
+    let mut table = AutoTableVar::new();
+    table.0.insert_fast_static("connectors", &connectors.0 .0);
+    table.0.insert_fast_static("os", &os_plugins.0 .0);
+    table
+}
+
+// Define the RescanPlugins Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.RescanPlugins",
+    "Rescans the memflow plugin directories and returns the available connectors and OS plugins."
+)]
+pub struct MemflowRescanPluginsShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Output table of plugins
+    plugins: AutoTableVar,
+}
+
+impl Default for MemflowRescanPluginsShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            plugins: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowRescanPluginsShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table of plugin lists
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.plugins = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Refresh the shared inventory in place so Memflow.Os picks up new plugins too
+        self.plugins = with_inventory(true, None, |inventory| inventory_to_table(inventory))?;
+
+        Ok(Some(self.plugins.0 .0))
+    }
+}
+
+// Define the Inventory Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.Inventory",
+    "Lists the installed memflow connectors and OS plugins."
+)]
+pub struct MemflowInventoryListShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("PluginPath", "Additional directory to load memflow plugins from.", [common_type::none, common_type::string])]
+    plugin_path: ClonedVar,
+
+    // Output table of plugins
+    plugins: AutoTableVar,
+}
+
+impl Default for MemflowInventoryListShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            plugin_path: ClonedVar::default(),
+            plugins: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowInventoryListShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table of plugin lists
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.plugins = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        shlog_debug!("Listing memflow plugins");
+
+        let plugin_path: &str = self.plugin_path.0.as_ref().try_into().unwrap_or("");
+        self.plugins = with_inventory(false, Some(plugin_path), |inventory| {
+            inventory_to_table(inventory)
+        })?;
+
+        Ok(Some(self.plugins.0 .0))
+    }
+}
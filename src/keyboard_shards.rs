@@ -0,0 +1,101 @@
+use crate::memflow_os_wrapper::MemflowOsWrapper;
+use crate::{MEMFLOW_OS_TYPE, MEMFLOW_OS_TYPE_VAR};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, Context, ExposedTypes, InstanceData, ParamVar, Type, Types, Var, BOOL_TYPES,
+    NONE_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Define the KeyboardState Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.KeyboardState",
+    "Checks whether a key is pressed on the target system, for OS plugins with keyboard support."
+)]
+pub struct MemflowKeyboardStateShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to query the keyboard of.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("KeyCode", "Virtual key code of the key to check.", [common_type::int, common_type::int_var])]
+    key_code: ParamVar,
+
+    #[shard_param("SetKey", "When set, marks the key as pressed (true) or released (false) before reading it back, where the plugin supports it.", [common_type::none, common_type::bool, common_type::bool_var])]
+    set_key: ParamVar,
+}
+
+impl Default for MemflowKeyboardStateShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            key_code: ParamVar::default(),
+            set_key: ParamVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowKeyboardStateShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &BOOL_TYPES // Outputs whether the key is down
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let key_code: i64 = self.key_code.get().as_ref().try_into()?;
+        let key_code = i32::try_from(key_code).map_err(|_| "KeyCode is out of range")?;
+
+        // The keyboard is an optional addon that only some OS plugins (e.g. win32) implement
+        let mut keyboard =
+            os.0.as_mut_impl_oskeyboard()
+                .ok_or("OS plugin does not support keyboard access.")?
+                .keyboard()
+                .map_err(|e| {
+                    shlog_error!("Failed to get keyboard: {}", e);
+                    "Failed to get keyboard from OS."
+                })?;
+
+        if !self.set_key.get().is_none() {
+            let down: bool = self.set_key.get().as_ref().try_into()?;
+            shlog_debug!("Setting key 0x{:x} down: {}", key_code, down);
+            keyboard.set_down(key_code, down);
+        }
+
+        Ok(Some(Var::new_bool(keyboard.is_down(key_code))))
+    }
+}
@@ -1,7 +1,8 @@
-use protection_filter::protection_filter_matches;
+use protection_filter::{page_type_to_rwx, protection_filter_matches};
 use shards::core::register_shard;
 use shards::ref_counted_object_type_impl;
 use shards::shard::Shard;
+use shards::shardsc::SHType_Int;
 use shards::types::{
     common_type,
     AutoSeqVar,
@@ -11,15 +12,19 @@ use shards::types::{
     ExposedTypes,
     InstanceData,
     ParamVar,
+    ShardsVar,
     TableVar,
     Type,
     Types,
     Var,
+    WireState,
     ANYS_TYPES,
     ANY_TABLE_TYPES,
     ANY_TYPES,
+    BOOL_TYPES,
     BYTES_TYPES,
     NONE_TYPES, // Input type
+    SHARDS_OR_NONE_TYPES,
 };
 use shards::{fourCharacterCode, shlog_debug, shlog_error};
 
@@ -28,7 +33,29 @@ use lazy_static::lazy_static;
 
 use memflow::prelude::v1::*;
 
+use cached_process::{process_handle, ProcessHandle};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use value_type::{Endian, ValueType};
+
+mod cached_process;
+mod disasm_shards;
+mod dump_shards;
+mod forensics_shards;
+mod inventory_shards;
+mod keyboard_shards;
+mod module_shards;
+mod os_shards;
+mod pe_image;
+mod physical_shards;
+mod pointer_shards;
+mod process_shards;
 mod protection_filter;
+mod region_hash;
+mod scan_modules_shard;
+mod signature_shards;
+mod string_shards;
+mod value_shards;
+mod value_type;
 mod xref_scanner;
 mod xref_shard;
 
@@ -40,6 +67,7 @@ lazy_static! {
     static ref MEMFLOW_PROCESS_TYPE_ID: i32 = fourCharacterCode(*b"PROC"); // Process Type ID
     static ref MEMFLOW_MODULE_TYPE_ID: i32 = fourCharacterCode(*b"MODL"); // Module Type ID
     static ref MEMFLOW_CACHED_PROCESS_TYPE_ID: i32 = fourCharacterCode(*b"CPRC"); // Cached Process Type ID
+    static ref MEMFLOW_CONNECTOR_TYPE_ID: i32 = fourCharacterCode(*b"CONN"); // Connector Type ID
 
     // The Shards Type descriptor for the Inventory object
     pub static ref MEMFLOW_OS_TYPE: Type = Type::object(*MEMFLOW_VENDOR_ID, *MEMFLOW_OS_TYPE_ID);
@@ -52,18 +80,80 @@ lazy_static! {
     pub static ref MEMFLOW_PROCESS_TYPE_VAR: Type = Type::context_variable(&[*MEMFLOW_PROCESS_TYPE]);
     pub static ref MEMFLOW_PROCESS_TYPES: Vec<Type> = vec![*MEMFLOW_PROCESS_TYPE];
 
+    // Cached process type definitions
+    pub static ref MEMFLOW_CACHED_PROCESS_TYPE: Type = Type::object(*MEMFLOW_VENDOR_ID, *MEMFLOW_CACHED_PROCESS_TYPE_ID);
+    pub static ref MEMFLOW_CACHED_PROCESS_TYPE_VAR: Type = Type::context_variable(&[*MEMFLOW_CACHED_PROCESS_TYPE]);
+    pub static ref MEMFLOW_CACHED_PROCESS_TYPES: Vec<Type> = vec![*MEMFLOW_CACHED_PROCESS_TYPE];
+
+    // Plain or cached process, accepted by the read and scan shards
+    pub static ref MEMFLOW_ANY_PROCESS_TYPES: Vec<Type> = vec![*MEMFLOW_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE];
+    // Plain or cached process, or an address when the process comes from a param
+    pub static ref MEMFLOW_ANY_PROCESS_OR_INT_TYPES: Vec<Type> = vec![*MEMFLOW_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE, common_type::int];
+
     // Module type definitions
     pub static ref MEMFLOW_MODULE_TYPE: Type = Type::object(*MEMFLOW_VENDOR_ID, *MEMFLOW_MODULE_TYPE_ID);
     pub static ref MEMFLOW_MODULE_TYPE_VAR: Type = Type::context_variable(&[*MEMFLOW_MODULE_TYPE]);
     pub static ref MEMFLOW_MODULE_TYPES: Vec<Type> = vec![*MEMFLOW_MODULE_TYPE];
+
+    // Connector type definitions
+    pub static ref MEMFLOW_CONNECTOR_TYPE: Type = Type::object(*MEMFLOW_VENDOR_ID, *MEMFLOW_CONNECTOR_TYPE_ID);
+    pub static ref MEMFLOW_CONNECTOR_TYPE_VAR: Type = Type::context_variable(&[*MEMFLOW_CONNECTOR_TYPE]);
+    pub static ref MEMFLOW_CONNECTOR_TYPES: Vec<Type> = vec![*MEMFLOW_CONNECTOR_TYPE];
+}
+
+// Addresses are unsigned, but Shards integers are signed 64-bit. An address with the high bit
+// set (such as a kernel address) therefore shows up as a negative int. Address params and
+// fields are always cast bit for bit with `as umem`, and addresses are only compared, sorted
+// and offset as `umem`, so such values round-trip unchanged. Scan and xref results also
+// carry the address as an unsigned hex string under "address_hex".
+
+// Helper function to format an address as unsigned hex, e.g. "0xffff800000000000"
+pub(crate) fn format_address_hex(address: umem) -> String {
+    format!("0x{:x}", address)
+}
+
+// Helper function to add an address to a result table, as a bit for bit int under
+// "address" and as unsigned hex under "address_hex"
+pub(crate) fn insert_address(entry: &mut AutoTableVar, address: umem) {
+    let address_var: Var = (address as i64).into();
+    let address_hex_var = Var::ephemeral_string(&format_address_hex(address));
+    entry.0.insert_fast_static("address", &address_var);
+    entry.0.insert_fast_static("address_hex", &address_hex_var);
+}
+
+// Helper function to read an address from an int or a hex string. Strings are always hex,
+// with or without a "0x" prefix, and may use underscores as separators ("0x7ff6_1234").
+pub fn parse_address(var: &Var) -> std::result::Result<umem, &'static str> {
+    if let Ok(text) = <&str>::try_from(var) {
+        let text = text.trim();
+        let digits = text
+            .strip_prefix("0x")
+            .or_else(|| text.strip_prefix("0X"))
+            .unwrap_or(text)
+            .replace('_', "");
+        return u64::from_str_radix(&digits, 16)
+            .map(|address| address as umem)
+            .map_err(|_| "Invalid address, expected an int or a hex string");
+    }
+    let address: i64 = var.try_into()?;
+    Ok(address as umem)
 }
 
 mod memflow_os_wrapper {
     use super::*;
 
-    // Wrapper struct to hold the OsInstanceArcBox
+    // How an OS instance was created, empty strings stand for unset values
+    #[derive(Clone, Default)]
+    pub struct OsOrigin {
+        pub connector: String,
+        pub os: String,
+        pub connector_args: String,
+        pub os_args: String,
+    }
+
+    // Wrapper struct to hold the OsInstanceArcBox and the plugins it was built with
     #[derive(Clone)] // Clone is needed because OsInstanceArcBox is Clone
-    pub struct MemflowOsWrapper(pub OsInstanceArcBox<'static>);
+    pub struct MemflowOsWrapper(pub OsInstanceArcBox<'static>, pub OsOrigin);
 
     ref_counted_object_type_impl!(MemflowOsWrapper);
 }
@@ -71,12 +161,27 @@ mod memflow_os_wrapper {
 pub mod memflow_process_wrapper {
     use super::*;
 
-    // Process wrapper struct to hold the ProcessInstance
-    pub struct MemflowProcessWrapper(pub ProcessInstanceArcBox<'static>);
+    // Process wrapper struct to hold the ProcessInstance and the number of writes made
+    // to it, so cached views of the process can tell when their pages may be stale
+    pub struct MemflowProcessWrapper(pub ProcessInstanceArcBox<'static>, pub u64);
 
     ref_counted_object_type_impl!(MemflowProcessWrapper);
 }
 
+mod memflow_connector_wrapper {
+    use super::*;
+
+    // Connector wrapper struct to hold the ConnectorInstanceArcBox, its plugin name and arguments
+    #[derive(Clone)]
+    pub struct MemflowConnectorWrapper(
+        pub ConnectorInstanceArcBox<'static>,
+        pub String,
+        pub String,
+    );
+
+    ref_counted_object_type_impl!(MemflowConnectorWrapper);
+}
+
 mod memflow_module_wrapper {
     use super::*;
 
@@ -102,6 +207,14 @@ struct MemflowOsShard {
     connector_name: ClonedVar,
     #[shard_param("Os", "The name of the OS plugin to use (e.g., 'win32', 'linux').", [common_type::string])]
     os_name: ClonedVar,
+    #[shard_param("ConnectorArgs", "Arguments passed to the connector (e.g., the VM name for 'qemu' or the file for 'coredump').", [common_type::none, common_type::string])]
+    connector_args: ClonedVar,
+    #[shard_param("OsArgs", "Arguments passed to the OS plugin.", [common_type::none, common_type::string])]
+    os_args: ClonedVar,
+    #[shard_param("RescanPlugins", "Rescan the plugin directories instead of reusing the cached plugin inventory.", [common_type::bool])]
+    rescan_plugins: ClonedVar,
+    #[shard_param("PluginPath", "Additional directory to load memflow plugins from.", [common_type::none, common_type::string])]
+    plugin_path: ClonedVar,
 
     // Store the output OS object
     output_os: ClonedVar,
@@ -114,6 +227,10 @@ impl Default for MemflowOsShard {
             required: ExposedTypes::new(),
             connector_name: ClonedVar::default(),
             os_name: default_os_name.into(),
+            connector_args: ClonedVar::default(),
+            os_args: ClonedVar::default(),
+            rescan_plugins: Var::new_bool(false).into(),
+            plugin_path: ClonedVar::default(),
             output_os: ClonedVar::default(),
         }
     }
@@ -156,40 +273,55 @@ impl Shard for MemflowOsShard {
         // Retrieve parameters
         let connector_name: &str = self.connector_name.0.as_ref().try_into().unwrap_or("");
         let os_name: &str = self.os_name.0.as_ref().try_into()?;
+        let connector_args_str: &str = self.connector_args.0.as_ref().try_into().unwrap_or("");
+        let os_args_str: &str = self.os_args.0.as_ref().try_into().unwrap_or("");
+        let rescan_plugins: bool = self.rescan_plugins.0.as_ref().try_into().unwrap_or(false);
+        let plugin_path: &str = self.plugin_path.0.as_ref().try_into().unwrap_or("");
 
         shlog_debug!(
-            "Attempting to create OS instance: connector='{}', os='{}'",
+            "Attempting to create OS instance: connector='{}' ('{}'), os='{}' ('{}')",
             connector_name,
-            os_name
+            connector_args_str,
+            os_name,
+            os_args_str
         );
 
-        // Create inventory and OS instance
-        let mut inventory = Inventory::scan();
-
-        if connector_name != "" {
-            let os = inventory
-                .builder()
-                .connector(connector_name)
-                .os(os_name)
-                .build()
-                .map_err(|e| {
-                    shlog_error!("Failed to create OS instance: {}", e);
-                    "Failed to create OS instance."
-                })?;
-
-            self.output_os =
-                Var::new_ref_counted(memflow_os_wrapper::MemflowOsWrapper(os), &MEMFLOW_OS_TYPE)
-                    .into();
-        } else {
-            let os = inventory.builder().os(os_name).build().map_err(|e| {
+        let connector_args = os_shards::parse_connector_args(connector_args_str)?;
+        let os_args = os_shards::parse_os_args(os_args_str)?;
+
+        // Create the OS instance from the cached inventory
+        let os =
+            inventory_shards::with_inventory(rescan_plugins, Some(plugin_path), |inventory| {
+                if connector_name != "" {
+                    inventory
+                        .builder()
+                        .connector(connector_name)
+                        .args(connector_args)
+                        .os(os_name)
+                        .args(os_args)
+                        .build()
+                } else {
+                    inventory.builder().os(os_name).args(os_args).build()
+                }
+            })?
+            .map_err(|e| {
                 shlog_error!("Failed to create OS instance: {}", e);
                 "Failed to create OS instance."
             })?;
 
-            self.output_os =
-                Var::new_ref_counted(memflow_os_wrapper::MemflowOsWrapper(os), &MEMFLOW_OS_TYPE)
-                    .into();
-        }
+        self.output_os = Var::new_ref_counted(
+            memflow_os_wrapper::MemflowOsWrapper(
+                os,
+                memflow_os_wrapper::OsOrigin {
+                    connector: connector_name.to_string(),
+                    os: os_name.to_string(),
+                    connector_args: connector_args_str.to_string(),
+                    os_args: os_args_str.to_string(),
+                },
+            ),
+            &MEMFLOW_OS_TYPE,
+        )
+        .into();
 
         Ok(Some(self.output_os.0))
     }
@@ -209,6 +341,9 @@ struct MemflowProcessListShard {
     #[shard_param("Os", "The Memflow OS instance to get process list from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
     os_instance: ParamVar,
 
+    #[shard_param("Handler", "Shards to run on each process table as it is enumerated (optional). When set, processes are streamed instead of collected and the output table stays empty. Stopping or returning from the handler ends the enumeration early.", SHARDS_OR_NONE_TYPES)]
+    handler: ShardsVar,
+
     // Output list of processes as tables
     process_list: AutoTableVar,
 }
@@ -227,6 +362,9 @@ struct MemflowKernelModuleListShard {
     #[shard_param("Os", "The Memflow OS instance to get kernel module list from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
     os_instance: ParamVar,
 
+    #[shard_param("Filter", "Only list modules whose name contains this text, ignoring case (optional).", [common_type::none, common_type::string, common_type::string_var])]
+    filter: ParamVar,
+
     // Output list of kernel modules as tables
     module_list: AutoSeqVar,
 }
@@ -236,6 +374,7 @@ impl Default for MemflowProcessListShard {
         Self {
             required: ExposedTypes::new(),
             os_instance: ParamVar::new_named("memflow/default-os"),
+            handler: ShardsVar::default(),
             process_list: AutoTableVar::new(),
         }
     }
@@ -246,6 +385,7 @@ impl Default for MemflowKernelModuleListShard {
         Self {
             required: ExposedTypes::new(),
             os_instance: ParamVar::new_named("memflow/default-os"),
+            filter: ParamVar::default(),
             module_list: AutoSeqVar::new(),
         }
     }
@@ -263,6 +403,17 @@ impl Shard for MemflowProcessListShard {
 
     fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
         self.compose_helper(data)?;
+
+        // The handler receives one process table at a time
+        if !self.handler.is_empty() {
+            let mut handler_data = *data;
+            handler_data.inputType = common_type::any_table;
+            self.handler.compose(&handler_data)?;
+
+            // Variables the handler uses have to come from the wire around us
+            shards::util::require_shards_contents(&mut self.required, &self.handler);
+        }
+
         Ok(self.output_types()[0])
     }
 
@@ -279,7 +430,7 @@ impl Shard for MemflowProcessListShard {
 
     fn activate(
         &mut self,
-        _context: &Context,
+        context: &Context,
         _input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
         // Get the OS instance from parameter
@@ -292,6 +443,40 @@ impl Shard for MemflowProcessListShard {
             )?
         };
 
+        self.process_list.0.clear();
+
+        if !self.handler.is_empty() {
+            shlog_debug!("Streaming process list from OS instance");
+
+            let handler = &mut self.handler;
+            let mut handler_error = false;
+            let mut callback = |process: ProcessInfo| {
+                let process_table = process_info_to_table(&process);
+                let mut output = Var::default();
+                match handler.activate(context, &process_table.0 .0, &mut output) {
+                    WireState::Continue => true,
+                    WireState::Error => {
+                        handler_error = true;
+                        false
+                    }
+                    // Stop, Return, Restart and Rebase all end the enumeration early
+                    _ => false,
+                }
+            };
+
+            os.0.process_info_list_callback((&mut callback).into())
+                .map_err(|e| {
+                    shlog_error!("Failed to enumerate processes: {}", e);
+                    "Failed to enumerate processes."
+                })?;
+
+            if handler_error {
+                return Err("Process list handler failed.");
+            }
+
+            return Ok(Some(self.process_list.0 .0));
+        }
+
         shlog_debug!("Getting process list from OS instance");
 
         let process_list = os.0.process_info_list().map_err(|e| {
@@ -299,27 +484,9 @@ impl Shard for MemflowProcessListShard {
             "Failed to get process list."
         })?;
 
-        self.process_list.0.clear();
-
         for process in process_list {
-            let mut process_table = AutoTableVar::new();
-
-            let name = process.name.to_string();
-            let name_str = Var::ephemeral_string(&name);
-            process_table.0.insert_fast_static("name", &name_str);
-
             let pid: Var = process.pid.into();
-
-            let path = process.path.to_string();
-            let path_str = Var::ephemeral_string(&path);
-            process_table.0.insert_fast_static("path", &path_str);
-
-            let command_line = process.command_line.to_string();
-            let command_line_str = Var::ephemeral_string(&command_line);
-            process_table
-                .0
-                .insert_fast_static("command_line", &command_line_str);
-
+            let process_table = process_info_to_table(&process);
             self.process_list.0.emplace_table(pid, process_table);
         }
 
@@ -327,6 +494,52 @@ impl Shard for MemflowProcessListShard {
     }
 }
 
+// Helper function to build the table describing a single process
+fn process_info_to_table(process: &ProcessInfo) -> AutoTableVar {
+    let mut process_table = AutoTableVar::new();
+
+    let pid: Var = process.pid.into();
+    process_table.0.insert_fast_static("pid", &pid);
+
+    let ppid: Var = process.ppid.into();
+    process_table.0.insert_fast_static("ppid", &ppid);
+
+    let state = Var::ephemeral_string(&format!("{:?}", process.state));
+    process_table.0.insert_fast_static("state", &state);
+
+    let name = process.name.to_string();
+    let name_str = Var::ephemeral_string(&name);
+    process_table.0.insert_fast_static("name", &name_str);
+
+    let path = process.path.to_string();
+    let path_str = Var::ephemeral_string(&path);
+    process_table.0.insert_fast_static("path", &path_str);
+
+    let command_line = process.command_line.to_string();
+    let command_line_str = Var::ephemeral_string(&command_line);
+    process_table
+        .0
+        .insert_fast_static("command_line", &command_line_str);
+
+    let sys_arch = Var::ephemeral_string(&format!("{:?}", process.sys_arch));
+    process_table.0.insert_fast_static("sys_arch", &sys_arch);
+
+    let proc_arch = Var::ephemeral_string(&format!("{:?}", process.proc_arch));
+    process_table.0.insert_fast_static("proc_arch", &proc_arch);
+
+    // Kernel object address and page table bases, mostly of interest for forensics
+    let address: Var = (process.address.to_umem() as i64).into();
+    process_table.0.insert_fast_static("address", &address);
+
+    let dtb1: Var = (process.dtb1.to_umem() as i64).into();
+    process_table.0.insert_fast_static("dtb1", &dtb1);
+
+    let dtb2: Var = (process.dtb2.to_umem() as i64).into();
+    process_table.0.insert_fast_static("dtb2", &dtb2);
+
+    process_table
+}
+
 // Define the Process Shard
 #[derive(shards::shard)]
 #[shard_info(
@@ -368,6 +581,9 @@ struct MemflowModuleInfoShard {
     #[shard_param("Name", "Module name to search for.", [common_type::string, common_type::string_var])]
     module_name: ParamVar,
 
+    #[shard_param("CaseSensitive", "Whether the name must match case exactly (default: false).", [common_type::bool, common_type::bool_var])]
+    case_sensitive: ParamVar,
+
     // Store the output Module object
     output_module: ClonedVar,
 }
@@ -390,6 +606,7 @@ impl Default for MemflowModuleInfoShard {
             required: ExposedTypes::new(),
             process_instance: ParamVar::default(),
             module_name: ParamVar::default(),
+            case_sensitive: ParamVar::new(false.into()),
             output_module: ClonedVar::default(),
         }
     }
@@ -437,14 +654,33 @@ impl Shard for MemflowModuleInfoShard {
 
         // Get module name parameter
         let module_name: &str = self.module_name.get().as_ref().try_into()?;
+        let case_sensitive: bool = self.case_sensitive.get().as_ref().try_into()?;
 
         shlog_debug!("Searching for module by name: {}", module_name);
 
-        // Find module by name
-        let module_info = process.0.module_by_name(module_name).map_err(|e| {
-            shlog_error!("Failed to find module by name '{}': {}", module_name, e);
-            "Module not found by name."
-        })?;
+        // Find module by name, falling back to a case-insensitive search of the module list
+        let module_info = match process.0.module_by_name(module_name) {
+            Ok(module_info) => module_info,
+            Err(e) if case_sensitive => {
+                shlog_error!("Failed to find module by name '{}': {}", module_name, e);
+                return Err("Module not found by name.");
+            }
+            Err(_) => {
+                let modules = process.0.module_list().map_err(|e| {
+                    shlog_error!("Failed to get process module list: {}", e);
+                    "Failed to get process module list."
+                })?;
+                let module_info = modules
+                    .into_iter()
+                    .find(|module| module.name.eq_ignore_ascii_case(module_name))
+                    .ok_or_else(|| {
+                        shlog_error!("Failed to find module by name '{}'", module_name);
+                        "Module not found by name."
+                    })?;
+                shlog_debug!("Module '{}' matched as '{}'", module_name, module_info.name);
+                module_info
+            }
+        };
 
         // Create and return the module object
         self.output_module = Var::new_ref_counted(
@@ -524,7 +760,7 @@ impl Shard for MemflowProcessShard {
 
         // Create and return the process object
         self.output_process = Var::new_ref_counted(
-            memflow_process_wrapper::MemflowProcessWrapper(process_instance),
+            memflow_process_wrapper::MemflowProcessWrapper(process_instance, 0),
             &MEMFLOW_PROCESS_TYPE,
         )
         .into();
@@ -532,6 +768,15 @@ impl Shard for MemflowProcessShard {
     }
 }
 
+// Helper function to get the lowercased Filter of the module list shards, empty when unset
+fn module_name_filter(filter: &ParamVar) -> std::result::Result<String, &'static str> {
+    if filter.get().is_none() {
+        return Ok(String::new());
+    }
+    let filter: &str = filter.get().as_ref().try_into()?;
+    Ok(filter.to_lowercase())
+}
+
 // Define the MemMap Shard
 #[derive(shards::shard)]
 #[shard_info(
@@ -543,7 +788,7 @@ struct MemflowMemMapShard {
     required: ExposedTypes,
 
     // Parameters
-    #[shard_param("GapSize", "Size of gaps to ignore between memory regions (in bytes).", [common_type::int, common_type::int_var])]
+    #[shard_param("GapSize", "Regions separated by at most this many unmapped bytes are merged into one (default: 0, don't coalesce).", [common_type::none, common_type::int, common_type::int_var])]
     gap_size: ParamVar,
 
     // Output memory maps as table
@@ -563,7 +808,7 @@ impl Default for MemflowMemMapShard {
 #[shards::shard_impl]
 impl Shard for MemflowMemMapShard {
     fn input_types(&mut self) -> &Types {
-        &MEMFLOW_PROCESS_TYPES // Takes process as input
+        &MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
     }
 
     fn output_types(&mut self) -> &Types {
@@ -591,16 +836,18 @@ impl Shard for MemflowMemMapShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
 
-        // Get gap size parameter
-        let gap_size: i64 = self.gap_size.get().as_ref().try_into()?;
+        // Get gap size parameter, none keeps the regions apart
+        let gap_size: i64 = if self.gap_size.get().is_none() {
+            0
+        } else {
+            self.gap_size.get().as_ref().try_into()?
+        };
+        if gap_size < 0 {
+            return Err("GapSize must not be negative");
+        }
 
         shlog_debug!(
             "Getting memory maps for process with gap size: {}",
@@ -608,21 +855,32 @@ impl Shard for MemflowMemMapShard {
         );
 
         // Get memory maps
-        let maps = process.0.mapped_mem_vec(gap_size);
+        let maps = process.process().mapped_mem_vec(gap_size);
 
         self.mem_maps.0.clear();
 
         // Build output table with memory maps
         for map in maps {
-            let address: Var = map.0.to_umem().into();
-            let size: Var = map.1.to_umem().into();
+            let address: Var = (map.0.to_umem() as i64).into();
+            let size: Var = (map.1.to_umem() as i64).into();
             let prot: Var = Var::ephemeral_string(&format!("{:?}", map.2));
 
+            // Structured permissions, derived from the same rwx string the filters use
+            let rwx_string = page_type_to_rwx(map.2);
+            let rwx = Var::ephemeral_string(&rwx_string);
+            let read = Var::new_bool(rwx_string.contains('r'));
+            let write = Var::new_bool(rwx_string.contains('w'));
+            let execute = Var::new_bool(rwx_string.contains('x'));
+
             // Insert into table
             let mut tab = AutoTableVar::new();
             tab.0.insert_fast_static("address", &address);
             tab.0.insert_fast_static("size", &size);
             tab.0.insert_fast_static("protection", &prot);
+            tab.0.insert_fast_static("rwx", &rwx);
+            tab.0.insert_fast_static("read", &read);
+            tab.0.insert_fast_static("write", &write);
+            tab.0.insert_fast_static("execute", &execute);
             self.mem_maps.0.emplace_table(tab);
         }
 
@@ -677,16 +935,22 @@ impl Shard for MemflowKernelModuleListShard {
             shlog_error!("Failed to get kernel module list: {}", e);
             "Failed to get kernel module list."
         })?;
+        let filter = module_name_filter(&self.filter)?;
 
         self.module_list.0.clear();
 
         for module in module_list {
+            if !filter.is_empty() && !module.name.to_lowercase().contains(&filter) {
+                continue;
+            }
+
             // Create column values for module information
-            let address: Var = module.address.to_umem().into();
-            let base: Var = module.base.to_umem().into();
-            let size: Var = module.size.into();
+            let address: Var = (module.address.to_umem() as i64).into();
+            let base: Var = (module.base.to_umem() as i64).into();
+            let size: Var = (module.size as i64).into();
             let name = Var::ephemeral_string(&module.name);
             let path = Var::ephemeral_string(&module.path);
+            let arch = Var::ephemeral_string(&format!("{:?}", module.arch));
 
             // Insert into table
             let mut tab = AutoTableVar::new();
@@ -695,6 +959,13 @@ impl Shard for MemflowKernelModuleListShard {
             tab.0.insert_fast_static("size", &size);
             tab.0.insert_fast_static("name", &name);
             tab.0.insert_fast_static("path", &path);
+            tab.0.insert_fast_static("arch", &arch);
+
+            // Kernel modules usually have no parent process, only emit it when it is set
+            if module.parent_process.is_valid() && !module.parent_process.is_null() {
+                let parent_process: Var = (module.parent_process.to_umem() as i64).into();
+                tab.0.insert_fast_static("parent_process", &parent_process);
+            }
 
             self.module_list.0.emplace_table(tab);
         }
@@ -707,19 +978,28 @@ impl Shard for MemflowKernelModuleListShard {
 #[derive(shards::shard)]
 #[shard_info(
     "Memflow.ReadMemory",
-    "Reads memory from a specific address in a process."
+    "Reads memory from a specific address in a process. Either the process is the input and the address comes from Address, or the process comes from Process and the address is the input."
 )]
 struct MemflowReadMemoryShard {
     #[shard_required]
     required: ExposedTypes,
 
     // Parameters
-    #[shard_param("Address", "Memory address to read from.", [common_type::int, common_type::int_var])]
+    #[shard_param("Address", "Memory address to read from, when the input is the process.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
     address: ParamVar,
 
+    #[shard_param("Process", "Process to read from, when the input is the address.", [common_type::none, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
+    process: ParamVar,
+
     #[shard_param("Size", "Number of bytes to read.", [common_type::int, common_type::int_var])]
     size: ParamVar,
 
+    #[shard_param("Volatile", "Perform a single, naturally aligned 1, 2, 4 or 8 byte access for memory-mapped I/O (default: false).", [common_type::bool, common_type::bool_var])]
+    volatile: ParamVar,
+
+    // Whether the address is the input (and the process the Process param), set in compose
+    address_from_input: bool,
+
     // Output buffer
     output_buffer: ClonedVar,
 }
@@ -729,7 +1009,10 @@ impl Default for MemflowReadMemoryShard {
         Self {
             required: ExposedTypes::new(),
             address: ParamVar::new(0.into()),
+            process: ParamVar::default(),
             size: ParamVar::new(1.into()),
+            volatile: ParamVar::new(false.into()),
+            address_from_input: false,
             output_buffer: ClonedVar::default(),
         }
     }
@@ -738,7 +1021,7 @@ impl Default for MemflowReadMemoryShard {
 #[shards::shard_impl]
 impl Shard for MemflowReadMemoryShard {
     fn input_types(&mut self) -> &Types {
-        &MEMFLOW_PROCESS_TYPES // Takes process as input
+        &MEMFLOW_ANY_PROCESS_OR_INT_TYPES // Takes a process, or an address with Process set
     }
 
     fn output_types(&mut self) -> &Types {
@@ -747,6 +1030,7 @@ impl Shard for MemflowReadMemoryShard {
 
     fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
         self.compose_helper(data)?;
+        self.address_from_input = address_input_mode(data, &self.process)?;
         Ok(self.output_types()[0])
     }
 
@@ -766,16 +1050,11 @@ impl Shard for MemflowReadMemoryShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance and the address, see compose
+        let (mut process, address_umem) =
+            process_and_address(self.address_from_input, input, &self.process, &self.address)?;
 
-        // Get address and size parameters
-        let address: i64 = self.address.get().as_ref().try_into()?;
+        // Get size parameter
         let size: i64 = self.size.get().as_ref().try_into()?;
 
         if size <= 0 {
@@ -783,21 +1062,20 @@ impl Shard for MemflowReadMemoryShard {
         }
 
         let size_usize = size as usize;
-        let address_umem = address as umem;
+
+        let volatile: bool = self.volatile.get().as_ref().try_into()?;
+        if volatile {
+            check_volatile_access(address_umem, size_usize)?;
+        }
 
         shlog_debug!(
-            "Reading memory at address: 0x{:x}, size: {} bytes",
+            "Reading memory at address: 0x{:x}, size: {} bytes, volatile: {}",
             address_umem,
-            size_usize
+            size_usize,
+            volatile
         );
 
-        // Create buffer to hold the read data
-        let mut buffer = vec![0u8; size_usize];
-
-        // Read memory into buffer
-        process
-            .0
-            .read_raw_into(Address::from(address_umem), &mut buffer)
+        let buffer = read_process_memory(&mut process, address_umem, size_usize, volatile)
             .map_err(|e| {
                 shlog_error!("Failed to read memory: {}", e);
                 "Failed to read memory from process."
@@ -808,6 +1086,215 @@ impl Shard for MemflowReadMemoryShard {
     }
 }
 
+// Helper function for the shards that take either the process as input and the address
+// from Address, or the address as input and the process from Process. Returns whether
+// the address is the input.
+fn address_input_mode(
+    data: &InstanceData,
+    process: &ParamVar,
+) -> std::result::Result<bool, &'static str> {
+    // An int input is the address, anything else is the process
+    let address_from_input = data.inputType.basicType == SHType_Int;
+    if address_from_input && process.get_param().is_none() {
+        return Err("Process is required when the address is the input");
+    }
+    if !address_from_input && !process.get_param().is_none() {
+        return Err("Process can only be set when the address is the input");
+    }
+    Ok(address_from_input)
+}
+
+// Helper function to get the process and the address in the mode chosen at compose time
+fn process_and_address<'a>(
+    address_from_input: bool,
+    input: &'a Var,
+    process: &'a ParamVar,
+    address: &ParamVar,
+) -> std::result::Result<(ProcessHandle<'a>, umem), &'static str> {
+    if address_from_input {
+        Ok((process_handle(process.get())?, parse_address(input)?))
+    } else {
+        Ok((process_handle(input)?, parse_address(address.get())?))
+    }
+}
+
+// Helper function to read memory for ReadMemory and TryReadMemory.
+// Volatile reads always go to the process and never to a cache.
+fn read_process_memory(
+    process: &mut ProcessHandle,
+    address: umem,
+    size: usize,
+    volatile: bool,
+) -> Result<Vec<u8>> {
+    let mut buffer = vec![0u8; size];
+    if volatile {
+        process
+            .process()
+            .read_raw_into(Address::from(address), &mut buffer)?;
+    } else {
+        process.read_raw_into(address, &mut buffer)?;
+    }
+    Ok(buffer)
+}
+
+// Define the TryReadMemory Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.TryReadMemory",
+    "Reads memory like Memflow.ReadMemory, but reports a failed read in its output instead of failing. Either the process is the input and the address comes from Address, or the process comes from Process and the address is the input."
+)]
+struct MemflowTryReadMemoryShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Memory address to read from, when the input is the process.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Process", "Process to read from, when the input is the address.", [common_type::none, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
+    process: ParamVar,
+
+    #[shard_param("Size", "Number of bytes to read.", [common_type::int, common_type::int_var])]
+    size: ParamVar,
+
+    #[shard_param("Volatile", "Perform a single, naturally aligned 1, 2, 4 or 8 byte access for memory-mapped I/O (default: false).", [common_type::bool, common_type::bool_var])]
+    volatile: ParamVar,
+
+    // Whether the address is the input (and the process the Process param), set in compose
+    address_from_input: bool,
+
+    // Output table with the outcome of the read
+    result: AutoTableVar,
+}
+
+impl Default for MemflowTryReadMemoryShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            process: ParamVar::default(),
+            size: ParamVar::new(1.into()),
+            volatile: ParamVar::new(false.into()),
+            address_from_input: false,
+            result: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowTryReadMemoryShard {
+    fn input_types(&mut self) -> &Types {
+        &MEMFLOW_ANY_PROCESS_OR_INT_TYPES // Takes a process, or an address with Process set
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {ok, data, error}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        self.address_from_input = address_input_mode(data, &self.process)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.result = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance and the address, see compose.
+        // Invalid params are still errors.
+        let (mut process, address_umem) =
+            process_and_address(self.address_from_input, input, &self.process, &self.address)?;
+        let size: i64 = self.size.get().as_ref().try_into()?;
+
+        if size <= 0 {
+            return Err("Size must be greater than 0");
+        }
+
+        let size_usize = size as usize;
+
+        let volatile: bool = self.volatile.get().as_ref().try_into()?;
+        if volatile {
+            check_volatile_access(address_umem, size_usize)?;
+        }
+
+        shlog_debug!(
+            "Trying to read memory at address: 0x{:x}, size: {} bytes, volatile: {}",
+            address_umem,
+            size_usize,
+            volatile
+        );
+
+        // Only the read itself is allowed to fail
+        let (ok, buffer, error) =
+            match read_process_memory(&mut process, address_umem, size_usize, volatile) {
+                Ok(buffer) => (true, buffer, String::new()),
+                Err(e) => {
+                    shlog_debug!("Failed to read memory at 0x{:x}: {}", address_umem, e);
+                    (false, Vec::new(), e.to_string())
+                }
+            };
+
+        let ok_var: Var = ok.into();
+        let data_var = Var::ephemeral_slice(buffer.as_slice());
+        let error_var = Var::ephemeral_string(&error);
+
+        self.result.0.clear();
+        self.result.0.insert_fast_static("ok", &ok_var);
+        self.result.0.insert_fast_static("data", &data_var);
+        self.result.0.insert_fast_static("error", &error_var);
+
+        Ok(Some(self.result.0 .0))
+    }
+}
+
+// Helper function to validate a volatile (memory-mapped I/O) access.
+// Volatile accesses must be a single 1, 2, 4 or 8 byte access that is naturally
+// aligned, so they never straddle a page and are never split by the memory view.
+// They are always issued on their own, bypassing the batcher.
+fn check_volatile_access(address: umem, size: usize) -> std::result::Result<(), &'static str> {
+    if !matches!(size, 1 | 2 | 4 | 8) {
+        return Err("Volatile access size must be 1, 2, 4 or 8 bytes");
+    }
+    if address % size as umem != 0 {
+        return Err("Volatile access must be naturally aligned");
+    }
+    Ok(())
+}
+
+// Helper function to get the pointer width (in bytes) of a process
+fn process_pointer_size(process: &ProcessInstanceArcBox<'static>) -> usize {
+    process.info().proc_arch.into_obj().size_addr()
+}
+
+// Helper function to read a 4 or 8 byte little-endian pointer from a plain or cached process
+fn read_pointer(
+    process: &mut ProcessHandle,
+    address: umem,
+    pointer_size: usize,
+) -> std::result::Result<umem, &'static str> {
+    let mut buffer = [0u8; 8];
+    process
+        .read_raw_into(address, &mut buffer[..pointer_size])
+        .map_err(|e| {
+            shlog_debug!("Failed to read pointer at 0x{:x}: {}", address, e);
+            "Failed to read pointer from process."
+        })?;
+    Ok(u64::from_le_bytes(buffer) as umem)
+}
+
 // Define the BatchReadMemory Shard for more efficient reading
 #[derive(shards::shard)]
 #[shard_info(
@@ -819,11 +1306,20 @@ struct MemflowBatchReadMemoryShard {
     required: ExposedTypes,
 
     // Parameters - table of addresses and sizes
-    #[shard_param("Reads", "Table of memory reads with 'address' and 'size' fields.", [common_type::any_table, common_type::any_table_var])]
+    #[shard_param("Reads", "Table of memory reads with 'address' and 'size' fields. Results are returned in a table under the same keys.", [common_type::none, common_type::any_table, common_type::any_table_var])]
     reads: ParamVar,
 
+    #[shard_param("ReadList", "Sequence of memory reads with 'address' and 'size' fields, used instead of Reads. Results are returned as a sequence of {ok, data} tables in the same order, a failed read only fails its own entry.", [common_type::none, common_type::anys, common_type::anys_var])]
+    read_list: ParamVar,
+
+    #[shard_param("Volatile", "Issue every read as its own single-width access instead of batching them, for memory-mapped I/O (default: false).", [common_type::bool, common_type::bool_var])]
+    volatile: ParamVar,
+
     // Output table of results
     output_results: AutoTableVar,
+
+    // Output sequence of results when reads come from ReadList
+    output_list: AutoSeqVar,
 }
 
 impl Default for MemflowBatchReadMemoryShard {
@@ -831,24 +1327,66 @@ impl Default for MemflowBatchReadMemoryShard {
         Self {
             required: ExposedTypes::new(),
             reads: ParamVar::default(),
+            read_list: ParamVar::default(),
+            volatile: ParamVar::new(false.into()),
             output_results: AutoTableVar::new(),
+            output_list: AutoSeqVar::new(),
         }
     }
 }
 
+// Helper function to get the address and size of a batch read entry
+fn batch_read_entry(
+    entry: &Var,
+    volatile: bool,
+) -> std::result::Result<(umem, usize), &'static str> {
+    let read_table = entry.as_table()?;
+
+    // Get address and size from the table
+    let address_var = read_table
+        .get(Var::ephemeral_string("address"))
+        .ok_or("Missing 'address' field in read entry")?;
+    let size_var = read_table
+        .get(Var::ephemeral_string("size"))
+        .ok_or("Missing 'size' field in read entry")?;
+
+    let address_umem = parse_address(&address_var)?;
+    let size: i64 = size_var.as_ref().try_into()?;
+
+    if size <= 0 {
+        return Err("Size must be greater than 0");
+    }
+
+    let size_usize = size as usize;
+
+    if volatile {
+        check_volatile_access(address_umem, size_usize)?;
+    }
+
+    Ok((address_umem, size_usize))
+}
+
 #[shards::shard_impl]
 impl Shard for MemflowBatchReadMemoryShard {
     fn input_types(&mut self) -> &Types {
-        &MEMFLOW_PROCESS_TYPES // Takes process as input
+        &MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
     }
 
     fn output_types(&mut self) -> &Types {
-        &ANY_TABLE_TYPES // Outputs a table of results
+        &ANY_TYPES // Outputs a table of results, or a sequence with ReadList
     }
 
     fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
         self.compose_helper(data)?;
-        Ok(self.output_types()[0])
+
+        let has_reads = !self.reads.get_param().is_none();
+        let has_read_list = !self.read_list.get_param().is_none();
+        match (has_reads, has_read_list) {
+            (true, false) => Ok(common_type::any_table),
+            (false, true) => Ok(common_type::anys),
+            (false, false) => Err("Either Reads or ReadList must be provided"),
+            (true, true) => Err("Reads and ReadList can not be used together"),
+        }
     }
 
     fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
@@ -857,10 +1395,8 @@ impl Shard for MemflowBatchReadMemoryShard {
     }
 
     fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
-        if self.reads.is_none() {
-            return Err("Missing 'reads' parameter");
-        }
         self.output_results = AutoTableVar::new();
+        self.output_list = AutoSeqVar::new();
         self.cleanup_helper(ctx)?;
         Ok(())
     }
@@ -870,17 +1406,11 @@ impl Shard for MemflowBatchReadMemoryShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
 
-        // Get reads table
-        let reads_var = self.reads.get();
-        let reads_table = reads_var.as_table()?;
+        let volatile: bool = self.volatile.get().as_ref().try_into()?;
+        let ordered = !self.read_list.get().is_none();
 
         shlog_debug!("Performing batch memory read operation");
 
@@ -889,44 +1419,43 @@ impl Shard for MemflowBatchReadMemoryShard {
             key: Var,
             address: umem,
             buffer: Vec<u8>,
+            ok: bool,
         }
 
         let mut read_ops = Vec::new();
 
         // Collect all read operations first
-        for (key, _) in reads_table.iter() {
-            let read_entry = reads_table.get(key).unwrap();
-            let read_table = read_entry.as_table()?;
-
-            // Get address and size from the table
-            let address_var = read_table
-                .get(Var::ephemeral_string("address"))
-                .ok_or("Missing 'address' field in read entry")?;
-            let size_var = read_table
-                .get(Var::ephemeral_string("size"))
-                .ok_or("Missing 'size' field in read entry")?;
-
-            let address: i64 = address_var.as_ref().try_into()?;
-            let size: i64 = size_var.as_ref().try_into()?;
-
-            if size <= 0 {
-                return Err("Size must be greater than 0");
+        if ordered {
+            for entry in self.read_list.get().as_seq()?.iter() {
+                let (address, size) = batch_read_entry(&entry, volatile)?;
+                read_ops.push(ReadOp {
+                    key: Var::default(),
+                    address,
+                    buffer: vec![0u8; size],
+                    ok: false,
+                });
+            }
+        } else {
+            let reads_var = self.reads.get();
+            let reads_table = reads_var.as_table()?;
+            for (key, _) in reads_table.iter() {
+                let read_entry = reads_table.get(key).unwrap();
+                let (address, size) = batch_read_entry(&read_entry, volatile)?;
+
+                // Create read operation
+                read_ops.push(ReadOp {
+                    key,
+                    address,
+                    buffer: vec![0u8; size],
+                    ok: false,
+                });
             }
-
-            let size_usize = size as usize;
-            let address_umem = address as umem;
-
-            // Create read operation
-            read_ops.push(ReadOp {
-                key,
-                address: address_umem,
-                buffer: vec![0u8; size_usize],
-            });
         }
 
-        // Now perform the batch read
-        {
-            let mut batcher = process.0.batcher();
+        // Batch the reads unless they must be issued one by one
+        let mut batched = false;
+        if !volatile && !process.is_cached() {
+            let mut batcher = process.process().batcher();
 
             // Set up all read operations in the batcher
             for op in &mut read_ops {
@@ -934,15 +1463,67 @@ impl Shard for MemflowBatchReadMemoryShard {
             }
 
             // Execute all read operations in batch
-            batcher.commit_rw().map_err(|e| {
-                shlog_error!("Failed to execute batch memory read: {}", e);
-                "Failed to read memory from process."
-            })?;
+            match batcher.commit_rw() {
+                Ok(_) => batched = true,
+                Err(e) if ordered => {
+                    // Retry each read on its own so only the broken ones fail
+                    shlog_debug!("Batch memory read failed, reading one by one: {}", e);
+                }
+                Err(e) => {
+                    shlog_error!("Failed to execute batch memory read: {}", e);
+                    return Err("Failed to read memory from process.");
+                }
+            }
         }
 
-        self.output_results.0.clear();
+        if batched {
+            for op in &mut read_ops {
+                op.ok = true;
+            }
+        } else {
+            // Volatile reads bypass the batcher so each one is issued exactly once, in order,
+            // and cached reads are served page by page from the cache
+            for op in &mut read_ops {
+                let result = if volatile {
+                    process
+                        .process()
+                        .read_raw_into(Address::from(op.address), &mut op.buffer)
+                } else {
+                    process.read_raw_into(op.address, &mut op.buffer)
+                };
+                match result {
+                    Ok(_) => op.ok = true,
+                    Err(e) if ordered => {
+                        shlog_debug!("Failed to read memory at 0x{:x}: {}", op.address, e);
+                    }
+                    Err(e) => {
+                        shlog_error!("Failed to read memory at 0x{:x}: {}", op.address, e);
+                        return Err("Failed to read memory from process.");
+                    }
+                }
+            }
+        }
 
         // Process results
+        if ordered {
+            self.output_list.0.clear();
+            for op in read_ops {
+                let ok_var: Var = op.ok.into();
+                let data_var = if op.ok {
+                    Var::ephemeral_slice(op.buffer.as_slice())
+                } else {
+                    Var::ephemeral_slice(&[])
+                };
+
+                let mut result_entry = AutoTableVar::new();
+                result_entry.0.insert_fast_static("ok", &ok_var);
+                result_entry.0.insert_fast_static("data", &data_var);
+                self.output_list.0.emplace_table(result_entry);
+            }
+            return Ok(Some(self.output_list.0 .0));
+        }
+
+        self.output_results.0.clear();
         for op in read_ops {
             let bytes = Var::ephemeral_slice(op.buffer.as_slice());
             // Add to results table
@@ -963,6 +1544,10 @@ struct MemflowProcessModuleListShard {
     #[shard_required]
     required: ExposedTypes,
 
+    // Parameters
+    #[shard_param("Filter", "Only list modules whose name contains this text, ignoring case (optional).", [common_type::none, common_type::string, common_type::string_var])]
+    filter: ParamVar,
+
     // Output list of modules as sequence of tables
     module_list: AutoSeqVar,
 }
@@ -971,6 +1556,7 @@ impl Default for MemflowProcessModuleListShard {
     fn default() -> Self {
         Self {
             required: ExposedTypes::new(),
+            filter: ParamVar::default(),
             module_list: AutoSeqVar::new(),
         }
     }
@@ -979,7 +1565,7 @@ impl Default for MemflowProcessModuleListShard {
 #[shards::shard_impl]
 impl Shard for MemflowProcessModuleListShard {
     fn input_types(&mut self) -> &Types {
-        &MEMFLOW_PROCESS_TYPES // Takes process as input
+        &MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
     }
 
     fn output_types(&mut self) -> &Types {
@@ -1007,27 +1593,27 @@ impl Shard for MemflowProcessModuleListShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
 
         shlog_debug!("Getting module list from process");
 
-        let module_list = process.0.module_list().map_err(|e| {
+        let module_list = process.process().module_list().map_err(|e| {
             shlog_error!("Failed to get process module list: {}", e);
             "Failed to get process module list."
         })?;
+        let filter = module_name_filter(&self.filter)?;
 
         self.module_list.0.clear();
 
         for module in module_list {
+            if !filter.is_empty() && !module.name.to_lowercase().contains(&filter) {
+                continue;
+            }
+
             // Create column values for module information
-            let base: Var = module.base.to_umem().into();
-            let size: Var = module.size.into();
+            let base: Var = (module.base.to_umem() as i64).into();
+            let size: Var = (module.size as i64).into();
             let name = Var::ephemeral_string(&module.name);
             let path = Var::ephemeral_string(&module.path);
             let arch = Var::ephemeral_string(&format!("{:?}", module.arch));
@@ -1058,14 +1644,23 @@ struct MemflowWriteMemoryShard {
     required: ExposedTypes,
 
     // Parameters
-    #[shard_param("Address", "Memory address to write to.", [common_type::int, common_type::int_var])]
+    #[shard_param("Address", "Memory address to write to.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
     address: ParamVar,
 
-    #[shard_param("Process", "The Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR])]
+    #[shard_param("Process", "The plain or cached Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
     process_instance: ParamVar,
 
+    #[shard_param("Volatile", "Perform a single, naturally aligned 1, 2, 4 or 8 byte access for memory-mapped I/O (default: false).", [common_type::bool, common_type::bool_var])]
+    volatile: ParamVar,
+
+    #[shard_param("Verify", "Read the range back after writing and fail with the first differing offset if it does not match (default: false).", [common_type::bool, common_type::bool_var])]
+    verify: ParamVar,
+
     // Output status
     output_status: ClonedVar,
+
+    // Last error, kept here so it can be returned by reference
+    error_message: String,
 }
 
 impl Default for MemflowWriteMemoryShard {
@@ -1074,7 +1669,10 @@ impl Default for MemflowWriteMemoryShard {
             required: ExposedTypes::new(),
             address: ParamVar::new(0.into()),
             process_instance: ParamVar::default(),
+            volatile: ParamVar::new(false.into()),
+            verify: ParamVar::new(false.into()),
             output_status: ClonedVar::default(),
+            error_message: String::new(),
         }
     }
 }
@@ -1110,18 +1708,11 @@ impl Shard for MemflowWriteMemoryShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from parameter
-        let process_var = &self.process_instance.get();
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                process_var,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from parameter
+        let mut process = process_handle(self.process_instance.get())?;
 
         // Get address parameter
-        let address: i64 = self.address.get().as_ref().try_into()?;
-        let address_umem = address as umem;
+        let address_umem = parse_address(self.address.get())?;
 
         // Get data to write from input
         let data: &[u8] = input.try_into()?;
@@ -1129,20 +1720,38 @@ impl Shard for MemflowWriteMemoryShard {
             return Err("No data to write");
         }
 
+        let volatile: bool = self.volatile.get().as_ref().try_into()?;
+        if volatile {
+            check_volatile_access(address_umem, data.len())?;
+        }
+
+        // Reading back memory-mapped I/O is not guaranteed to return what was written
+        let verify: bool = self.verify.get().as_ref().try_into()?;
+        if verify && volatile {
+            return Err("Verify can not be used with Volatile");
+        }
+
         shlog_debug!(
-            "Writing memory at address: 0x{:x}, size: {} bytes",
+            "Writing memory at address: 0x{:x}, size: {} bytes, volatile: {}",
             address_umem,
-            data.len()
+            data.len(),
+            volatile
         );
 
         // Write memory
-        process
-            .0
-            .write_raw(Address::from(address_umem), data)
-            .map_err(|e| {
-                shlog_error!("Failed to write memory: {}", e);
-                "Failed to write memory to process."
-            })?;
+        process.write_raw(address_umem, data).map_err(|e| {
+            shlog_error!("Failed to write memory: {}", e);
+            "Failed to write memory to process."
+        })?;
+
+        // Some connectors report success for writes that did not land
+        if verify {
+            if let Err(e) = verify_write(process.process(), address_umem, data) {
+                shlog_error!("{}", e);
+                self.error_message = e;
+                return Err(&self.error_message);
+            }
+        }
 
         // Return success
         self.output_status = Var::new_bool(true).into();
@@ -1150,6 +1759,288 @@ impl Shard for MemflowWriteMemoryShard {
     }
 }
 
+// Helper function to read back a written range and compare it with what was written.
+// On mismatch the error names the first differing offset.
+fn verify_write(
+    process: &mut ProcessInstanceArcBox<'static>,
+    address: umem,
+    expected: &[u8],
+) -> std::result::Result<(), String> {
+    let mut actual = vec![0u8; expected.len()];
+    process
+        .read_raw_into(Address::from(address), &mut actual)
+        .map_err(|e| format!("Failed to read back written memory: {}", e))?;
+
+    match expected.iter().zip(&actual).position(|(e, a)| e != a) {
+        Some(offset) => Err(format!(
+            "Write verification failed at offset {} (0x{:x}): expected 0x{:02x}, found 0x{:02x}",
+            offset,
+            address + offset as umem,
+            expected[offset],
+            actual[offset]
+        )),
+        None => Ok(()),
+    }
+}
+
+// Number of bytes FillMemory writes at once
+const FILL_CHUNK_SIZE: usize = 0x10000;
+
+// Helper function to check that [address, address + size) lies within one of the
+// coalesced regions
+fn range_mapped(regions: &[(umem, usize)], address: umem, size: usize) -> bool {
+    let Some(end) = address.checked_add(size as umem) else {
+        return false;
+    };
+    regions.iter().any(|&(region_address, region_size)| {
+        address >= region_address && end <= region_address + region_size as umem
+    })
+}
+
+// Define the FillMemory Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.FillMemory",
+    "Fills a range of mapped, writable process memory with a single byte value."
+)]
+struct MemflowFillMemoryShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Process", "The plain or cached Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
+    process_instance: ParamVar,
+
+    #[shard_param("Address", "Memory address to start filling at.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Size", "Number of bytes to fill.", [common_type::int, common_type::int_var])]
+    size: ParamVar,
+
+    #[shard_param("Value", "Byte value to fill with, 0 to 255 (default: 0).", [common_type::int, common_type::int_var])]
+    value: ParamVar,
+
+    #[shard_param("Verify", "Read the range back after writing and fail if it does not match (default: false).", [common_type::bool, common_type::bool_var])]
+    verify: ParamVar,
+
+    // Last error, kept here so it can be returned by reference
+    error_message: String,
+}
+
+impl Default for MemflowFillMemoryShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            process_instance: ParamVar::default(),
+            address: ParamVar::new(0.into()),
+            size: ParamVar::new(1.into()),
+            value: ParamVar::new(0.into()),
+            verify: ParamVar::new(false.into()),
+            error_message: String::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowFillMemoryShard {
+    fn input_types(&mut self) -> &Types {
+        &ANY_TYPES // Input is ignored
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &NONE_TYPES // No output, just success/failure
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from parameter
+        let mut process = process_handle(self.process_instance.get())?;
+
+        // Get parameters
+        let address_umem = parse_address(self.address.get())?;
+        let size: i64 = self.size.get().as_ref().try_into()?;
+        let value: i64 = self.value.get().as_ref().try_into()?;
+        let verify: bool = self.verify.get().as_ref().try_into()?;
+
+        if size <= 0 {
+            return Err("Size must be greater than 0");
+        }
+        let value = u8::try_from(value).map_err(|_| "Value must be a byte, 0 to 255")?;
+
+        shlog_debug!(
+            "Filling memory at address: 0x{:x}, size: {} bytes, value: 0x{:02x}",
+            address_umem,
+            size,
+            value
+        );
+
+        // Refuse to start a fill that would run into unmapped or read-only memory
+        let maps: Vec<_> = process
+            .process()
+            .mapped_mem_vec(0)
+            .into_iter()
+            .filter(|map| protection_filter_matches(map.2, "rw"))
+            .collect();
+        if !range_mapped(&coalesce_regions(&maps), address_umem, size as usize) {
+            return Err("Fill range is not mapped writable memory");
+        }
+
+        // Write in chunks so a large fill never allocates the whole range at once
+        let data = vec![value; (size as usize).min(FILL_CHUNK_SIZE)];
+        let mut offset = 0;
+        while offset < size as usize {
+            let len = (size as usize - offset).min(FILL_CHUNK_SIZE);
+            let chunk_address = address_umem + offset as umem;
+            process
+                .write_raw(chunk_address, &data[..len])
+                .map_err(|e| {
+                    shlog_error!("Failed to write memory at 0x{:x}: {}", chunk_address, e);
+                    "Failed to write memory to process."
+                })?;
+
+            if verify {
+                if let Err(e) = verify_write(process.process(), chunk_address, &data[..len]) {
+                    shlog_error!("{}", e);
+                    self.error_message = e;
+                    return Err(&self.error_message);
+                }
+            }
+            offset += len;
+        }
+
+        Ok(None)
+    }
+}
+
+// Define the CompareAndWrite Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.CompareAndWrite",
+    "Writes the input bytes only if the memory at the address currently holds the expected bytes. The compare and the write are separate memory accesses, not an atomic compare-and-swap: the target can change the memory in between."
+)]
+struct MemflowCompareAndWriteShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Process", "The plain or cached Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
+    process_instance: ParamVar,
+
+    #[shard_param("Address", "Memory address to compare and write at.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Expected", "Bytes the memory must currently hold for the write to happen.", [common_type::bytes, common_type::bytes_var])]
+    expected: ParamVar,
+}
+
+impl Default for MemflowCompareAndWriteShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            process_instance: ParamVar::default(),
+            address: ParamVar::new(0.into()),
+            expected: ParamVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowCompareAndWriteShard {
+    fn input_types(&mut self) -> &Types {
+        &BYTES_TYPES // Takes bytes as input to write
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &BOOL_TYPES // Outputs whether the write happened
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from parameter
+        let mut process = process_handle(self.process_instance.get())?;
+
+        // Get parameters
+        let address_umem = parse_address(self.address.get())?;
+        let expected: &[u8] = self.expected.get().as_ref().try_into()?;
+        if expected.is_empty() {
+            return Err("Expected must not be empty");
+        }
+
+        // Get data to write from input
+        let data: &[u8] = input.try_into()?;
+        if data.is_empty() {
+            return Err("No data to write");
+        }
+
+        // Read the current bytes, never from a cache, and leave the memory alone if they changed
+        let mut current = vec![0u8; expected.len()];
+        process
+            .process()
+            .read_raw_into(Address::from(address_umem), &mut current)
+            .map_err(|e| {
+                shlog_error!("Failed to read memory: {}", e);
+                "Failed to read memory from process."
+            })?;
+
+        if current != expected {
+            shlog_debug!(
+                "Memory at 0x{:x} does not hold the expected bytes, not writing",
+                address_umem
+            );
+            return Ok(Some(false.into()));
+        }
+
+        shlog_debug!(
+            "Writing memory at address: 0x{:x}, size: {} bytes",
+            address_umem,
+            data.len()
+        );
+
+        process.write_raw(address_umem, data).map_err(|e| {
+            shlog_error!("Failed to write memory: {}", e);
+            "Failed to write memory to process."
+        })?;
+
+        Ok(Some(true.into()))
+    }
+}
+
 // Define the BatchWriteMemory Shard for more efficient writing
 #[derive(shards::shard)]
 #[shard_info(
@@ -1164,8 +2055,11 @@ struct MemflowBatchWriteMemoryShard {
     #[shard_param("Writes", "Table of memory writes with 'address' and 'data' fields.", [common_type::any_table, common_type::any_table_var])]
     writes: ParamVar,
 
-    #[shard_param("Process", "The Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR])]
+    #[shard_param("Process", "The plain or cached Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
     process_instance: ParamVar,
+
+    #[shard_param("Volatile", "Issue every write as its own single-width access instead of batching them, for memory-mapped I/O (default: false).", [common_type::bool, common_type::bool_var])]
+    volatile: ParamVar,
 }
 
 impl Default for MemflowBatchWriteMemoryShard {
@@ -1174,6 +2068,7 @@ impl Default for MemflowBatchWriteMemoryShard {
             required: ExposedTypes::new(),
             writes: ParamVar::default(),
             process_instance: ParamVar::default(),
+            volatile: ParamVar::new(false.into()),
         }
     }
 }
@@ -1211,18 +2106,13 @@ impl Shard for MemflowBatchWriteMemoryShard {
         _context: &Context,
         _input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from parameter
-        let process_var = &self.process_instance.get();
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                process_var,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from parameter
+        let mut process = process_handle(self.process_instance.get())?;
 
         // Get writes table
         let writes_var = self.writes.get();
         let writes_table = writes_var.as_table()?;
+        let volatile: bool = self.volatile.get().as_ref().try_into()?;
 
         shlog_debug!("Performing batch memory write operation");
 
@@ -1247,14 +2137,16 @@ impl Shard for MemflowBatchWriteMemoryShard {
                 .get(Var::ephemeral_string("data"))
                 .ok_or("Missing 'data' field in write entry")?;
 
-            let address: i64 = address_var.as_ref().try_into()?;
+            let address_umem = parse_address(&address_var)?;
             let data: &[u8] = data_var.try_into()?;
 
             if data.is_empty() {
                 return Err("Empty data in write entry");
             }
 
-            let address_umem = address as umem;
+            if volatile {
+                check_volatile_access(address_umem, data.len())?;
+            }
 
             // Create write operation
             write_ops.push(WriteOp {
@@ -1263,9 +2155,17 @@ impl Shard for MemflowBatchWriteMemoryShard {
             });
         }
 
-        // Now perform the batch write
-        {
-            let mut batcher = process.0.batcher();
+        if volatile || process.is_cached() {
+            // Volatile writes bypass the batcher so they are never coalesced or reordered,
+            // and cached writes go through the cache so it drops the written pages
+            for op in &write_ops {
+                process.write_raw(op.address, &op.data).map_err(|e| {
+                    shlog_error!("Failed to write memory at 0x{:x}: {}", op.address, e);
+                    "Failed to write memory to process."
+                })?;
+            }
+        } else {
+            let mut batcher = process.process().batcher();
 
             // Set up all write operations in the batcher
             for op in &write_ops {
@@ -1273,7 +2173,10 @@ impl Shard for MemflowBatchWriteMemoryShard {
             }
 
             // Execute all write operations in batch
-            batcher.commit_rw().map_err(|e| {
+            let committed = batcher.commit_rw();
+            drop(batcher);
+            process.note_write();
+            committed.map_err(|e| {
                 shlog_error!("Failed to execute batch memory write: {}", e);
                 "Failed to write memory to process."
             })?;
@@ -1294,13 +2197,13 @@ struct MemflowMemoryScanShard {
     required: ExposedTypes,
 
     // Parameters
-    #[shard_param("ValueType", "Type of value to scan for: 'int', 'float', 'double', 'string', 'bytes'.", [common_type::string, common_type::string_var])]
+    #[shard_param("ValueType", "Type of value to scan for: 'int' (same as 'i64'), 'i8', 'u8', 'i16', 'u16', 'i32', 'u32', 'i64', 'u64', 'float', 'double', 'string', 'bytes', 'guid' (a '{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}' string).", [common_type::string, common_type::string_var])]
     value_type: ParamVar,
 
     #[shard_param("Value", "Value to scan for.", [common_type::any, common_type::any_var])]
     value: ParamVar,
 
-    #[shard_param("Alignment", "Memory alignment for the scan (default: 1).", [common_type::none, common_type::int, common_type::int_var])]
+    #[shard_param("Alignment", "Memory alignment for the scan (default: the value size for int, float and double scans, 1 otherwise). Values below 1 scan byte by byte.", [common_type::none, common_type::int, common_type::int_var])]
     alignment: ParamVar,
 
     #[shard_param("MinSize", "Minimum size of memory regions to scan (default: 4096).", [common_type::none, common_type::int, common_type::int_var])]
@@ -1312,14 +2215,47 @@ struct MemflowMemoryScanShard {
     #[shard_param("Protection", "Memory protection to filter by (e.g., 'r--', 'rw-', 'r-x').", [common_type::none, common_type::string, common_type::string_var])]
     protection: ParamVar,
 
+    #[shard_param("Start", "Lowest address to scan (default: no limit).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    start: ParamVar,
+
+    #[shard_param("End", "Address to stop scanning at, exclusive (default: no limit).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    end: ParamVar,
+
+    #[shard_param("Module", "Module to restrict the scan to (default: all memory).", [common_type::none, *MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
     #[shard_param("PreviousScan", "Results from a previous scan for incremental scanning.", [common_type::none, common_type::any_table, common_type::any_table_var])]
     previous_scan: ParamVar,
 
-    #[shard_param("CompareType", "For incremental scans: 'equal', 'notequal', 'greater', 'less', 'changed', 'unchanged'.", [common_type::none, common_type::string, common_type::string_var])]
+    #[shard_param("CompareType", "For incremental scans: 'equal', 'notequal', 'greater', 'less', 'changed', 'unchanged', 'increased', 'decreased'. 'between' matches int, float and double values from ValueMin to ValueMax, in first and incremental scans. 'snapshot' outputs a table with the contents of all scanned memory, for unknown initial value scans through PreviousScan with the same ValueType and Endian.", [common_type::none, common_type::string, common_type::string_var])]
     compare_type: ParamVar,
 
+    #[shard_param("ValueMin", "Lowest matching value when CompareType is 'between'.", [common_type::none, common_type::int, common_type::int_var, common_type::float, common_type::float_var])]
+    value_min: ParamVar,
+
+    #[shard_param("ValueMax", "Highest matching value when CompareType is 'between'.", [common_type::none, common_type::int, common_type::int_var, common_type::float, common_type::float_var])]
+    value_max: ParamVar,
+
+    #[shard_param("CaseInsensitive", "Match string values ignoring case (default: false). Only ASCII letters are folded, not full Unicode.", [common_type::bool, common_type::bool_var])]
+    case_insensitive: ParamVar,
+
+    #[shard_param("Endian", "Byte order of int, float and double values: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Threads", "Number of worker threads scanning the read memory (default: 1). Incremental scans always run on the calling thread.", [common_type::int, common_type::int_var])]
+    threads: ParamVar,
+
     // Output results
     scan_results: AutoSeqVar,
+
+    // Output snapshot, keyed by chunk address
+    snapshot: AutoTableVar,
+
+    // Whether CompareType is 'snapshot', known at compose time
+    snapshot_mode: bool,
 }
 
 impl Default for MemflowMemoryScanShard {
@@ -1328,13 +2264,24 @@ impl Default for MemflowMemoryScanShard {
             required: ExposedTypes::new(),
             value_type: ParamVar::new(Var::ephemeral_string("int")),
             value: ParamVar::default(),
-            alignment: ParamVar::new(1.into()),
+            alignment: ParamVar::default(),
             min_size: ParamVar::new(4096.into()),
             max_size: ParamVar::default(),
             protection: ParamVar::default(),
+            start: ParamVar::default(),
+            end: ParamVar::default(),
+            module: ParamVar::default(),
             previous_scan: ParamVar::default(),
             compare_type: ParamVar::default(),
+            value_min: ParamVar::default(),
+            value_max: ParamVar::default(),
+            case_insensitive: ParamVar::new(false.into()),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            threads: ParamVar::new(1.into()),
             scan_results: AutoSeqVar::new(),
+            snapshot: AutoTableVar::new(),
+            snapshot_mode: false,
         }
     }
 }
@@ -1342,16 +2289,24 @@ impl Default for MemflowMemoryScanShard {
 #[shards::shard_impl]
 impl Shard for MemflowMemoryScanShard {
     fn input_types(&mut self) -> &Types {
-        &MEMFLOW_PROCESS_TYPES // Takes process as input
+        &MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
     }
 
     fn output_types(&mut self) -> &Types {
-        &ANYS_TYPES // Outputs a sequence of results
+        &ANY_TYPES // Outputs a sequence of results, or a table for snapshots
     }
 
     fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
         self.compose_helper(data)?;
-        Ok(self.output_types()[0])
+
+        let compare_type: std::result::Result<&str, _> =
+            self.compare_type.get_param().as_ref().try_into();
+        self.snapshot_mode = compare_type == Ok("snapshot");
+        if self.snapshot_mode {
+            Ok(common_type::any_table)
+        } else {
+            Ok(common_type::anys)
+        }
     }
 
     fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
@@ -1361,6 +2316,7 @@ impl Shard for MemflowMemoryScanShard {
 
     fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
         self.scan_results = AutoSeqVar::new();
+        self.snapshot = AutoTableVar::new();
         self.cleanup_helper(ctx)?;
         Ok(())
     }
@@ -1370,17 +2326,13 @@ impl Shard for MemflowMemoryScanShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
 
         // Get parameters
         let value_type: &str = self.value_type.get().as_ref().try_into()?;
-        let alignment: i64 = self.alignment.get().as_ref().try_into().unwrap_or(1);
+        let endian_str: &str = self.endian.get().as_ref().try_into()?;
+        let endian = Endian::from_name(endian_str)?;
         let min_size: i64 = self.min_size.get().as_ref().try_into().unwrap_or(4096);
         let max_size: Option<i64> = if self.max_size.get().is_none() {
             None
@@ -1397,7 +2349,7 @@ impl Shard for MemflowMemoryScanShard {
         };
 
         // Get memory maps with filtering
-        let maps = process.0.mapped_mem_vec(0);
+        let maps = process.process().mapped_mem_vec(0);
         let filtered_maps: Vec<_> = maps
             .into_iter()
             .filter(|map| {
@@ -1429,35 +2381,33 @@ impl Shard for MemflowMemoryScanShard {
             filtered_maps.len()
         );
 
-        // Prepare the value to search for
-        let search_value = match value_type {
-            "int" => {
-                let val: i64 = self.value.get().as_ref().try_into()?;
-                ScanValue::Integer(val)
-            }
-            "float" => {
-                let val: f32 = self.value.get().as_ref().try_into()?;
-                ScanValue::Float(val)
-            }
-            "double" => {
-                let val: f64 = self.value.get().as_ref().try_into()?;
-                ScanValue::Double(val)
+        // Check if this is an incremental scan
+        let incremental_scan = !self.previous_scan.get().is_none();
+
+        // A snapshot records the whole memory instead of matching anything
+        let compare_type_str: Option<&str> = if self.compare_type.get().is_none() {
+            None
+        } else {
+            Some(self.compare_type.get().as_ref().try_into()?)
+        };
+        let take_snapshot = compare_type_str == Some("snapshot");
+        if take_snapshot {
+            if !self.snapshot_mode {
+                return Err("CompareType 'snapshot' must be set directly, not through a variable");
             }
-            "string" => {
-                let val: &str = self.value.get().as_ref().try_into()?;
-                ScanValue::String(val.to_string())
+            if incremental_scan {
+                return Err("CompareType 'snapshot' can not be used together with PreviousScan");
             }
-            "bytes" => {
-                let val: &[u8] = self.value.get().as_ref().try_into()?;
-                ScanValue::Bytes(val.to_vec())
+            if matches!(value_type, "string" | "bytes" | "guid") {
+                return Err("CompareType 'snapshot' only supports int, float and double values");
             }
-            _ => return Err("Unsupported value type"),
-        };
+        }
 
-        // Check if this is an incremental scan
-        let incremental_scan = !self.previous_scan.get().is_none();
-        let compare_type = if incremental_scan {
-            let compare_type_str: &str = self.compare_type.get().as_ref().try_into()?;
+        let compare_type = if take_snapshot {
+            None
+        } else if incremental_scan || compare_type_str.is_some() {
+            let compare_type_str =
+                compare_type_str.ok_or("CompareType is required with PreviousScan")?;
             Some(match compare_type_str {
                 "equal" => CompareType::Equal,
                 "notequal" => CompareType::NotEqual,
@@ -1465,16 +2415,52 @@ impl Shard for MemflowMemoryScanShard {
                 "less" => CompareType::Less,
                 "changed" => CompareType::Changed,
                 "unchanged" => CompareType::Unchanged,
+                "increased" => CompareType::Increased,
+                "decreased" => CompareType::Decreased,
+                "between" => {
+                    if matches!(value_type, "string" | "bytes" | "guid") {
+                        return Err(
+                            "CompareType 'between' only supports int, float and double values",
+                        );
+                    }
+                    CompareType::Between(
+                        parse_scan_value(value_type, self.value_min.get(), endian)?,
+                        parse_scan_value(value_type, self.value_max.get(), endian)?,
+                    )
+                }
                 _ => return Err("Unsupported compare type"),
             })
         } else {
             None
         };
 
-        // Get previous scan results if this is an incremental scan
-        let previous_results = if incremental_scan {
+        // Prepare the value to search for. Range scans don't need one, the lower bound
+        // stands in for it to give the scan its value type and size. Snapshots and
+        // comparisons against the previous value only need the type, so Value is optional.
+        let mut search_value = match &compare_type {
+            Some(CompareType::Between(min, _)) => min.clone(),
+            Some(
+                CompareType::Changed
+                | CompareType::Unchanged
+                | CompareType::Increased
+                | CompareType::Decreased,
+            ) if self.value.get().is_none() => placeholder_scan_value(value_type, endian)?,
+            None if take_snapshot && self.value.get().is_none() => {
+                placeholder_scan_value(value_type, endian)?
+            }
+            _ => parse_scan_value(value_type, self.value.get(), endian)?,
+        };
+        let case_insensitive: bool = self.case_insensitive.get().as_ref().try_into()?;
+        if let ScanValue::String(_, fold) = &mut search_value {
+            *fold = case_insensitive;
+        }
+
+        // Get previous scan results if this is an incremental scan
+        let snapshot_type = snapshot_value_type(value_type, endian);
+        let previous_results = if incremental_scan {
             let prev_var = self.previous_scan.get();
             let prev_table = prev_var.as_table()?;
+            check_snapshot_type(&prev_table, &snapshot_type)?;
             Some(prev_table)
         } else {
             None
@@ -1483,58 +2469,122 @@ impl Shard for MemflowMemoryScanShard {
         // Perform the scan
         self.scan_results.0.clear();
 
-        let alignment_usize = alignment as usize;
+        // Typed values are usually stored at their natural alignment. An alignment of 0
+        // would never advance the scan, so anything below 1 scans byte by byte instead.
+        let alignment_usize = if self.alignment.get().is_none() {
+            match &search_value {
+                ScanValue::Integer(..) | ScanValue::Float(_) | ScanValue::Double(_) => {
+                    search_value.size()
+                }
+                ScanValue::String(..) | ScanValue::Bytes(_) => 1,
+            }
+        } else {
+            let alignment: i64 = self.alignment.get().as_ref().try_into()?;
+            alignment.max(1) as usize
+        };
+
+        // Merge adjacent regions so values spanning a region boundary are found
+        let regions = clip_regions(
+            coalesce_regions(&filtered_maps),
+            &self.start,
+            &self.end,
+            &self.module,
+        )?;
+
+        // Skip regions that are too small and split large ones into overlapping chunks
+        let overlap = search_value.size().saturating_sub(1);
+        let chunk_size = chunk_size_param(&self.chunk_size, overlap)?;
+        let regions: Vec<(umem, usize)> = regions
+            .into_iter()
+            .filter(|(_, size)| *size >= search_value.size())
+            .flat_map(|(address, size)| split_region(address, size, chunk_size, overlap))
+            .collect();
 
-        for map in filtered_maps {
-            let base_addr = map.0.to_umem();
-            let size = map.1.to_umem() as usize;
+        if take_snapshot {
+            // Keep the raw contents of every chunk, keyed by its address. Later scans decode
+            // the values at each aligned offset from them when given this as PreviousScan.
+            self.snapshot.0.clear();
+            let snapshot = &mut self.snapshot;
+            read_regions(&mut process, &regions, |base_addr, buffer| {
+                let key = Var::ephemeral_string(&format!("0x{:x}", base_addr));
+                let address_var: Var = (base_addr as i64).into();
+                let snapshot_var = Var::ephemeral_slice(buffer.as_slice());
+                let type_var = Var::ephemeral_string(&snapshot_type);
+
+                let mut entry = AutoTableVar::new();
+                entry.0.insert_fast_static("address", &address_var);
+                entry.0.insert_fast_static("snapshot", &snapshot_var);
+                entry.0.insert_fast_static("type", &type_var);
+                snapshot.0.insert_fast(key, &entry.0 .0);
+                true
+            });
 
-            // Skip regions that are too small
-            if size < search_value.size() {
-                continue;
-            }
+            shlog_debug!("Took a snapshot of {} memory chunks", regions.len());
+            return Ok(Some(self.snapshot.0 .0));
+        }
 
-            // Read the memory region
-            let mut buffer = vec![0u8; size];
-            match process
-                .0
-                .read_raw_into(Address::from(base_addr), &mut buffer)
-            {
-                Ok(_) => {
-                    // Scan the buffer for matches
-                    let matches = scan_buffer(
-                        &buffer,
+        let threads: i64 = self.threads.get().as_ref().try_into()?;
+        let mut results = if incremental_scan {
+            // Previous results live in a shards table, which can't be shared across threads
+            let mut results = Vec::new();
+            read_regions(&mut process, &regions, |base_addr, buffer| {
+                results.extend(scan_buffer(
+                    &buffer,
+                    &search_value,
+                    alignment_usize,
+                    base_addr,
+                    previous_results,
+                    compare_type.as_ref(),
+                    endian,
+                ));
+                true
+            });
+            results
+        } else {
+            scan_regions(
+                &mut process,
+                &regions,
+                threads.max(1) as usize,
+                |base_addr, buffer| {
+                    scan_buffer(
+                        buffer,
                         &search_value,
                         alignment_usize,
                         base_addr,
-                        previous_results,
+                        None,
                         compare_type.as_ref(),
-                    );
-
-                    for result in matches {
-                        let address: Var = result.address.into();
-                        let value = match &search_value {
-                            ScanValue::Integer(_) => Var::new_int(result.value_int),
-                            ScanValue::Float(_) => Var::new_float(result.value_float.into()),
-                            ScanValue::Double(_) => Var::new_float(result.value_double),
-                            ScanValue::String(_) => Var::ephemeral_string(&result.value_string),
-                            ScanValue::Bytes(_) => {
-                                Var::ephemeral_slice(result.value_bytes.as_slice())
-                            }
-                        };
-
-                        let mut result_entry = AutoTableVar::new();
-                        result_entry.0.insert_fast_static("address", &address);
-                        result_entry.0.insert_fast_static("value", &value);
+                        endian,
+                    )
+                },
+            )
+        };
 
-                        self.scan_results.0.emplace_table(result_entry);
-                    }
-                }
-                Err(e) => {
-                    shlog_debug!("Failed to read memory region at 0x{:x}: {}", base_addr, e);
-                    continue;
-                }
+        // Report matches in address order no matter how the scan was split up. Values in
+        // the overlap of two chunks are found in both when scanning against a snapshot.
+        results.sort_by_key(|result| result.address);
+        results.dedup_by_key(|result| result.address);
+
+        for result in results {
+            let value = match &search_value {
+                ScanValue::Integer(..) => Var::new_int(result.value_int),
+                ScanValue::Float(_) => Var::new_float(result.value_float.into()),
+                ScanValue::Double(_) => Var::new_float(result.value_double),
+                ScanValue::String(..) => Var::ephemeral_string(&result.value_string),
+                ScanValue::Bytes(_) => Var::ephemeral_slice(result.value_bytes.as_slice()),
+            };
+            let raw = Var::ephemeral_slice(result.raw.as_slice());
+
+            let mut result_entry = AutoTableVar::new();
+            insert_address(&mut result_entry, result.address);
+            if value_type == "guid" {
+                let guid = Var::ephemeral_string(&format_guid(&result.raw));
+                result_entry.0.insert_fast_static("guid", &guid);
+            } else {
+                result_entry.0.insert_fast_static("value", &value);
             }
+            result_entry.0.insert_fast_static("raw", &raw);
+
+            self.scan_results.0.emplace_table(result_entry);
         }
 
         Ok(Some(self.scan_results.0 .0))
@@ -1542,24 +2592,149 @@ impl Shard for MemflowMemoryScanShard {
 }
 
 // Helper enum for scan value types
+#[derive(Clone)]
 enum ScanValue {
-    Integer(i64),
+    Integer(i64, ValueType),
     Float(f32),
     Double(f64),
-    String(String),
+    // The string and whether it is compared ignoring ASCII case
+    String(String, bool),
     Bytes(Vec<u8>),
 }
 
 impl ScanValue {
     fn size(&self) -> usize {
         match self {
-            ScanValue::Integer(_) => std::mem::size_of::<i64>(),
+            ScanValue::Integer(_, width) => width.size(),
             ScanValue::Float(_) => std::mem::size_of::<f32>(),
             ScanValue::Double(_) => std::mem::size_of::<f64>(),
-            ScanValue::String(s) => s.len(),
+            ScanValue::String(s, _) => s.len(),
             ScanValue::Bytes(b) => b.len(),
         }
     }
+
+    // Decode a value of the same type from raw memory, only for int, float and double
+    fn decode_var(&self, bytes: &[u8], endian: Endian) -> Option<Var> {
+        match self {
+            ScanValue::Integer(_, width) => Some(Var::new_int(width.decode_int(bytes, endian)?)),
+            ScanValue::Float(_) => {
+                Some(Var::new_float(ValueType::F32.decode_float(bytes, endian)?))
+            }
+            ScanValue::Double(_) => {
+                Some(Var::new_float(ValueType::F64.decode_float(bytes, endian)?))
+            }
+            ScanValue::String(..) | ScanValue::Bytes(_) => None,
+        }
+    }
+}
+
+// Helper function to create a scan value of the given type for scans that don't look
+// for a specific value
+fn placeholder_scan_value(
+    value_type: &str,
+    endian: Endian,
+) -> std::result::Result<ScanValue, &'static str> {
+    match value_type {
+        "float" => Ok(ScanValue::Float(0.0)),
+        "double" => Ok(ScanValue::Double(0.0)),
+        "string" | "bytes" | "guid" => Err("Value is required for string, bytes and guid scans"),
+        _ => parse_scan_value(value_type, &Var::new_int(0), endian),
+    }
+}
+
+// Helper function to parse a GUID string such as '{6B29FC40-CA47-1067-B31D-00DD010662DA}'
+// (braces optional) into its in-memory layout: the first three fields are little-endian
+// integers, the last two are stored as plain bytes
+fn parse_guid(guid: &str) -> std::result::Result<[u8; 16], &'static str> {
+    const INVALID: &str = "Invalid GUID, expected '{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}'";
+
+    let guid = guid
+        .strip_prefix('{')
+        .and_then(|guid| guid.strip_suffix('}'))
+        .unwrap_or(guid);
+    let fields: Vec<&str> = guid.split('-').collect();
+    let valid = fields.len() == 5
+        && fields
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(field, len)| field.len() == len && field.bytes().all(|b| b.is_ascii_hexdigit()));
+    if !valid {
+        return Err(INVALID);
+    }
+
+    let data1 = u32::from_str_radix(fields[0], 16).map_err(|_| INVALID)?;
+    let data2 = u16::from_str_radix(fields[1], 16).map_err(|_| INVALID)?;
+    let data3 = u16::from_str_radix(fields[2], 16).map_err(|_| INVALID)?;
+
+    let mut bytes = [0u8; 16];
+    bytes[0..4].copy_from_slice(&data1.to_le_bytes());
+    bytes[4..6].copy_from_slice(&data2.to_le_bytes());
+    bytes[6..8].copy_from_slice(&data3.to_le_bytes());
+    let tail = format!("{}{}", fields[3], fields[4]);
+    for (i, byte) in bytes[8..].iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&tail[i * 2..i * 2 + 2], 16).map_err(|_| INVALID)?;
+    }
+    Ok(bytes)
+}
+
+// Helper function to format the in-memory layout of a GUID in its canonical form
+fn format_guid(bytes: &[u8]) -> String {
+    if bytes.len() != 16 {
+        return String::new();
+    }
+    let hex = |bytes: &[u8]| -> String { bytes.iter().map(|b| format!("{:02X}", b)).collect() };
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{}-{}}}",
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        u16::from_le_bytes([bytes[6], bytes[7]]),
+        hex(&bytes[8..10]),
+        hex(&bytes[10..16])
+    )
+}
+
+// Helper function to parse the value to scan for from a param, by value type name
+fn parse_scan_value(
+    value_type: &str,
+    value: &Var,
+    endian: Endian,
+) -> std::result::Result<ScanValue, &'static str> {
+    Ok(match value_type {
+        "int" | "i8" | "u8" | "i16" | "u16" | "i32" | "u32" | "i64" | "u64" => {
+            // "int" is kept as an alias of "i64"
+            let width = if value_type == "int" {
+                ValueType::I64
+            } else {
+                ValueType::from_name(value_type)?
+            };
+            // Reject values that can't be represented at the requested width
+            width.encode_var(value, endian)?;
+            let val: i64 = value.as_ref().try_into()?;
+            ScanValue::Integer(val, width)
+        }
+        "float" => {
+            let val: f32 = value.as_ref().try_into()?;
+            ScanValue::Float(val)
+        }
+        "double" => {
+            let val: f64 = value.as_ref().try_into()?;
+            ScanValue::Double(val)
+        }
+        "string" => {
+            let val: &str = value.as_ref().try_into()?;
+            ScanValue::String(val.to_string(), false)
+        }
+        "bytes" => {
+            let val: &[u8] = value.as_ref().try_into()?;
+            ScanValue::Bytes(val.to_vec())
+        }
+        "guid" => {
+            // GUIDs are scanned for as the 16 bytes they are stored as
+            let val: &str = value.as_ref().try_into()?;
+            ScanValue::Bytes(parse_guid(val)?.to_vec())
+        }
+        _ => return Err("Unsupported value type"),
+    })
 }
 
 // Helper enum for comparison types in incremental scans
@@ -1570,16 +2745,269 @@ enum CompareType {
     Less,
     Changed,
     Unchanged,
+    Increased,
+    Decreased,
+    // Inclusive range of int, float or double values, of the same type as the scan value
+    Between(ScanValue, ScanValue),
+}
+
+// Helper function to compare memory with a searched string, optionally folding ASCII case.
+// Bytes are compared in place, without lowercasing a copy of the memory.
+fn string_matches(memory: &[u8], search: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        memory.eq_ignore_ascii_case(search.as_bytes())
+    } else {
+        memory == search.as_bytes()
+    }
+}
+
+// Helper function to check whether a value lies within an inclusive range
+fn in_range<T: PartialOrd>(value: T, min: T, max: T) -> bool {
+    min <= value && value <= max
+}
+
+// Helper function to check whether an integer of the given type lies within an
+// inclusive range, comparing U64 values as unsigned
+fn int_in_range(width: &ValueType, value: i64, min: i64, max: i64) -> bool {
+    width.cmp_int(min, value).is_le() && width.cmp_int(value, max).is_le()
 }
 
 // Helper struct for scan results
 struct ScanResult {
-    address: i64,
+    address: umem,
     value_int: i64,
     value_float: f32,
     value_double: f64,
     value_string: String,
     value_bytes: Vec<u8>,
+    raw: Vec<u8>,
+}
+
+// Helper function to compare the value at `offset` in a buffer against the searched
+// value or the value it had in a previous scan
+fn compare_scan_value(
+    buffer: &[u8],
+    offset: usize,
+    search_value: &ScanValue,
+    compare_type: &CompareType,
+    prev_value: &Var,
+    endian: Endian,
+) -> bool {
+    match search_value {
+        ScanValue::Integer(search_int, width) => {
+            if offset + width.size() > buffer.len() {
+                return false;
+            }
+            let current_value = width.decode_int(&buffer[offset..], endian).unwrap_or(0);
+            let prev_int: i64 = match prev_value.as_ref().try_into() {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+
+            match compare_type {
+                CompareType::Equal => current_value == *search_int,
+                CompareType::NotEqual => current_value != *search_int,
+                CompareType::Greater => width.cmp_int(current_value, *search_int).is_gt(),
+                CompareType::Less => width.cmp_int(current_value, *search_int).is_lt(),
+                CompareType::Changed => current_value != prev_int,
+                CompareType::Unchanged => current_value == prev_int,
+                CompareType::Increased => width.cmp_int(current_value, prev_int).is_gt(),
+                CompareType::Decreased => width.cmp_int(current_value, prev_int).is_lt(),
+                CompareType::Between(min, max) => match (min, max) {
+                    (ScanValue::Integer(min, _), ScanValue::Integer(max, _)) => {
+                        int_in_range(width, current_value, *min, *max)
+                    }
+                    _ => false,
+                },
+            }
+        }
+        ScanValue::Float(search_float) => {
+            if offset + std::mem::size_of::<f32>() > buffer.len() {
+                return false;
+            }
+            let current_value = ValueType::F32
+                .decode_float(&buffer[offset..], endian)
+                .unwrap_or(0.0) as f32;
+            let prev_float: f64 = match prev_value.as_ref().try_into() {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+            let prev_float = prev_float as f32;
+
+            match compare_type {
+                CompareType::Equal => (current_value - *search_float).abs() < f32::EPSILON,
+                CompareType::NotEqual => (current_value - *search_float).abs() >= f32::EPSILON,
+                CompareType::Greater => current_value > *search_float,
+                CompareType::Less => current_value < *search_float,
+                CompareType::Changed => (current_value - prev_float).abs() >= f32::EPSILON,
+                CompareType::Unchanged => (current_value - prev_float).abs() < f32::EPSILON,
+                CompareType::Increased => current_value > prev_float,
+                CompareType::Decreased => current_value < prev_float,
+                CompareType::Between(min, max) => match (min, max) {
+                    (ScanValue::Float(min), ScanValue::Float(max)) => {
+                        in_range(current_value, *min, *max)
+                    }
+                    _ => false,
+                },
+            }
+        }
+        ScanValue::Double(search_double) => {
+            if offset + std::mem::size_of::<f64>() > buffer.len() {
+                return false;
+            }
+            let current_value = ValueType::F64
+                .decode_float(&buffer[offset..], endian)
+                .unwrap_or(0.0);
+            let prev_double: f64 = match prev_value.as_ref().try_into() {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+
+            match compare_type {
+                CompareType::Equal => (current_value - *search_double).abs() < f64::EPSILON,
+                CompareType::NotEqual => (current_value - *search_double).abs() >= f64::EPSILON,
+                CompareType::Greater => current_value > *search_double,
+                CompareType::Less => current_value < *search_double,
+                CompareType::Changed => (current_value - prev_double).abs() >= f64::EPSILON,
+                CompareType::Unchanged => (current_value - prev_double).abs() < f64::EPSILON,
+                CompareType::Increased => current_value > prev_double,
+                CompareType::Decreased => current_value < prev_double,
+                CompareType::Between(min, max) => match (min, max) {
+                    (ScanValue::Double(min), ScanValue::Double(max)) => {
+                        in_range(current_value, *min, *max)
+                    }
+                    _ => false,
+                },
+            }
+        }
+        ScanValue::String(search_string, case_insensitive) => {
+            let current_value = &buffer[offset..offset + search_string.len()];
+            let prev_string: &[u8] = match prev_value.as_ref().try_into() {
+                Ok(v) => v,
+                Err(_) => match TryInto::<&str>::try_into(prev_value.as_ref()) {
+                    Ok(v) => v.as_bytes(),
+                    Err(_) => return false,
+                },
+            };
+
+            // Strings have no ordering, greater/less/between/increased/decreased never match
+            match compare_type {
+                CompareType::Equal => {
+                    string_matches(current_value, search_string, *case_insensitive)
+                }
+                CompareType::NotEqual => {
+                    !string_matches(current_value, search_string, *case_insensitive)
+                }
+                CompareType::Greater
+                | CompareType::Less
+                | CompareType::Between(..)
+                | CompareType::Increased
+                | CompareType::Decreased => false,
+                CompareType::Changed => current_value != prev_string,
+                CompareType::Unchanged => current_value == prev_string,
+            }
+        }
+        ScanValue::Bytes(search_bytes) => {
+            let current_value = &buffer[offset..offset + search_bytes.len()];
+            let prev_bytes: &[u8] = match prev_value.as_ref().try_into() {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+
+            // Byte strings have no ordering, greater/less/between/increased/decreased never match
+            match compare_type {
+                CompareType::Equal => current_value == search_bytes.as_slice(),
+                CompareType::NotEqual => current_value != search_bytes.as_slice(),
+                CompareType::Greater
+                | CompareType::Less
+                | CompareType::Between(..)
+                | CompareType::Increased
+                | CompareType::Decreased => false,
+                CompareType::Changed => current_value != prev_bytes,
+                CompareType::Unchanged => current_value == prev_bytes,
+            }
+        }
+    }
+}
+
+// Helper function to name the value type and byte order of a snapshot. The raw bytes it
+// holds only mean the same values to a scan that decodes them the same way.
+fn snapshot_value_type(value_type: &str, endian: Endian) -> String {
+    let value_type = if value_type == "int" {
+        "i64"
+    } else {
+        value_type
+    };
+    let endian = match endian {
+        Endian::Native if cfg!(target_endian = "big") => "big",
+        Endian::Native | Endian::Little => "little",
+        Endian::Big => "big",
+    };
+    format!("{}:{}", value_type, endian)
+}
+
+// Helper function to reject a snapshot taken with another value type or byte order
+fn check_snapshot_type(
+    previous_results: &TableVar,
+    snapshot_type: &str,
+) -> std::result::Result<(), &'static str> {
+    for (key, _) in previous_results.iter() {
+        let entry = previous_results.get(key).unwrap();
+        let entry_table = match entry.as_table() {
+            Ok(t) => t,
+            Err(_) => continue,
+        };
+        if entry_table.get(Var::ephemeral_string("snapshot")).is_none() {
+            continue;
+        }
+        let type_var = entry_table.get(Var::ephemeral_string("type"));
+        let entry_type: Option<&str> = type_var.as_ref().and_then(|t| t.try_into().ok());
+        if entry_type != Some(snapshot_type) {
+            return Err("PreviousScan snapshot was taken with a different ValueType or Endian");
+        }
+    }
+    Ok(())
+}
+
+// Helper function to compare every aligned value in a buffer against a snapshot taken by
+// an earlier scan. Only the part of the buffer the snapshot also covers is compared.
+#[allow(clippy::too_many_arguments)]
+fn scan_snapshot(
+    buffer: &[u8],
+    base_addr: umem,
+    snapshot: &[u8],
+    snapshot_addr: umem,
+    search_value: &ScanValue,
+    alignment: usize,
+    compare_type: &CompareType,
+    endian: Endian,
+) -> Vec<ScanResult> {
+    let mut results = Vec::new();
+    let value_size = search_value.size();
+
+    let start = base_addr.max(snapshot_addr);
+    let end = (base_addr + buffer.len() as umem).min(snapshot_addr + snapshot.len() as umem);
+    let mut address = start + (alignment as umem - start % alignment as umem) % alignment as umem;
+    while address + value_size as umem <= end {
+        let offset = (address - base_addr) as usize;
+        let snapshot_offset = (address - snapshot_addr) as usize;
+        let prev_value = search_value.decode_var(&snapshot[snapshot_offset..], endian);
+        if let Some(prev_value) = prev_value {
+            if compare_scan_value(
+                buffer,
+                offset,
+                search_value,
+                compare_type,
+                &prev_value,
+                endian,
+            ) {
+                let result = create_scan_result(buffer, offset, address, search_value, endian);
+                results.push(result);
+            }
+        }
+        address += alignment as umem;
+    }
+    results
 }
 
 // Helper function to scan a buffer for matches
@@ -1590,6 +3018,7 @@ fn scan_buffer(
     base_addr: umem,
     previous_results: Option<&TableVar>,
     compare_type: Option<&CompareType>,
+    endian: Endian,
 ) -> Vec<ScanResult> {
     let mut results = Vec::new();
     let value_size = search_value.size();
@@ -1612,94 +3041,120 @@ fn scan_buffer(
                 Ok(a) => a,
                 Err(_) => continue,
             };
-
-            let offset = (addr as umem - base_addr) as usize;
-            if offset + value_size > buffer.len() {
+            let addr = addr as umem;
+
+            // Snapshot entries hold the raw contents of a whole scanned chunk
+            if let Some(snapshot) = entry_table.get(Var::ephemeral_string("snapshot")) {
+                let snapshot: &[u8] = match snapshot.as_ref().try_into() {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                results.extend(scan_snapshot(
+                    buffer,
+                    base_addr,
+                    snapshot,
+                    addr,
+                    search_value,
+                    alignment,
+                    compare_type,
+                    endian,
+                ));
                 continue;
             }
 
-            let prev_value = match entry_table.get(Var::ephemeral_string("value")) {
-                Some(v) => v,
-                None => continue,
+            // Previous results from other regions are not in this buffer
+            let offset = match addr.checked_sub(base_addr) {
+                Some(offset) if offset as usize + value_size <= buffer.len() => offset as usize,
+                _ => continue,
             };
 
-            let matches = match search_value {
-                ScanValue::Integer(search_int) => {
-                    if offset + std::mem::size_of::<i64>() > buffer.len() {
-                        continue;
-                    }
-                    let current_value = i64::from_ne_bytes(
-                        buffer[offset..offset + std::mem::size_of::<i64>()]
-                            .try_into()
-                            .unwrap_or([0; 8]),
-                    );
-                    let prev_int: i64 = match prev_value.as_ref().try_into() {
-                        Ok(v) => v,
-                        Err(_) => continue,
-                    };
-
-                    match compare_type {
-                        CompareType::Equal => current_value == *search_int,
-                        CompareType::NotEqual => current_value != *search_int,
-                        CompareType::Greater => current_value > *search_int,
-                        CompareType::Less => current_value < *search_int,
-                        CompareType::Changed => current_value != prev_int,
-                        CompareType::Unchanged => current_value == prev_int,
-                    }
-                }
-                // Similar implementations for other types...
-                _ => false, // Simplified for now
+            // Prefer the raw bytes of the previous match, its value may have been converted
+            // lossily (strings through from_utf8_lossy)
+            let raw_var = entry_table.get(Var::ephemeral_string("raw"));
+            let raw: Option<&[u8]> = raw_var.as_ref().and_then(|raw| raw.try_into().ok());
+            let prev_value = match raw {
+                Some(raw) if raw.len() == value_size => search_value
+                    .decode_var(raw, endian)
+                    .unwrap_or_else(|| Var::ephemeral_slice(raw)),
+                _ => match entry_table.get(Var::ephemeral_string("value")) {
+                    Some(v) => v,
+                    None => continue,
+                },
             };
 
-            if matches {
+            if compare_scan_value(
+                buffer,
+                offset,
+                search_value,
+                compare_type,
+                &prev_value,
+                endian,
+            ) {
                 // Add to results
-                let result = create_scan_result(buffer, offset, addr, search_value);
+                let result = create_scan_result(buffer, offset, addr, search_value, endian);
                 results.push(result);
             }
         }
     } else {
-        // First scan - check all memory
-        for offset in (0..buffer.len().saturating_sub(value_size)).step_by(alignment) {
+        // First scan - check all memory, including a value that ends exactly at the buffer end.
+        // Only a range compare applies here, any other compare type looks for the value itself.
+        if buffer.len() < value_size {
+            return results;
+        }
+        let range = match compare_type {
+            Some(CompareType::Between(min, max)) => Some((min, max)),
+            _ => None,
+        };
+        // Buffers may start anywhere, so align offsets to absolute addresses
+        let first_offset = (alignment - (base_addr % alignment as umem) as usize) % alignment;
+        for offset in (first_offset..=buffer.len() - value_size).step_by(alignment) {
             let matches = match search_value {
-                ScanValue::Integer(val) => {
-                    if offset + std::mem::size_of::<i64>() > buffer.len() {
+                ScanValue::Integer(val, width) => {
+                    if offset + width.size() > buffer.len() {
                         continue;
                     }
-                    let current_value = i64::from_ne_bytes(
-                        buffer[offset..offset + std::mem::size_of::<i64>()]
-                            .try_into()
-                            .unwrap_or([0; 8]),
-                    );
-                    current_value == *val
+                    let current_value = width.decode_int(&buffer[offset..], endian).unwrap_or(0);
+                    match range {
+                        Some((ScanValue::Integer(min, _), ScanValue::Integer(max, _))) => {
+                            int_in_range(width, current_value, *min, *max)
+                        }
+                        _ => current_value == *val,
+                    }
                 }
                 ScanValue::Float(val) => {
                     if offset + std::mem::size_of::<f32>() > buffer.len() {
                         continue;
                     }
-                    let current_value = f32::from_ne_bytes(
-                        buffer[offset..offset + std::mem::size_of::<f32>()]
-                            .try_into()
-                            .unwrap_or([0; 4]),
-                    );
-                    (current_value - *val).abs() < f32::EPSILON
+                    let current_value = ValueType::F32
+                        .decode_float(&buffer[offset..], endian)
+                        .unwrap_or(0.0) as f32;
+                    match range {
+                        Some((ScanValue::Float(min), ScanValue::Float(max))) => {
+                            in_range(current_value, *min, *max)
+                        }
+                        _ => (current_value - *val).abs() < f32::EPSILON,
+                    }
                 }
                 ScanValue::Double(val) => {
                     if offset + std::mem::size_of::<f64>() > buffer.len() {
                         continue;
                     }
-                    let current_value = f64::from_ne_bytes(
-                        buffer[offset..offset + std::mem::size_of::<f64>()]
-                            .try_into()
-                            .unwrap_or([0; 8]),
-                    );
-                    (current_value - *val).abs() < f64::EPSILON
+                    let current_value = ValueType::F64
+                        .decode_float(&buffer[offset..], endian)
+                        .unwrap_or(0.0);
+                    match range {
+                        Some((ScanValue::Double(min), ScanValue::Double(max))) => {
+                            in_range(current_value, *min, *max)
+                        }
+                        _ => (current_value - *val).abs() < f64::EPSILON,
+                    }
                 }
-                ScanValue::String(val) => {
+                ScanValue::String(val, case_insensitive) => {
                     if offset + val.len() > buffer.len() {
                         continue;
                     }
                     let slice = &buffer[offset..offset + val.len()];
-                    slice == val.as_bytes()
+                    string_matches(slice, val, *case_insensitive)
                 }
                 ScanValue::Bytes(val) => {
                     if offset + val.len() > buffer.len() {
@@ -1712,7 +3167,7 @@ fn scan_buffer(
 
             if matches {
                 let addr = base_addr + offset as umem;
-                let result = create_scan_result(buffer, offset, addr as i64, search_value);
+                let result = create_scan_result(buffer, offset, addr, search_value, endian);
                 results.push(result);
             }
         }
@@ -1725,8 +3180,9 @@ fn scan_buffer(
 fn create_scan_result(
     buffer: &[u8],
     offset: usize,
-    address: i64,
+    address: umem,
     search_value: &ScanValue,
+    endian: Endian,
 ) -> ScanResult {
     let mut result = ScanResult {
         address,
@@ -1735,37 +3191,34 @@ fn create_scan_result(
         value_double: 0.0,
         value_string: String::new(),
         value_bytes: Vec::new(),
+        raw: Vec::new(),
     };
 
+    if offset + search_value.size() <= buffer.len() {
+        result.raw = buffer[offset..offset + search_value.size()].to_vec();
+    }
+
     match search_value {
-        ScanValue::Integer(_) => {
-            if offset + std::mem::size_of::<i64>() <= buffer.len() {
-                result.value_int = i64::from_ne_bytes(
-                    buffer[offset..offset + std::mem::size_of::<i64>()]
-                        .try_into()
-                        .unwrap_or([0; 8]),
-                );
+        ScanValue::Integer(_, width) => {
+            if offset + width.size() <= buffer.len() {
+                result.value_int = width.decode_int(&buffer[offset..], endian).unwrap_or(0);
             }
         }
         ScanValue::Float(_) => {
             if offset + std::mem::size_of::<f32>() <= buffer.len() {
-                result.value_float = f32::from_ne_bytes(
-                    buffer[offset..offset + std::mem::size_of::<f32>()]
-                        .try_into()
-                        .unwrap_or([0; 4]),
-                );
+                result.value_float = ValueType::F32
+                    .decode_float(&buffer[offset..], endian)
+                    .unwrap_or(0.0) as f32;
             }
         }
         ScanValue::Double(_) => {
             if offset + std::mem::size_of::<f64>() <= buffer.len() {
-                result.value_double = f64::from_ne_bytes(
-                    buffer[offset..offset + std::mem::size_of::<f64>()]
-                        .try_into()
-                        .unwrap_or([0; 8]),
-                );
+                result.value_double = ValueType::F64
+                    .decode_float(&buffer[offset..], endian)
+                    .unwrap_or(0.0);
             }
         }
-        ScanValue::String(val) => {
+        ScanValue::String(val, _) => {
             if offset + val.len() <= buffer.len() {
                 let slice = &buffer[offset..offset + val.len()];
                 result.value_string = String::from_utf8_lossy(slice).to_string();
@@ -1792,15 +3245,39 @@ struct MemflowPatternScanShard {
     required: ExposedTypes,
 
     // Parameters
-    #[shard_param("Pattern", "Byte pattern to scan for (e.g., '48 8B ? ? 89 7C' or '48 8B [01001...] 89 7C'). Content in square brackets is treated as wildcards.", [common_type::string, common_type::string_var])]
+    #[shard_param("Pattern", "Byte pattern to scan for (e.g., '48 8B ? ? 89 7C', '48 8B ?? ?? 89 7C' or '48 8B [01001...] 89 7C'). Content in square brackets is treated as wildcards.", [common_type::string, common_type::string_var])]
     pattern: ParamVar,
 
+    #[shard_param("Mask", "Code-style mask (e.g., 'xx??xx'). When set, Pattern holds the raw bytes ('\\x48\\x8B...' or '48 8B ...') and '?' in the mask marks wildcards.", [common_type::none, common_type::string, common_type::string_var])]
+    mask: ParamVar,
+
     #[shard_param("MinSize", "Minimum size of memory regions to scan (default: 4096).", [common_type::none, common_type::int, common_type::int_var])]
     min_size: ParamVar,
 
     #[shard_param("Protection", "Memory protection to filter by (e.g., 'r--', 'rw-', 'r-x').", [common_type::none, common_type::string, common_type::string_var])]
     protection: ParamVar,
 
+    #[shard_param("Start", "Lowest address to scan (default: no limit).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    start: ParamVar,
+
+    #[shard_param("End", "Address to stop scanning at, exclusive (default: no limit).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    end: ParamVar,
+
+    #[shard_param("Module", "Module to scan. When set, only the module's memory is scanned and each result is a table with 'address', 'module' and 'offset' fields instead of a plain address.", [common_type::none, *MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Threads", "Number of worker threads scanning the read memory (default: 1).", [common_type::int, common_type::int_var])]
+    threads: ParamVar,
+
+    #[shard_param("Limit", "Maximum number of matches to return, lowest addresses first. The scan stops once it is reached (default: 0, no limit).", [common_type::int, common_type::int_var])]
+    limit: ParamVar,
+
+    #[shard_param("FirstOnly", "Stop at the first match, same as a Limit of 1 (default: false).", [common_type::bool, common_type::bool_var])]
+    first_only: ParamVar,
+
     // Output results
     scan_results: AutoSeqVar,
 }
@@ -1810,8 +3287,16 @@ impl Default for MemflowPatternScanShard {
         Self {
             required: ExposedTypes::new(),
             pattern: ParamVar::default(),
+            mask: ParamVar::default(),
             min_size: ParamVar::new(4096.into()),
             protection: ParamVar::default(),
+            start: ParamVar::default(),
+            end: ParamVar::default(),
+            module: ParamVar::default(),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            threads: ParamVar::new(1.into()),
+            limit: ParamVar::new(0.into()),
+            first_only: ParamVar::new(false.into()),
             scan_results: AutoSeqVar::new(),
         }
     }
@@ -1820,7 +3305,7 @@ impl Default for MemflowPatternScanShard {
 #[shards::shard_impl]
 impl Shard for MemflowPatternScanShard {
     fn input_types(&mut self) -> &Types {
-        &MEMFLOW_PROCESS_TYPES // Takes process as input
+        &MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
     }
 
     fn output_types(&mut self) -> &Types {
@@ -1848,13 +3333,8 @@ impl Shard for MemflowPatternScanShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<memflow_process_wrapper::MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
-        };
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
 
         // Get parameters
         let pattern_str: &str = self.pattern.get().as_ref().try_into()?;
@@ -1868,17 +3348,48 @@ impl Shard for MemflowPatternScanShard {
             Some(prot_str)
         };
 
+        // Get the module to report offsets against, if any
+        let module = if self.module.get().is_none() {
+            None
+        } else {
+            let module = unsafe {
+                &*Var::from_ref_counted_object::<memflow_module_wrapper::MemflowModuleWrapper>(
+                    self.module.get(),
+                    &*MEMFLOW_MODULE_TYPE,
+                )?
+            };
+            Some(module.0.clone())
+        };
+
         // Parse the pattern
-        let pattern = parse_pattern(pattern_str).map_err(|e| e)?;
+        let pattern = if self.mask.get().is_none() {
+            parse_pattern(pattern_str)?
+        } else {
+            let mask: &str = self.mask.get().as_ref().try_into()?;
+            parse_pattern_with_mask(pattern_str, mask)?
+        };
 
         if pattern.is_empty() {
             return Err("Empty pattern");
         }
 
+        let limit: i64 = self.limit.get().as_ref().try_into()?;
+        if limit < 0 {
+            return Err("Limit must not be negative");
+        }
+        let first_only: bool = self.first_only.get().as_ref().try_into()?;
+        let limit = if first_only {
+            Some(1)
+        } else if limit > 0 {
+            Some(limit as usize)
+        } else {
+            None
+        };
+
         shlog_debug!("Scanning memory with pattern: {}", pattern_str);
 
         // Get memory maps with filtering
-        let maps = process.0.mapped_mem_vec(0);
+        let maps = process.process().mapped_mem_vec(0);
         let filtered_maps: Vec<_> = maps
             .into_iter()
             .filter(|map| {
@@ -1903,41 +3414,415 @@ impl Shard for MemflowPatternScanShard {
 
         self.scan_results.0.clear();
 
-        for map in filtered_maps {
-            let base_addr = map.0.to_umem();
-            let size = map.1.to_umem() as usize;
+        // Merge adjacent regions so patterns spanning a region boundary are found
+        let regions = clip_regions(
+            coalesce_regions(&filtered_maps),
+            &self.start,
+            &self.end,
+            &self.module,
+        )?;
+
+        // Skip regions that are too small and split large ones into overlapping chunks
+        let overlap = pattern.len() - 1;
+        let chunk_size = chunk_size_param(&self.chunk_size, overlap)?;
+        let regions: Vec<(umem, usize)> = regions
+            .into_iter()
+            .filter(|(_, size)| *size >= pattern.len())
+            .flat_map(|(address, size)| split_region(address, size, chunk_size, overlap))
+            .collect();
 
-            // Skip regions that are too small
-            if size < pattern.len() {
-                continue;
+        // Scan the buffers for pattern matches
+        let threads: i64 = self.threads.get().as_ref().try_into()?;
+        let mut matches = scan_regions_limited(
+            &mut process,
+            &regions,
+            threads.max(1) as usize,
+            limit,
+            |base_addr, buffer| scan_pattern(buffer, &pattern, base_addr),
+        );
+        if limit.is_some_and(|limit| matches.len() >= limit) {
+            shlog_debug!("Pattern scan stopped early after {} matches", matches.len());
+        }
+
+        // Report matches in address order no matter how the scan was split up
+        matches.sort_unstable();
+
+        for match_ in matches {
+            let addr_var: Var = (match_ as i64).into();
+            match &module {
+                Some(module) => {
+                    let module_var = Var::ephemeral_string(&module.name);
+                    let offset_var: Var = ((match_ - module.base.to_umem()) as i64).into();
+
+                    let mut result_entry = AutoTableVar::new();
+                    result_entry.0.insert_fast_static("address", &addr_var);
+                    result_entry.0.insert_fast_static("module", &module_var);
+                    result_entry.0.insert_fast_static("offset", &offset_var);
+                    self.scan_results.0.emplace_table(result_entry);
+                }
+                None => self.scan_results.0.push(&addr_var),
             }
+        }
+
+        Ok(Some(self.scan_results.0 .0))
+    }
+}
+
+// Helper function to merge memory regions that are contiguous in the address space
+// (one region ends where the next one starts) into single (address, size) regions.
+// Regions separated by a gap are kept apart, so a match can never span a gap in
+// the address space.
+pub(crate) fn coalesce_regions(maps: &[MemoryRange]) -> Vec<(umem, usize)> {
+    let mut regions: Vec<(umem, usize)> = Vec::new();
+    for map in maps {
+        let address = map.0.to_umem();
+        let size = map.1.to_umem() as usize;
+        match regions.last_mut() {
+            Some((last_address, last_size)) if *last_address + *last_size as umem == address => {
+                *last_size += size;
+            }
+            _ => regions.push((address, size)),
+        }
+    }
+    regions
+}
+
+// Helper function to clip scan regions to the optional [Start, End) params and the
+// address range of the optional Module param, dropping regions entirely outside of them
+fn clip_regions(
+    regions: Vec<(umem, usize)>,
+    start: &ParamVar,
+    end: &ParamVar,
+    module: &ParamVar,
+) -> std::result::Result<Vec<(umem, usize)>, &'static str> {
+    if start.get().is_none() && end.get().is_none() && module.get().is_none() {
+        return Ok(regions);
+    }
+
+    let mut start: umem = if start.get().is_none() {
+        0
+    } else {
+        parse_address(start.get())?
+    };
+    let mut end: umem = if end.get().is_none() {
+        umem::MAX
+    } else {
+        parse_address(end.get())?
+    };
+    if start > end {
+        return Err("Start must not be greater than End");
+    }
+
+    if !module.get().is_none() {
+        let module = unsafe {
+            &*Var::from_ref_counted_object::<memflow_module_wrapper::MemflowModuleWrapper>(
+                module.get(),
+                &*MEMFLOW_MODULE_TYPE,
+            )?
+        };
+        let module_base = module.0.base.to_umem();
+        start = start.max(module_base);
+        end = end.min(module_base + module.0.size as umem);
+    }
 
-            // Read the memory region
-            let mut buffer = vec![0u8; size];
-            match process
-                .0
-                .read_raw_into(Address::from(base_addr), &mut buffer)
-            {
-                Ok(_) => {
-                    // Scan the buffer for pattern matches
-                    let matches = scan_pattern(&buffer, &pattern, base_addr);
-                    for match_ in matches {
-                        let addr_var: Var = match_.into();
-                        self.scan_results.0.push(&addr_var);
+    Ok(regions
+        .into_iter()
+        .filter_map(|(address, size)| clip_region(address, size as umem, start, end))
+        .collect())
+}
+
+// Default number of bytes read at once from a memory region while scanning
+pub(crate) const DEFAULT_CHUNK_SIZE: i64 = 4 * 1024 * 1024;
+
+// Helper function to read a ChunkSize param. Consecutive chunks overlap by `overlap`
+// bytes so matches crossing a chunk boundary are not lost, so a chunk must be larger.
+pub(crate) fn chunk_size_param(
+    param: &ParamVar,
+    overlap: usize,
+) -> std::result::Result<usize, &'static str> {
+    let chunk_size: i64 = param.get().as_ref().try_into()?;
+    if chunk_size <= overlap as i64 {
+        return Err("ChunkSize must be larger than the searched value or pattern.");
+    }
+    Ok(chunk_size as usize)
+}
+
+// Helper function to split a memory region into chunks of at most `chunk_size` bytes.
+// Each chunk starts `chunk_size - overlap` bytes after the previous one, so anything up
+// to `overlap + 1` bytes long is fully contained in exactly one chunk.
+pub(crate) fn split_region(
+    address: umem,
+    size: usize,
+    chunk_size: usize,
+    overlap: usize,
+) -> Vec<(umem, usize)> {
+    let step = chunk_size - overlap;
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    loop {
+        let len = chunk_size.min(size - offset);
+        chunks.push((address + offset as umem, len));
+        if offset + len >= size {
+            break;
+        }
+        offset += step;
+    }
+    chunks
+}
+
+// Maximum number of bytes buffered for a single batched read of scan regions
+const SCAN_BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+// Granularity at which scan regions that fail to read are retried
+const SCAN_PAGE_SIZE: umem = 0x1000;
+
+// Helper function to read scan regions, calling `scan` with each region's base address
+// and contents. Regions are read through the process batcher in groups of up to
+// SCAN_BATCH_BYTES and committed once per group. Regions larger than that, and groups
+// whose batched read fails, are read one region at a time instead. Cached processes
+// read every region through their page cache. Reading stops as soon as `scan` returns
+// false.
+fn read_regions(
+    process: &mut ProcessHandle,
+    regions: &[(umem, usize)],
+    mut scan: impl FnMut(umem, Vec<u8>) -> bool,
+) {
+    if process.is_cached() {
+        for region in regions {
+            if !read_region(process, *region, &mut scan) {
+                return;
+            }
+        }
+        return;
+    }
+
+    let mut start = 0;
+    while start < regions.len() {
+        // Gather as many regions as fit in the batch budget
+        let mut end = start;
+        let mut total = 0;
+        while end < regions.len() && total + regions[end].1 <= SCAN_BATCH_BYTES {
+            total += regions[end].1;
+            end += 1;
+        }
+
+        if end == start {
+            // Region is larger than the batch budget, read it on its own
+            if !read_region(process, regions[start], &mut scan) {
+                return;
+            }
+            start += 1;
+            continue;
+        }
+
+        let group = &regions[start..end];
+        let mut buffers: Vec<Vec<u8>> = group.iter().map(|(_, size)| vec![0u8; *size]).collect();
+        let committed = {
+            let mut batcher = process.process().batcher();
+            for ((address, _), buffer) in group.iter().zip(buffers.iter_mut()) {
+                batcher.read_raw_into(Address::from(*address), &mut buffer[..]);
+            }
+            batcher.commit_rw()
+        };
+
+        match committed {
+            Ok(_) => {
+                for ((address, _), buffer) in group.iter().zip(buffers) {
+                    if !scan(*address, buffer) {
+                        return;
                     }
                 }
-                Err(e) => {
-                    shlog_debug!("Failed to read memory region at 0x{:x}: {}", base_addr, e);
-                    continue;
+            }
+            Err(e) => {
+                shlog_debug!(
+                    "Batched region read failed, reading regions one by one: {}",
+                    e
+                );
+                drop(buffers);
+                for region in group {
+                    if !read_region(process, *region, &mut scan) {
+                        return;
+                    }
                 }
             }
         }
 
-        Ok(Some(self.scan_results.0 .0))
+        start = end;
+    }
+}
+
+// Helper function to read a single scan region and pass its contents to `scan`.
+// Returns false if `scan` asked to stop reading.
+fn read_region(
+    process: &mut ProcessHandle,
+    (address, size): (umem, usize),
+    scan: &mut impl FnMut(umem, Vec<u8>) -> bool,
+) -> bool {
+    let mut buffer = vec![0u8; size];
+    match process.read_raw_into(address, &mut buffer) {
+        Ok(_) => scan(address, buffer),
+        Err(e) => {
+            shlog_debug!(
+                "Failed to read memory region at 0x{:x}, reading it page by page: {}",
+                address,
+                e
+            );
+            read_region_pages(process, address, buffer, scan)
+        }
+    }
+}
+
+// Helper function to read a region that could not be read at once page by page, passing
+// each run of readable pages to `scan` on its own. A scan region can be merged from
+// several mapped regions, so one of them going away must not hide the others.
+// Returns false if `scan` asked to stop reading.
+fn read_region_pages(
+    process: &mut ProcessHandle,
+    address: umem,
+    mut buffer: Vec<u8>,
+    scan: &mut impl FnMut(umem, Vec<u8>) -> bool,
+) -> bool {
+    let size = buffer.len();
+    let mut run_start = None;
+    let mut offset = 0;
+    while offset < size {
+        let page_address = address + offset as umem;
+        let page_len =
+            ((SCAN_PAGE_SIZE - page_address % SCAN_PAGE_SIZE) as usize).min(size - offset);
+        let readable = process
+            .read_raw_into(page_address, &mut buffer[offset..offset + page_len])
+            .is_ok();
+
+        match (readable, run_start) {
+            (true, None) => run_start = Some(offset),
+            (false, Some(start)) => {
+                run_start = None;
+                if !scan(address + start as umem, buffer[start..offset].to_vec()) {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+        offset += page_len;
+    }
+
+    match run_start {
+        Some(0) => scan(address, buffer),
+        Some(start) => scan(address + start as umem, buffer.split_off(start)),
+        None => true,
+    }
+}
+
+// Helper function to run `scan` over the contents of every scan region and collect
+// its results in region order. With more than one thread, buffers are still read on
+// the calling thread (memflow handles are not Sync) and then scanned by a pool of
+// worker threads, one batch of up to SCAN_BATCH_BYTES at a time.
+pub(crate) fn scan_regions<R: Send>(
+    process: &mut ProcessHandle,
+    regions: &[(umem, usize)],
+    threads: usize,
+    scan: impl Fn(umem, &[u8]) -> Vec<R> + Sync,
+) -> Vec<R> {
+    scan_regions_limited(process, regions, threads, None, scan)
+}
+
+// Same as `scan_regions`, but stops reading further regions once `limit` results were
+// found. The results are cut to `limit`, keeping the ones from the first regions.
+pub(crate) fn scan_regions_limited<R: Send>(
+    process: &mut ProcessHandle,
+    regions: &[(umem, usize)],
+    threads: usize,
+    limit: Option<usize>,
+    scan: impl Fn(umem, &[u8]) -> Vec<R> + Sync,
+) -> Vec<R> {
+    let mut results = Vec::new();
+    let reached = |results: &Vec<R>| limit.is_some_and(|limit| results.len() >= limit);
+
+    if threads <= 1 {
+        read_regions(process, regions, |address, buffer| {
+            results.extend(scan(address, &buffer));
+            !reached(&results)
+        });
+    } else {
+        let mut pending: Vec<(umem, Vec<u8>)> = Vec::new();
+        let mut pending_bytes = 0;
+        read_regions(process, regions, |address, buffer| {
+            pending_bytes += buffer.len();
+            pending.push((address, buffer));
+            if pending_bytes >= SCAN_BATCH_BYTES {
+                results.extend(scan_buffers_parallel(&pending, threads, &scan));
+                pending.clear();
+                pending_bytes = 0;
+            }
+            !reached(&results)
+        });
+        if !reached(&results) {
+            results.extend(scan_buffers_parallel(&pending, threads, &scan));
+        }
+    }
+
+    if let Some(limit) = limit {
+        results.truncate(limit);
+    }
+    results
+}
+
+// Helper function to scan already read buffers on `threads` worker threads.
+// Results are returned in buffer order regardless of which worker produced them.
+fn scan_buffers_parallel<R: Send>(
+    buffers: &[(umem, Vec<u8>)],
+    threads: usize,
+    scan: &(impl Fn(umem, &[u8]) -> Vec<R> + Sync),
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let mut scanned: Vec<(usize, Vec<R>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(buffers.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut scanned = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((address, buffer)) = buffers.get(index) else {
+                            break;
+                        };
+                        scanned.push((index, scan(*address, buffer)));
+                    }
+                    scanned
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Scan worker thread panicked"))
+            .collect()
+    });
+
+    scanned.sort_by_key(|(index, _)| *index);
+    scanned
+        .into_iter()
+        .flat_map(|(_, results)| results)
+        .collect()
+}
+
+// Helper function to clip a memory region to the [start, end) bounds.
+// Returns the clipped base address and size, or None if they don't overlap.
+fn clip_region(
+    region_addr: umem,
+    region_size: umem,
+    start: umem,
+    end: umem,
+) -> Option<(umem, usize)> {
+    let clipped_start = region_addr.max(start);
+    let clipped_end = (region_addr + region_size).min(end);
+    if clipped_start >= clipped_end {
+        return None;
     }
+    Some((clipped_start, (clipped_end - clipped_start) as usize))
 }
 
 // Pattern element can be either a specific byte or a wildcard
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum PatternElement {
     Byte(u8),
     Wildcard,
@@ -1950,7 +3835,7 @@ fn parse_pattern(pattern: &str) -> std::result::Result<Vec<PatternElement>, &'st
 
     // Buffer to accumulate characters between whitespace
     let mut current_token = String::new();
-    
+
     while let Some(c) = chars.next() {
         match c {
             // Handle whitespace - process any accumulated token
@@ -1959,31 +3844,31 @@ fn parse_pattern(pattern: &str) -> std::result::Result<Vec<PatternElement>, &'st
                     process_token(&current_token, &mut result)?;
                     current_token.clear();
                 }
-            },
+            }
             // Handle opening bracket - start of a bracketed pattern
             '[' => {
                 if !current_token.is_empty() {
                     process_token(&current_token, &mut result)?;
                     current_token.clear();
                 }
-                
+
                 // Skip everything until the closing bracket
                 while let Some(c) = chars.next() {
                     if c == ']' {
                         break;
                     }
                 }
-                
+
                 // Add a wildcard for the bracketed content
                 result.push(PatternElement::Wildcard);
-            },
+            }
             // Normal character - add to current token
             _ => {
                 current_token.push(c);
             }
         }
     }
-    
+
     // Process any remaining token
     if !current_token.is_empty() {
         process_token(&current_token, &mut result)?;
@@ -1993,13 +3878,16 @@ fn parse_pattern(pattern: &str) -> std::result::Result<Vec<PatternElement>, &'st
 }
 
 // Helper function to process a token and add the appropriate pattern element
-fn process_token(token: &str, result: &mut Vec<PatternElement>) -> std::result::Result<(), &'static str> {
+fn process_token(
+    token: &str,
+    result: &mut Vec<PatternElement>,
+) -> std::result::Result<(), &'static str> {
     let token = token.trim();
     if token.is_empty() {
         return Ok(());
     }
-    
-    if token == "?" {
+
+    if token == "?" || token == "??" {
         result.push(PatternElement::Wildcard);
     } else {
         // Try to parse as hex byte
@@ -2008,15 +3896,50 @@ fn process_token(token: &str, result: &mut Vec<PatternElement>) -> std::result::
             Err(_) => return Err("Invalid pattern format"),
         }
     }
-    
+
     Ok(())
 }
 
+// Parse a code-style pattern: raw bytes plus a mask such as "xxxx??xx", where 'x'
+// keeps the byte at that position and '?' turns it into a wildcard. The bytes can be
+// given escaped ("\x48\x8B\x00") or as hex ("48 8B 00").
+fn parse_pattern_with_mask(
+    pattern: &str,
+    mask: &str,
+) -> std::result::Result<Vec<PatternElement>, &'static str> {
+    let hex = pattern.replace("\\x", " ");
+
+    let mut bytes = Vec::new();
+    for token in hex.split_whitespace() {
+        let byte = u8::from_str_radix(token, 16).map_err(|_| "Invalid pattern format")?;
+        bytes.push(byte);
+    }
+
+    if bytes.len() != mask.chars().count() {
+        return Err("Pattern and mask lengths differ");
+    }
+
+    bytes
+        .into_iter()
+        .zip(mask.chars())
+        .map(|(byte, m)| match m {
+            'x' | 'X' => Ok(PatternElement::Byte(byte)),
+            '?' => Ok(PatternElement::Wildcard),
+            _ => Err("Invalid mask format"),
+        })
+        .collect()
+}
+
 // Scan a buffer for pattern matches
-fn scan_pattern(buffer: &[u8], pattern: &[PatternElement], base_addr: umem) -> Vec<i64> {
+fn scan_pattern(buffer: &[u8], pattern: &[PatternElement], base_addr: umem) -> Vec<umem> {
     let mut results = Vec::new();
 
-    'outer: for i in 0..buffer.len().saturating_sub(pattern.len()) {
+    if buffer.len() < pattern.len() {
+        return results;
+    }
+
+    // Check every offset, including a match that ends exactly at the buffer end
+    'outer: for i in 0..=buffer.len() - pattern.len() {
         for (j, element) in pattern.iter().enumerate() {
             match element {
                 PatternElement::Byte(byte) => {
@@ -2031,8 +3954,7 @@ fn scan_pattern(buffer: &[u8], pattern: &[PatternElement], base_addr: umem) -> V
         }
 
         // If we get here, the pattern matched
-        let addr = base_addr + i as umem;
-        results.push(addr as i64);
+        results.push(base_addr + i as umem);
     }
 
     results
@@ -2046,19 +3968,386 @@ fn register_memflow_shards() {
     shlog_debug!("Registering Memflow Shards...");
 
     register_shard::<MemflowOsShard>();
+    register_shard::<os_shards::MemflowOsInfoShard>();
+    register_shard::<os_shards::MemflowOsPluginInfoShard>();
+    register_shard::<os_shards::MemflowConnectorShard>();
+    register_shard::<os_shards::MemflowOsFromConnectorShard>();
     register_shard::<MemflowProcessListShard>();
     register_shard::<MemflowProcessShard>();
     register_shard::<MemflowMemMapShard>();
     register_shard::<MemflowKernelModuleListShard>();
     register_shard::<MemflowModuleInfoShard>();
     register_shard::<MemflowReadMemoryShard>();
+    register_shard::<MemflowTryReadMemoryShard>();
     register_shard::<MemflowBatchReadMemoryShard>();
     register_shard::<MemflowProcessModuleListShard>();
     register_shard::<MemflowWriteMemoryShard>();
+    register_shard::<MemflowFillMemoryShard>();
+    register_shard::<MemflowCompareAndWriteShard>();
     register_shard::<MemflowBatchWriteMemoryShard>();
     register_shard::<MemflowMemoryScanShard>();
     register_shard::<MemflowPatternScanShard>();
     register_shard::<xref_shard::MemflowFunctionXrefShard>();
+    register_shard::<xref_shard::MemflowDataXrefShard>();
+    register_shard::<scan_modules_shard::MemflowScanModulesShard>();
+    register_shard::<inventory_shards::MemflowRescanPluginsShard>();
+    register_shard::<inventory_shards::MemflowInventoryListShard>();
+    register_shard::<process_shards::MemflowProcessEnvShard>();
+    register_shard::<process_shards::MemflowWaitForProcessShard>();
+    register_shard::<signature_shards::MemflowFindSignatureShard>();
+    register_shard::<signature_shards::MemflowMakeSignatureShard>();
+    register_shard::<value_shards::MemflowReadValueShard>();
+    register_shard::<value_shards::MemflowWriteValueShard>();
+    register_shard::<value_shards::MemflowBatchReadValuesShard>();
+    register_shard::<value_shards::MemflowReadStructShard>();
+    register_shard::<value_shards::MemflowReadArrayShard>();
+    register_shard::<pointer_shards::MemflowReadPointerChainShard>();
+    register_shard::<pointer_shards::MemflowPointerScanShard>();
+    register_shard::<pointer_shards::MemflowResolvePointerPathShard>();
+    register_shard::<string_shards::MemflowReadStringShard>();
+    register_shard::<string_shards::MemflowReadWideStringShard>();
+    register_shard::<string_shards::MemflowFindStringsShard>();
+    register_shard::<string_shards::MemflowRegexScanShard>();
+    register_shard::<physical_shards::MemflowVirtToPhysShard>();
+    register_shard::<physical_shards::MemflowPageMapShard>();
+    register_shard::<physical_shards::MemflowPhysReadShard>();
+    register_shard::<physical_shards::MemflowPhysWriteShard>();
+    register_shard::<cached_process::MemflowCachedProcessShard>();
+    register_shard::<keyboard_shards::MemflowKeyboardStateShard>();
+    register_shard::<module_shards::MemflowModuleExportsShard>();
+    register_shard::<module_shards::MemflowModuleImportsShard>();
+    register_shard::<module_shards::MemflowModuleSectionsShard>();
+    register_shard::<module_shards::MemflowPrimaryModuleShard>();
+    register_shard::<process_shards::MemflowProcessInfoShard>();
+    register_shard::<process_shards::MemflowProcessIsAliveShard>();
+    register_shard::<process_shards::MemflowProcessStateShard>();
+    register_shard::<process_shards::MemflowProcessByPidShard>();
+    register_shard::<process_shards::MemflowProcessByAddressShard>();
+    register_shard::<process_shards::MemflowProcessesByNameShard>();
+    register_shard::<module_shards::MemflowModuleByAddressShard>();
+    register_shard::<module_shards::MemflowResolveAddressShard>();
+    register_shard::<module_shards::MemflowExportAddressShard>();
+    register_shard::<module_shards::MemflowWaitForModuleShard>();
+    register_shard::<module_shards::MemflowFormatAddressShard>();
+    register_shard::<disasm_shards::MemflowDisassembleShard>();
+    register_shard::<disasm_shards::MemflowInstructionLengthShard>();
+    register_shard::<disasm_shards::MemflowFindFunctionStartShard>();
+    register_shard::<dump_shards::MemflowDumpProcessShard>();
+    register_shard::<dump_shards::MemflowDumpModuleShard>();
+    register_shard::<dump_shards::MemflowHexdumpShard>();
+    register_shard::<forensics_shards::MemflowSuspiciousRegionsShard>();
+    register_shard::<forensics_shards::MemflowRegionEntropyShard>();
+    register_shard::<forensics_shards::MemflowHashRegionShard>();
+    register_shard::<forensics_shards::MemflowDiffRegionShard>();
+    register_shard::<forensics_shards::MemflowDetectHooksShard>();
 
     shlog_debug!("Memflow Shards registered.");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f32_memory(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    fn f64_memory(values: &[f64]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    fn snapshot_addresses(
+        memory: &[u8],
+        snapshot: &[u8],
+        search_value: &ScanValue,
+        compare_type: CompareType,
+    ) -> Vec<umem> {
+        let size = search_value.size();
+        scan_snapshot(
+            memory,
+            0x1000,
+            snapshot,
+            0x1000,
+            search_value,
+            size,
+            &compare_type,
+            Endian::Little,
+        )
+        .into_iter()
+        .map(|result| result.address)
+        .collect()
+    }
+
+    #[test]
+    fn incremental_changed_scan_on_f32_array() {
+        let snapshot = f32_memory(&[1.0, 2.0, 3.0, 4.0]);
+        let memory = f32_memory(&[1.0, 2.5, 3.0, 3.0]);
+        let value = ScanValue::Float(0.0);
+
+        assert_eq!(
+            snapshot_addresses(&memory, &snapshot, &value, CompareType::Changed),
+            vec![0x1004, 0x100c]
+        );
+        assert_eq!(
+            snapshot_addresses(&memory, &snapshot, &value, CompareType::Unchanged),
+            vec![0x1000, 0x1008]
+        );
+        assert_eq!(
+            snapshot_addresses(&memory, &snapshot, &value, CompareType::Increased),
+            vec![0x1004]
+        );
+        assert_eq!(
+            snapshot_addresses(&memory, &snapshot, &value, CompareType::Decreased),
+            vec![0x100c]
+        );
+    }
+
+    // Compares the value at the start of `memory` with a previous float value
+    fn compare(memory: &[u8], value: &ScanValue, compare_type: CompareType, prev: f64) -> bool {
+        let prev = Var::new_float(prev);
+        compare_scan_value(memory, 0, value, &compare_type, &prev, Endian::Little)
+    }
+
+    #[test]
+    fn float_compares_use_epsilon() {
+        let value = ScanValue::Float(1.0);
+
+        // Within epsilon of both the searched and the previous value
+        let memory = f32_memory(&[1.0 + f32::EPSILON / 2.0]);
+        assert!(compare(&memory, &value, CompareType::Equal, 1.0));
+        assert!(!compare(&memory, &value, CompareType::NotEqual, 1.0));
+        assert!(compare(&memory, &value, CompareType::Unchanged, 1.0));
+        assert!(!compare(&memory, &value, CompareType::Changed, 1.0));
+
+        let memory = f32_memory(&[1.5]);
+        assert!(compare(&memory, &value, CompareType::Greater, 1.0));
+        assert!(!compare(&memory, &value, CompareType::Less, 1.0));
+        assert!(compare(&memory, &value, CompareType::Changed, 1.0));
+
+        let memory = f32_memory(&[0.5]);
+        assert!(compare(&memory, &value, CompareType::Less, 1.0));
+        assert!(!compare(&memory, &value, CompareType::Greater, 1.0));
+        assert!(compare(&memory, &value, CompareType::Decreased, 1.0));
+    }
+
+    #[test]
+    fn double_compares_use_epsilon() {
+        let value = ScanValue::Double(1.0);
+
+        let memory = f64_memory(&[1.0 + f64::EPSILON / 2.0]);
+        assert!(compare(&memory, &value, CompareType::Equal, 1.0));
+        assert!(compare(&memory, &value, CompareType::Unchanged, 1.0));
+        assert!(!compare(&memory, &value, CompareType::Changed, 1.0));
+
+        let memory = f64_memory(&[2.0]);
+        assert!(compare(&memory, &value, CompareType::Greater, 1.0));
+        assert!(!compare(&memory, &value, CompareType::Less, 1.0));
+        assert!(compare(&memory, &value, CompareType::Changed, 1.0));
+        assert!(compare(&memory, &value, CompareType::Increased, 1.0));
+    }
+
+    #[test]
+    fn incremental_changed_scan_on_f64_array() {
+        let snapshot = f64_memory(&[1.0, 2.0, 3.0]);
+        let memory = f64_memory(&[1.0, -2.0, 3.0]);
+        let value = ScanValue::Double(0.0);
+
+        assert_eq!(
+            snapshot_addresses(&memory, &snapshot, &value, CompareType::Changed),
+            vec![0x1008]
+        );
+        assert_eq!(
+            snapshot_addresses(&memory, &snapshot, &value, CompareType::Unchanged),
+            vec![0x1000, 0x1010]
+        );
+    }
+
+    #[test]
+    fn wildcard_styles_parse_to_same_pattern() {
+        let expected = vec![
+            PatternElement::Byte(0x48),
+            PatternElement::Byte(0x8B),
+            PatternElement::Wildcard,
+            PatternElement::Byte(0x89),
+        ];
+        assert_eq!(parse_pattern("48 8B ?? 89").unwrap(), expected);
+        assert_eq!(parse_pattern("48 8B ? 89").unwrap(), expected);
+        assert_eq!(parse_pattern("48 8B [00] 89").unwrap(), expected);
+        assert_eq!(
+            parse_pattern_with_mask("\\x48\\x8B\\x00\\x89", "xx?x").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_pattern_with_mask("48 8B 00 89", "xx?x").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        assert!(parse_pattern("48 8G").is_err());
+        assert!(parse_pattern("48 ??? 89").is_err());
+        assert!(parse_pattern_with_mask("48 8B 00", "xx").is_err());
+        assert!(parse_pattern_with_mask("48 8B", "xy").is_err());
+    }
+
+    fn pattern(bytes: &[u8]) -> Vec<PatternElement> {
+        bytes.iter().map(|&b| PatternElement::Byte(b)).collect()
+    }
+
+    #[test]
+    fn scan_pattern_finds_match_at_buffer_end() {
+        let buffer = [0x00, 0x11, 0x22, 0x48, 0x8B];
+        assert_eq!(
+            scan_pattern(&buffer, &pattern(&[0x48, 0x8B]), 0x1000),
+            vec![0x1003]
+        );
+        // A pattern as long as the buffer matches at its only offset
+        assert_eq!(
+            scan_pattern(&buffer, &pattern(&buffer), 0x1000),
+            vec![0x1000]
+        );
+    }
+
+    #[test]
+    fn scan_pattern_handles_short_buffers() {
+        assert!(scan_pattern(&[], &pattern(&[0x48]), 0x1000).is_empty());
+        assert!(scan_pattern(&[0x48], &pattern(&[0x48, 0x8B]), 0x1000).is_empty());
+    }
+
+    fn first_scan_addresses(buffer: &[u8], value: &ScanValue, alignment: usize) -> Vec<umem> {
+        scan_buffer(buffer, value, alignment, 0x1000, None, None, Endian::Little)
+            .into_iter()
+            .map(|result| result.address)
+            .collect()
+    }
+
+    #[test]
+    fn scan_buffer_finds_value_at_buffer_end() {
+        let value = ScanValue::Integer(0x1234_5678, ValueType::I32);
+        let buffer = [0, 0, 0, 0, 0x78, 0x56, 0x34, 0x12];
+        assert_eq!(first_scan_addresses(&buffer, &value, 4), vec![0x1004]);
+        assert_eq!(first_scan_addresses(&buffer, &value, 1), vec![0x1004]);
+        assert_eq!(first_scan_addresses(&buffer[4..], &value, 4), vec![0x1000]);
+
+        let value = ScanValue::Bytes(vec![0xAA, 0xBB]);
+        assert_eq!(
+            first_scan_addresses(&[0x00, 0xAA, 0xBB], &value, 1),
+            vec![0x1001]
+        );
+    }
+
+    #[test]
+    fn scan_buffer_handles_short_buffers() {
+        let value = ScanValue::Integer(0x1234_5678, ValueType::I32);
+        assert!(first_scan_addresses(&[], &value, 4).is_empty());
+        assert!(first_scan_addresses(&[0x78, 0x56, 0x34], &value, 1).is_empty());
+    }
+
+    #[test]
+    fn range_mapped_requires_one_covering_region() {
+        let regions = [(0x1000, 0x2000), (0x4000, 0x1000)];
+        assert!(range_mapped(&regions, 0x1000, 0x2000));
+        assert!(range_mapped(&regions, 0x2800, 0x800));
+        assert!(!range_mapped(&regions, 0x2800, 0x801));
+        assert!(!range_mapped(&regions, 0x2800, 0x2000));
+        assert!(!range_mapped(&regions, 0x800, 0x10));
+        assert!(!range_mapped(&regions, umem::MAX - 4, 0x10));
+    }
+
+    #[test]
+    fn high_addresses_round_trip_through_the_output() {
+        let address: umem = 0xFFFF_8000_0000_0000;
+        let hex = format_address_hex(address);
+        assert_eq!(hex, "0xffff800000000000");
+        assert_eq!(parse_address(&Var::ephemeral_string(&hex)), Ok(address));
+
+        // The int form is negative, but parses back to the same address
+        let int_var: Var = (address as i64).into();
+        assert_eq!(parse_address(&int_var), Ok(address));
+    }
+
+    #[test]
+    fn u64_compares_are_unsigned() {
+        let high: u64 = 0x8000_0000_0000_0010;
+        let memory = high.to_le_bytes();
+        let prev = Var::new_int(5);
+        let compare_u64 = |value: i64, compare_type: CompareType| {
+            let value = ScanValue::Integer(value, ValueType::U64);
+            compare_scan_value(&memory, 0, &value, &compare_type, &prev, Endian::Little)
+        };
+
+        assert!(compare_u64(5, CompareType::Greater));
+        assert!(!compare_u64(5, CompareType::Less));
+        assert!(compare_u64(0, CompareType::Increased));
+        assert!(!compare_u64(0, CompareType::Decreased));
+        let between = CompareType::Between(
+            ScanValue::Integer(0x7FFF_FFFF_FFFF_FFFF, ValueType::U64),
+            ScanValue::Integer(u64::MAX as i64, ValueType::U64),
+        );
+        assert!(compare_u64(0, between));
+
+        // The same bits as an i64 are negative and order below small values
+        let value = ScanValue::Integer(5, ValueType::I64);
+        assert!(compare_scan_value(
+            &memory,
+            0,
+            &value,
+            &CompareType::Less,
+            &prev,
+            Endian::Little
+        ));
+    }
+
+    #[test]
+    fn u64_range_scan_finds_high_values() {
+        let memory: Vec<u8> = [1u64, 0x8000_0000_0000_0000, u64::MAX]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let value = ScanValue::Integer(0, ValueType::U64);
+        let range = CompareType::Between(
+            ScanValue::Integer(0x8000_0000_0000_0000u64 as i64, ValueType::U64),
+            ScanValue::Integer(u64::MAX as i64, ValueType::U64),
+        );
+        let addresses: Vec<umem> = scan_buffer(
+            &memory,
+            &value,
+            8,
+            0x1000,
+            None,
+            Some(&range),
+            Endian::Little,
+        )
+        .into_iter()
+        .map(|result| result.address)
+        .collect();
+        assert_eq!(addresses, vec![0x1008, 0x1010]);
+    }
+
+    #[test]
+    fn snapshot_types_tell_value_types_and_byte_orders_apart() {
+        assert_eq!(snapshot_value_type("int", Endian::Little), "i64:little");
+        assert_eq!(
+            snapshot_value_type("i64", Endian::Little),
+            snapshot_value_type("int", Endian::Little)
+        );
+        assert_ne!(
+            snapshot_value_type("i32", Endian::Little),
+            snapshot_value_type("float", Endian::Little)
+        );
+        assert_ne!(
+            snapshot_value_type("i32", Endian::Little),
+            snapshot_value_type("i32", Endian::Big)
+        );
+        let native = if cfg!(target_endian = "big") {
+            Endian::Big
+        } else {
+            Endian::Little
+        };
+        assert_eq!(
+            snapshot_value_type("double", Endian::Native),
+            snapshot_value_type("double", native)
+        );
+    }
+}
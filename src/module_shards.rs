@@ -0,0 +1,997 @@
+use crate::cached_process::process_handle;
+use crate::memflow_module_wrapper::MemflowModuleWrapper;
+use crate::{
+    parse_address, MEMFLOW_CACHED_PROCESS_TYPE, MEMFLOW_CACHED_PROCESS_TYPE_VAR,
+    MEMFLOW_MODULE_TYPE, MEMFLOW_MODULE_TYPE_VAR, MEMFLOW_PROCESS_TYPE, MEMFLOW_PROCESS_TYPE_VAR,
+};
+
+use memflow::prelude::v1::*;
+use shards::core::suspend;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, WireState, ANYS_TYPES, ANY_TABLE_TYPES, INT_TYPES, STRING_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+use std::time::{Duration, Instant};
+
+// Helper function to get the module passed in a Module param
+fn module_param(param: &ParamVar) -> std::result::Result<&ModuleInfo, &'static str> {
+    let module = unsafe {
+        &*Var::from_ref_counted_object::<MemflowModuleWrapper>(param.get(), &*MEMFLOW_MODULE_TYPE)?
+    };
+    Ok(&module.0)
+}
+
+// Helper function to find the module whose [base, base + size) range contains an address
+pub fn module_containing(
+    process: &mut ProcessInstanceArcBox<'static>,
+    address: umem,
+) -> std::result::Result<ModuleInfo, &'static str> {
+    let modules = process.module_list().map_err(|e| {
+        shlog_error!("Failed to get process module list: {}", e);
+        "Failed to get process module list."
+    })?;
+
+    modules
+        .into_iter()
+        .find(|module| {
+            let base = module.base.to_umem();
+            address >= base && address - base < module.size
+        })
+        .ok_or("Address does not belong to any module.")
+}
+
+// Helper function to get the absolute address of a module export by name
+pub fn find_export(
+    process: &mut ProcessInstanceArcBox<'static>,
+    module: &ModuleInfo,
+    name: &str,
+    case_sensitive: bool,
+) -> std::result::Result<umem, &'static str> {
+    let exports = process.module_export_list(module).map_err(|e| {
+        shlog_error!("Failed to get exports of module '{}': {}", module.name, e);
+        "Failed to get module exports."
+    })?;
+
+    exports
+        .iter()
+        .find(|export| {
+            if case_sensitive {
+                &*export.name == name
+            } else {
+                export.name.eq_ignore_ascii_case(name)
+            }
+        })
+        .map(|export| module.base.to_umem() + export.offset)
+        .ok_or_else(|| {
+            shlog_error!("Export '{}' not found in module '{}'", name, module.name);
+            "Export not found in module."
+        })
+}
+
+// Helper function to fill a sequence with {name, offset, address} tables for the
+// symbols of a module, computing absolute addresses from the module base
+fn symbols_to_seq<'a>(
+    output: &mut AutoSeqVar,
+    module: &ModuleInfo,
+    symbols: impl Iterator<Item = (&'a str, umem)>,
+) {
+    output.0.clear();
+    for (name, offset) in symbols {
+        let name_var = Var::ephemeral_string(name);
+        let offset_var: Var = (offset as i64).into();
+        let address_var: Var = ((module.base.to_umem() + offset) as i64).into();
+
+        let mut entry = AutoTableVar::new();
+        entry.0.insert_fast_static("name", &name_var);
+        entry.0.insert_fast_static("offset", &offset_var);
+        entry.0.insert_fast_static("address", &address_var);
+        output.0.emplace_table(entry);
+    }
+}
+
+// Define the ModuleExports Shard
+#[derive(shards::shard)]
+#[shard_info("Memflow.ModuleExports", "Returns the exported symbols of a module.")]
+pub struct MemflowModuleExportsShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The module to list the exports of.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    // Output list of exports as sequence of tables
+    exports: AutoSeqVar,
+}
+
+impl Default for MemflowModuleExportsShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            exports: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowModuleExportsShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of export tables
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.exports = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+        let module = module_param(&self.module)?;
+
+        // Modules without an export directory simply have no exports
+        let exports = process
+            .process()
+            .module_export_list(module)
+            .unwrap_or_else(|e| {
+                shlog_debug!("No exports for module '{}': {}", module.name, e);
+                Vec::new()
+            });
+
+        symbols_to_seq(
+            &mut self.exports,
+            module,
+            exports.iter().map(|export| (&*export.name, export.offset)),
+        );
+
+        Ok(Some(self.exports.0 .0))
+    }
+}
+
+// Define the ModuleImports Shard
+#[derive(shards::shard)]
+#[shard_info("Memflow.ModuleImports", "Returns the imported symbols of a module.")]
+pub struct MemflowModuleImportsShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The module to list the imports of.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    // Output list of imports as sequence of tables
+    imports: AutoSeqVar,
+}
+
+impl Default for MemflowModuleImportsShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            imports: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowModuleImportsShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of import tables
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.imports = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+        let module = module_param(&self.module)?;
+
+        // Modules without an import directory simply have no imports
+        let imports = process
+            .process()
+            .module_import_list(module)
+            .unwrap_or_else(|e| {
+                shlog_debug!("No imports for module '{}': {}", module.name, e);
+                Vec::new()
+            });
+
+        symbols_to_seq(
+            &mut self.imports,
+            module,
+            imports.iter().map(|import| (&*import.name, import.offset)),
+        );
+
+        Ok(Some(self.imports.0 .0))
+    }
+}
+
+// Define the ModuleSections Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ModuleSections",
+    "Returns the sections of a module with their absolute base addresses and sizes."
+)]
+pub struct MemflowModuleSectionsShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The module to list the sections of.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    // Output list of sections as sequence of tables
+    sections: AutoSeqVar,
+}
+
+impl Default for MemflowModuleSectionsShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            sections: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowModuleSectionsShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of section tables
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.sections = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+        let module = module_param(&self.module)?;
+
+        let sections = process.process().module_section_list(module).map_err(|e| {
+            shlog_error!("Failed to get sections of module '{}': {}", module.name, e);
+            "Failed to get module sections."
+        })?;
+
+        self.sections.0.clear();
+        for section in sections {
+            let name = Var::ephemeral_string(&section.name);
+            let base: Var = (section.base.to_umem() as i64).into();
+            let size: Var = (section.size as i64).into();
+
+            let mut entry = AutoTableVar::new();
+            entry.0.insert_fast_static("name", &name);
+            entry.0.insert_fast_static("base", &base);
+            entry.0.insert_fast_static("size", &size);
+            self.sections.0.emplace_table(entry);
+        }
+
+        Ok(Some(self.sections.0 .0))
+    }
+}
+
+// Define the PrimaryModule Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.PrimaryModule",
+    "Gets the primary module (the main executable image) of a process."
+)]
+pub struct MemflowPrimaryModuleShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Store the output Module object
+    output_module: ClonedVar,
+}
+
+impl Default for MemflowPrimaryModuleShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            output_module: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowPrimaryModuleShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_MODULE_TYPES // Outputs our custom Module object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Module instance when the shard is cleaned up
+        self.output_module = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let module_info = process.process().primary_module().map_err(|e| {
+            shlog_error!("Failed to get primary module: {}", e);
+            "Failed to determine the primary module of the process."
+        })?;
+
+        shlog_debug!("Primary module: {}", module_info.name);
+
+        // Create and return the module object
+        self.output_module =
+            Var::new_ref_counted(MemflowModuleWrapper(module_info), &MEMFLOW_MODULE_TYPE).into();
+
+        Ok(Some(self.output_module.0))
+    }
+}
+
+// Define the ModuleByAddress Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ModuleByAddress",
+    "Gets the module of a process that contains a specific address."
+)]
+pub struct MemflowModuleByAddressShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Absolute address to look up.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    // Store the output Module object
+    output_module: ClonedVar,
+}
+
+impl Default for MemflowModuleByAddressShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            output_module: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowModuleByAddressShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_MODULE_TYPES // Outputs our custom Module object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Module instance when the shard is cleaned up
+        self.output_module = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let address_umem = parse_address(self.address.get())?;
+
+        shlog_debug!(
+            "Searching for module containing address: 0x{:x}",
+            address_umem
+        );
+
+        let module_info = module_containing(process.process(), address_umem)?;
+
+        // Create and return the module object
+        self.output_module =
+            Var::new_ref_counted(MemflowModuleWrapper(module_info), &MEMFLOW_MODULE_TYPE).into();
+
+        Ok(Some(self.output_module.0))
+    }
+}
+
+// Helper function to find the export closest at or before `module_offset`, returning its
+// name and the distance from it
+fn nearest_export(exports: &[ExportInfo], module_offset: umem) -> Option<(&str, umem)> {
+    exports
+        .iter()
+        .filter(|export| export.offset <= module_offset)
+        .max_by_key(|export| export.offset)
+        .map(|export| (&*export.name, module_offset - export.offset))
+}
+
+// Define the ResolveAddress Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ResolveAddress",
+    "Resolves an absolute address to its module and the nearest preceding export."
+)]
+pub struct MemflowResolveAddressShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Absolute address to resolve.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    // Output table with the resolved symbol
+    resolved: AutoTableVar,
+}
+
+impl Default for MemflowResolveAddressShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            resolved: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowResolveAddressShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {module, module_offset, export, export_offset}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.resolved = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let address_umem = parse_address(self.address.get())?;
+
+        let module = module_containing(process.process(), address_umem)?;
+        let module_offset = address_umem - module.base.to_umem();
+
+        // Find the closest export at or before the address, if there is one
+        let exports = process
+            .process()
+            .module_export_list(&module)
+            .unwrap_or_else(|e| {
+                shlog_debug!("No exports for module '{}': {}", module.name, e);
+                Vec::new()
+            });
+        let export = nearest_export(&exports, module_offset);
+
+        self.resolved.0.clear();
+
+        let module_var = Var::ephemeral_string(&module.name);
+        let module_offset_var: Var = (module_offset as i64).into();
+        self.resolved.0.insert_fast_static("module", &module_var);
+        self.resolved
+            .0
+            .insert_fast_static("module_offset", &module_offset_var);
+
+        if let Some((export_name, export_offset)) = export {
+            let export_var = Var::ephemeral_string(export_name);
+            let export_offset_var: Var = (export_offset as i64).into();
+            self.resolved.0.insert_fast_static("export", &export_var);
+            self.resolved
+                .0
+                .insert_fast_static("export_offset", &export_offset_var);
+        }
+
+        Ok(Some(self.resolved.0 .0))
+    }
+}
+
+// Define the ExportAddress Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ExportAddress",
+    "Gets the absolute address of an exported symbol of a module."
+)]
+pub struct MemflowExportAddressShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The module exporting the symbol.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("Name", "Name of the export to look up.", [common_type::string, common_type::string_var])]
+    name: ParamVar,
+
+    #[shard_param("CaseSensitive", "Whether the name must match case exactly (default: true).", [common_type::bool, common_type::bool_var])]
+    case_sensitive: ParamVar,
+}
+
+impl Default for MemflowExportAddressShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            name: ParamVar::default(),
+            case_sensitive: ParamVar::new(true.into()),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowExportAddressShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &INT_TYPES // Outputs the export address
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+        let module = module_param(&self.module)?;
+        let name: &str = self.name.get().as_ref().try_into()?;
+        let case_sensitive: bool = self.case_sensitive.get().as_ref().try_into()?;
+
+        shlog_debug!("Looking up export '{}' in module '{}'", name, module.name);
+
+        let address = find_export(process.process(), module, name, case_sensitive)?;
+        Ok(Some((address as i64).into()))
+    }
+}
+
+// Define the WaitForModule Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.WaitForModule",
+    "Waits until a module with the given name is loaded in the input process."
+)]
+pub struct MemflowWaitForModuleShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Name", "Module name to wait for.", [common_type::string, common_type::string_var])]
+    module_name: ParamVar,
+
+    #[shard_param("Timeout", "Maximum time to wait in milliseconds (0 waits forever).", [common_type::int, common_type::int_var])]
+    timeout: ParamVar,
+
+    #[shard_param("PollInterval", "Time between two looks at the module list in milliseconds (default: 100).", [common_type::int, common_type::int_var])]
+    poll_interval: ParamVar,
+
+    // Store the output Module object
+    output_module: ClonedVar,
+}
+
+impl Default for MemflowWaitForModuleShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module_name: ParamVar::default(),
+            timeout: ParamVar::new(0.into()),
+            poll_interval: ParamVar::new(100.into()),
+            output_module: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowWaitForModuleShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_MODULE_TYPES // Outputs our custom Module object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Module instance when the shard is cleaned up
+        self.output_module = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let name: &str = self.module_name.get().as_ref().try_into()?;
+        let timeout: i64 = self.timeout.get().as_ref().try_into()?;
+        if timeout < 0 {
+            return Err("Timeout must not be negative");
+        }
+        let poll_interval: i64 = self.poll_interval.get().as_ref().try_into()?;
+        if poll_interval <= 0 {
+            return Err("PollInterval must be greater than 0");
+        }
+
+        shlog_debug!("Waiting for module '{}' (timeout: {} ms)", name, timeout);
+
+        let deadline = Duration::from_millis(timeout as u64);
+        let start = Instant::now();
+
+        let module_info = loop {
+            if let Ok(module) = process.process().module_by_name(name) {
+                break module;
+            }
+
+            if timeout != 0 && start.elapsed() >= deadline {
+                shlog_error!("Timed out waiting for module '{}'", name);
+                return Err("Timed out waiting for module.");
+            }
+
+            // Yield back to the scheduler, this also lets the flow cancel the wait
+            if suspend(context, poll_interval as f64 / 1000.0) != WireState::Continue {
+                return Err("Waiting for module was cancelled.");
+            }
+        };
+
+        // Create and return the module object
+        self.output_module =
+            Var::new_ref_counted(MemflowModuleWrapper(module_info), &MEMFLOW_MODULE_TYPE).into();
+
+        Ok(Some(self.output_module.0))
+    }
+}
+
+// Helper function to format an address as hex, zero-padded to `width` digits, with an
+// optional separator between the high and low 32 bits (e.g. "`" for the WinDbg style)
+fn format_hex(value: umem, width: usize, uppercase: bool, prefix: bool, separator: &str) -> String {
+    let mut digits = if uppercase {
+        format!("{:0width$X}", value, width = width)
+    } else {
+        format!("{:0width$x}", value, width = width)
+    };
+    if !separator.is_empty() && digits.len() > 8 {
+        digits.insert_str(digits.len() - 8, separator);
+    }
+    if prefix {
+        digits.insert_str(0, "0x");
+    }
+    digits
+}
+
+// Largest Width of a formatted address, the number of hex digits of a 64-bit address
+const MAX_ADDRESS_WIDTH: i64 = 16;
+
+// Helper function to format an offset into a module as "module!export+0x..", or as
+// "module+0x.." when no export precedes it
+fn format_module_offset(
+    module: &str,
+    export: Option<(&str, umem)>,
+    module_offset: umem,
+    uppercase: bool,
+    prefix: bool,
+) -> String {
+    match export {
+        Some((name, 0)) => format!("{}!{}", module, name),
+        Some((name, offset)) => format!(
+            "{}!{}+{}",
+            module,
+            name,
+            format_hex(offset, 0, uppercase, prefix, "")
+        ),
+        None => format!(
+            "{}+{}",
+            module,
+            format_hex(module_offset, 0, uppercase, prefix, "")
+        ),
+    }
+}
+
+// Define the FormatAddress Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.FormatAddress",
+    "Formats an address as a hex string, optionally relative to a module and its nearest preceding export."
+)]
+pub struct MemflowFormatAddressShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Width", "Number of hex digits to zero-pad the address to, 0 to 16 (default: 16, 0 for no padding).", [common_type::int, common_type::int_var])]
+    width: ParamVar,
+
+    #[shard_param("Uppercase", "Whether to use uppercase hex digits (default: true).", [common_type::bool, common_type::bool_var])]
+    uppercase: ParamVar,
+
+    #[shard_param("Prefix", "Whether to prefix the hex digits with '0x' (default: true).", [common_type::bool, common_type::bool_var])]
+    prefix: ParamVar,
+
+    #[shard_param("Separator", "Text inserted between the high and low 32 bits of the address (e.g., '`').", [common_type::none, common_type::string, common_type::string_var])]
+    separator: ParamVar,
+
+    #[shard_param("Module", "When set and the address is inside this module, formats it as 'module!export+0x..', or as 'module+0x..' when no export precedes it.", [common_type::none, *MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("Process", "The plain or cached process Module belongs to, used to look up its exports. Without it, addresses inside Module are formatted as 'module+0x..'.", [common_type::none, *MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
+    process_instance: ParamVar,
+
+    // Store the output string
+    formatted: ClonedVar,
+}
+
+impl Default for MemflowFormatAddressShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            width: ParamVar::new(16.into()),
+            uppercase: ParamVar::new(true.into()),
+            prefix: ParamVar::new(true.into()),
+            separator: ParamVar::default(),
+            module: ParamVar::default(),
+            process_instance: ParamVar::default(),
+            formatted: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowFormatAddressShard {
+    fn input_types(&mut self) -> &Types {
+        &INT_TYPES // Takes an address as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &STRING_TYPES // Outputs the formatted address
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.formatted = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        let address = parse_address(input)?;
+
+        let width: i64 = self.width.get().as_ref().try_into()?;
+        if !(0..=MAX_ADDRESS_WIDTH).contains(&width) {
+            return Err("Width must be between 0 and 16");
+        }
+        let uppercase: bool = self.uppercase.get().as_ref().try_into()?;
+        let prefix: bool = self.prefix.get().as_ref().try_into()?;
+        let separator: &str = if self.separator.get().is_none() {
+            ""
+        } else {
+            self.separator.get().as_ref().try_into()?
+        };
+
+        let module = if self.module.get().is_none() {
+            None
+        } else {
+            Some(module_param(&self.module)?)
+        };
+
+        // Render addresses inside the module relative to its nearest preceding export when
+        // the process is known, like ResolveAddress, or else to the module base
+        let formatted = match module {
+            Some(module)
+                if address >= module.base.to_umem()
+                    && address - module.base.to_umem() < module.size =>
+            {
+                let module_offset = address - module.base.to_umem();
+                let exports = if self.process_instance.get().is_none() {
+                    Vec::new()
+                } else {
+                    let mut process = process_handle(self.process_instance.get())?;
+                    process
+                        .process()
+                        .module_export_list(module)
+                        .unwrap_or_else(|e| {
+                            shlog_debug!("No exports for module '{}': {}", module.name, e);
+                            Vec::new()
+                        })
+                };
+                format_module_offset(
+                    &module.name,
+                    nearest_export(&exports, module_offset),
+                    module_offset,
+                    uppercase,
+                    prefix,
+                )
+            }
+            _ => format_hex(address, width as usize, uppercase, prefix, separator),
+        };
+
+        self.formatted = Var::ephemeral_string(&formatted).into();
+        Ok(Some(self.formatted.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_hex_pads_and_separates() {
+        assert_eq!(
+            format_hex(0x7FF6_1234_5678, 16, true, true, "`"),
+            "0x00007FF6`12345678"
+        );
+        assert_eq!(format_hex(0xABC, 0, false, false, ""), "abc");
+        assert_eq!(
+            format_hex(u64::MAX as umem, 16, false, true, ""),
+            "0xffffffffffffffff"
+        );
+    }
+
+    #[test]
+    fn format_module_offset_prefers_exports() {
+        assert_eq!(
+            format_module_offset("kernel32.dll", Some(("Sleep", 0x12)), 0x1012, true, true),
+            "kernel32.dll!Sleep+0x12"
+        );
+        assert_eq!(
+            format_module_offset("kernel32.dll", Some(("Sleep", 0)), 0x1000, true, true),
+            "kernel32.dll!Sleep"
+        );
+        assert_eq!(
+            format_module_offset("game.exe", None, 0x1abc, false, true),
+            "game.exe+0x1abc"
+        );
+    }
+}
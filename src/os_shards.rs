@@ -0,0 +1,429 @@
+use crate::inventory_shards::with_inventory;
+use crate::memflow_connector_wrapper::MemflowConnectorWrapper;
+use crate::memflow_os_wrapper::{MemflowOsWrapper, OsOrigin};
+use crate::{MEMFLOW_CONNECTOR_TYPE, MEMFLOW_OS_TYPE, MEMFLOW_OS_TYPE_VAR};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData, ParamVar, Type,
+    Types, Var, ANY_TABLE_TYPES, NONE_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Helper function to parse connector arguments, empty arguments keep the plugin defaults
+pub fn parse_connector_args(args: &str) -> std::result::Result<ConnectorArgs, &'static str> {
+    if args.is_empty() {
+        return Ok(ConnectorArgs::default());
+    }
+    args.parse().map_err(|e| {
+        shlog_error!("Failed to parse connector arguments: {}", e);
+        "Failed to parse connector arguments."
+    })
+}
+
+// Helper function to parse OS arguments, empty arguments keep the plugin defaults
+pub fn parse_os_args(args: &str) -> std::result::Result<OsArgs, &'static str> {
+    if args.is_empty() {
+        return Ok(OsArgs::default());
+    }
+    args.parse().map_err(|e| {
+        shlog_error!("Failed to parse OS arguments: {}", e);
+        "Failed to parse OS arguments."
+    })
+}
+
+// Define the OsInfo Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.OsInfo",
+    "Returns the kernel base, size and architecture of an OS instance, along with the OS plugin it was created with."
+)]
+pub struct MemflowOsInfoShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to describe.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    // Output table of OS information
+    info: AutoTableVar,
+}
+
+impl Default for MemflowOsInfoShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            info: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowOsInfoShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table of OS information
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.info = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let info = os.0.info();
+        shlog_debug!("OS '{}' info: {:?}", os.1.os, info);
+
+        let mut table = AutoTableVar::new();
+
+        let base: Var = (info.base.to_umem() as i64).into();
+        table.0.insert_fast_static("base", &base);
+
+        let size: Var = (info.size as i64).into();
+        table.0.insert_fast_static("size", &size);
+
+        let arch = Var::ephemeral_string(&format!("{:?}", info.arch));
+        table.0.insert_fast_static("arch", &arch);
+
+        let plugin = Var::ephemeral_string(&os.1.os);
+        table.0.insert_fast_static("plugin", &plugin);
+
+        self.info = table;
+        Ok(Some(self.info.0 .0))
+    }
+}
+
+// Define the OsPluginInfo Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.OsPluginInfo",
+    "Returns the connector, OS plugin and arguments an OS instance was created with."
+)]
+pub struct MemflowOsPluginInfoShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to describe.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    // Output table of plugin information
+    info: AutoTableVar,
+}
+
+impl Default for MemflowOsPluginInfoShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            info: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowOsPluginInfoShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table of plugin information
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.info = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &*Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+        let origin = &os.1;
+
+        // Unset connector and arguments are reported as empty strings
+        let mut table = AutoTableVar::new();
+
+        let connector = Var::ephemeral_string(&origin.connector);
+        table.0.insert_fast_static("connector", &connector);
+
+        let os_name = Var::ephemeral_string(&origin.os);
+        table.0.insert_fast_static("os", &os_name);
+
+        let connector_args = Var::ephemeral_string(&origin.connector_args);
+        table
+            .0
+            .insert_fast_static("connector_args", &connector_args);
+
+        let os_args = Var::ephemeral_string(&origin.os_args);
+        table.0.insert_fast_static("os_args", &os_args);
+
+        self.info = table;
+        Ok(Some(self.info.0 .0))
+    }
+}
+
+// Define the Connector Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.Connector",
+    "Creates a memflow connector on its own, to inspect physical memory or build OS instances on top of it."
+)]
+pub struct MemflowConnectorShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Connector", "The name of the memflow connector to use.", [common_type::string])]
+    connector_name: ClonedVar,
+    #[shard_param("ConnectorArgs", "Arguments passed to the connector (e.g., the VM name for 'qemu' or the file for 'coredump').", [common_type::none, common_type::string])]
+    connector_args: ClonedVar,
+    #[shard_param("RescanPlugins", "Rescan the plugin directories instead of reusing the cached plugin inventory.", [common_type::bool])]
+    rescan_plugins: ClonedVar,
+    #[shard_param("PluginPath", "Additional directory to load memflow plugins from.", [common_type::none, common_type::string])]
+    plugin_path: ClonedVar,
+
+    // Store the output Connector object
+    output_connector: ClonedVar,
+}
+
+impl Default for MemflowConnectorShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            connector_name: ClonedVar::default(),
+            connector_args: ClonedVar::default(),
+            rescan_plugins: Var::new_bool(false).into(),
+            plugin_path: ClonedVar::default(),
+            output_connector: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowConnectorShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_CONNECTOR_TYPES // Outputs our custom Connector object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Connector instance when the shard is cleaned up
+        self.output_connector = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Retrieve parameters
+        let connector_name: &str = self.connector_name.0.as_ref().try_into()?;
+        let connector_args_str: &str = self.connector_args.0.as_ref().try_into().unwrap_or("");
+        let rescan_plugins: bool = self.rescan_plugins.0.as_ref().try_into().unwrap_or(false);
+        let plugin_path: &str = self.plugin_path.0.as_ref().try_into().unwrap_or("");
+
+        shlog_debug!(
+            "Attempting to create connector: '{}' ('{}')",
+            connector_name,
+            connector_args_str
+        );
+
+        let connector_args = parse_connector_args(connector_args_str)?;
+
+        // Create the connector from the cached inventory
+        let connector = with_inventory(rescan_plugins, Some(plugin_path), |inventory| {
+            inventory
+                .builder()
+                .connector(connector_name)
+                .args(connector_args)
+                .build()
+        })?
+        .map_err(|e| {
+            shlog_error!("Failed to create connector '{}': {}", connector_name, e);
+            "Failed to create connector."
+        })?;
+
+        self.output_connector = Var::new_ref_counted(
+            MemflowConnectorWrapper(
+                connector,
+                connector_name.to_string(),
+                connector_args_str.to_string(),
+            ),
+            &MEMFLOW_CONNECTOR_TYPE,
+        )
+        .into();
+
+        Ok(Some(self.output_connector.0))
+    }
+}
+
+// Define the OsFromConnector Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.OsFromConnector",
+    "Creates a Memflow OS instance on top of an existing connector."
+)]
+pub struct MemflowOsFromConnectorShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The name of the OS plugin to use (e.g., 'win32', 'linux').", [common_type::string])]
+    os_name: ClonedVar,
+    #[shard_param("OsArgs", "Arguments passed to the OS plugin.", [common_type::none, common_type::string])]
+    os_args: ClonedVar,
+
+    // Store the output OS object
+    output_os: ClonedVar,
+}
+
+impl Default for MemflowOsFromConnectorShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_name: ClonedVar::default(),
+            os_args: ClonedVar::default(),
+            output_os: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowOsFromConnectorShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_CONNECTOR_TYPES // Takes connector as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_OS_TYPES // Outputs our custom OS object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the OS instance when the shard is cleaned up
+        self.output_os = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the Connector instance from input
+        let connector = unsafe {
+            &*Var::from_ref_counted_object::<MemflowConnectorWrapper>(
+                input,
+                &*MEMFLOW_CONNECTOR_TYPE,
+            )?
+        };
+
+        let os_name: &str = self.os_name.0.as_ref().try_into()?;
+        let os_args_str: &str = self.os_args.0.as_ref().try_into().unwrap_or("");
+
+        shlog_debug!(
+            "Attempting to create OS instance: os='{}' ('{}') on connector '{}'",
+            os_name,
+            os_args_str,
+            connector.1
+        );
+
+        let os_args = parse_os_args(os_args_str)?;
+
+        // The OS takes its own clone of the connector, so several OS instances can share it
+        let os = with_inventory(false, None, |inventory| {
+            inventory.create_os(os_name, Some(connector.0.clone()), Some(&os_args))
+        })?
+        .map_err(|e| {
+            shlog_error!("Failed to create OS instance: {}", e);
+            "Failed to create OS instance."
+        })?;
+
+        self.output_os = Var::new_ref_counted(
+            MemflowOsWrapper(
+                os,
+                OsOrigin {
+                    connector: connector.1.clone(),
+                    os: os_name.to_string(),
+                    connector_args: connector.2.clone(),
+                    os_args: os_args_str.to_string(),
+                },
+            ),
+            &MEMFLOW_OS_TYPE,
+        )
+        .into();
+
+        Ok(Some(self.output_os.0))
+    }
+}
@@ -0,0 +1,362 @@
+// Minimal PE file parsing, just enough to map the sections of a module file the way
+// the loader lays them out in memory and to apply its base relocations.
+
+// Section characteristics flag for executable sections
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+// Data directory indices
+const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+const IMAGE_DIRECTORY_ENTRY_IAT: usize = 12;
+
+// Base relocation types
+const IMAGE_REL_BASED_ABSOLUTE: u16 = 0;
+const IMAGE_REL_BASED_HIGHLOW: u16 = 3;
+const IMAGE_REL_BASED_DIR64: u16 = 10;
+
+pub struct PeSection {
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    pub raw_offset: u32,
+    pub raw_size: u32,
+    pub characteristics: u32,
+}
+
+pub struct PeImage<'a> {
+    file: &'a [u8],
+    pub image_base: u64,
+    pub sections: Vec<PeSection>,
+    reloc_dir: (u32, u32),
+    pub iat_dir: (u32, u32),
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, &'static str> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or("PE file is truncated")
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, &'static str> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or("PE file is truncated")
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, &'static str> {
+    Ok(read_u32(data, offset)? as u64 | (read_u32(data, offset + 4)? as u64) << 32)
+}
+
+impl<'a> PeImage<'a> {
+    // Parse the headers and section table of a PE file
+    pub fn parse(file: &'a [u8]) -> Result<PeImage<'a>, &'static str> {
+        if file.get(0..2) != Some(b"MZ") {
+            return Err("Not a PE file (missing MZ header)");
+        }
+        let nt = read_u32(file, 0x3C)? as usize;
+        if file.get(nt..nt + 4) != Some(b"PE\0\0") {
+            return Err("Not a PE file (missing PE signature)");
+        }
+
+        let file_header = nt + 4;
+        let section_count = read_u16(file, file_header + 2)? as usize;
+        let optional_header_size = read_u16(file, file_header + 16)? as usize;
+        let optional_header = file_header + 20;
+
+        // PE32 and PE32+ differ in the width of the image base and the header layout
+        let (image_base, directories) = match read_u16(file, optional_header)? {
+            0x10B => (read_u32(file, optional_header + 28)? as u64, 96),
+            0x20B => (read_u64(file, optional_header + 24)?, 112),
+            _ => return Err("Unsupported PE optional header"),
+        };
+        let directory_count = read_u32(file, optional_header + directories - 4)? as usize;
+        let directory = |index: usize| -> Result<(u32, u32), &'static str> {
+            if index >= directory_count {
+                return Ok((0, 0));
+            }
+            let entry = optional_header + directories + index * 8;
+            Ok((read_u32(file, entry)?, read_u32(file, entry + 4)?))
+        };
+
+        let section_table = optional_header + optional_header_size;
+        let mut sections = Vec::with_capacity(section_count);
+        for index in 0..section_count {
+            let header = section_table + index * 40;
+            let name = file.get(header..header + 8).ok_or("PE file is truncated")?;
+            let name_len = name.iter().position(|b| *b == 0).unwrap_or(8);
+            let virtual_size = read_u32(file, header + 8)?;
+            let virtual_address = read_u32(file, header + 12)?;
+            if virtual_address.checked_add(virtual_size).is_none() {
+                return Err("PE section is out of range");
+            }
+            sections.push(PeSection {
+                name: String::from_utf8_lossy(&name[..name_len]).into_owned(),
+                virtual_size,
+                virtual_address,
+                raw_size: read_u32(file, header + 16)?,
+                raw_offset: read_u32(file, header + 20)?,
+                characteristics: read_u32(file, header + 36)?,
+            });
+        }
+
+        Ok(PeImage {
+            file,
+            image_base,
+            sections,
+            reloc_dir: directory(IMAGE_DIRECTORY_ENTRY_BASERELOC)?,
+            iat_dir: directory(IMAGE_DIRECTORY_ENTRY_IAT)?,
+        })
+    }
+
+    // Lay out a section as it is mapped in memory: the raw data cut or zero-padded
+    // to the virtual size. The size is capped to the file size, so a malformed header
+    // can not make us allocate gigabytes for a small file.
+    pub fn map_section(&self, section: &PeSection) -> Vec<u8> {
+        let size = if section.virtual_size != 0 {
+            section.virtual_size
+        } else {
+            section.raw_size
+        } as usize;
+        let size = size.min(self.file.len());
+        let mut mapped = vec![0u8; size];
+        let start = (section.raw_offset as usize).min(self.file.len());
+        let end = start
+            .saturating_add((section.raw_size as usize).min(size))
+            .min(self.file.len());
+        mapped[..end - start].copy_from_slice(&self.file[start..end]);
+        mapped
+    }
+
+    // Translate an RVA into an offset in the file
+    fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        self.sections.iter().find_map(|section| {
+            let offset = rva.checked_sub(section.virtual_address)?;
+            if offset >= section.raw_size {
+                return None;
+            }
+            section
+                .raw_offset
+                .checked_add(offset)
+                .map(|offset| offset as usize)
+        })
+    }
+
+    // Apply the base relocations that fall in a mapped section, as the loader does when
+    // the module is loaded at `load_base` instead of its preferred image base
+    pub fn relocate_section(
+        &self,
+        section: &PeSection,
+        mapped: &mut [u8],
+        load_base: u64,
+    ) -> Result<(), &'static str> {
+        let delta = load_base.wrapping_sub(self.image_base);
+        let (reloc_rva, reloc_size) = self.reloc_dir;
+        if delta == 0 || reloc_size == 0 {
+            return Ok(());
+        }
+        let reloc_start = self
+            .rva_to_offset(reloc_rva)
+            .ok_or("PE relocation directory is not in any section")?;
+
+        let mut block = reloc_start;
+        while block + 8 <= reloc_start + reloc_size as usize {
+            let page_rva = read_u32(self.file, block)?;
+            let block_size = read_u32(self.file, block + 4)? as usize;
+            if block_size < 8 {
+                break;
+            }
+
+            for entry in (block + 8..block + block_size).step_by(2) {
+                let entry = read_u16(self.file, entry)?;
+                let kind = entry >> 12;
+                let rva = page_rva
+                    .checked_add((entry & 0xFFF) as u32)
+                    .ok_or("PE relocation is out of range")?;
+                let width = match kind {
+                    IMAGE_REL_BASED_ABSOLUTE => continue,
+                    IMAGE_REL_BASED_HIGHLOW => 4,
+                    IMAGE_REL_BASED_DIR64 => 8,
+                    _ => continue,
+                };
+
+                let Some(offset) = rva.checked_sub(section.virtual_address) else {
+                    continue;
+                };
+                let offset = offset as usize;
+                if offset + width > mapped.len() {
+                    continue;
+                }
+                let target = &mut mapped[offset..offset + width];
+                if width == 4 {
+                    let value = read_u32(target, 0)?.wrapping_add(delta as u32);
+                    target.copy_from_slice(&value.to_le_bytes());
+                } else {
+                    let value = read_u64(target, 0)?.wrapping_add(delta);
+                    target.copy_from_slice(&value.to_le_bytes());
+                }
+            }
+
+            block += block_size;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NT_OFFSET: usize = 0x40;
+    const OPTIONAL_HEADER_SIZE: usize = 112 + 16 * 8;
+    const SECTION_TABLE: usize = NT_OFFSET + 24 + OPTIONAL_HEADER_SIZE;
+
+    fn put_u32(file: &mut [u8], offset: usize, value: u32) {
+        file[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    // Build a PE32+ file with an image base of 0x1000_0000 and one section per
+    // (virtual address, virtual size, raw offset, raw size) entry
+    fn pe_file(sections: &[(u32, u32, u32, u32)], size: usize) -> Vec<u8> {
+        let mut file = vec![0u8; size];
+        file[0..2].copy_from_slice(b"MZ");
+        put_u32(&mut file, 0x3C, NT_OFFSET as u32);
+        file[NT_OFFSET..NT_OFFSET + 4].copy_from_slice(b"PE\0\0");
+
+        let file_header = NT_OFFSET + 4;
+        file[file_header + 2..file_header + 4]
+            .copy_from_slice(&(sections.len() as u16).to_le_bytes());
+        file[file_header + 16..file_header + 18]
+            .copy_from_slice(&(OPTIONAL_HEADER_SIZE as u16).to_le_bytes());
+
+        let optional_header = file_header + 20;
+        file[optional_header..optional_header + 2].copy_from_slice(&0x20Bu16.to_le_bytes());
+        file[optional_header + 24..optional_header + 32]
+            .copy_from_slice(&0x1000_0000u64.to_le_bytes());
+        put_u32(&mut file, optional_header + 108, 16);
+
+        for (index, (virtual_address, virtual_size, raw_offset, raw_size)) in
+            sections.iter().enumerate()
+        {
+            let header = SECTION_TABLE + index * 40;
+            file[header..header + 5].copy_from_slice(b".text");
+            put_u32(&mut file, header + 8, *virtual_size);
+            put_u32(&mut file, header + 12, *virtual_address);
+            put_u32(&mut file, header + 16, *raw_size);
+            put_u32(&mut file, header + 20, *raw_offset);
+            put_u32(&mut file, header + 36, IMAGE_SCN_MEM_EXECUTE);
+        }
+        file
+    }
+
+    fn set_reloc_dir(file: &mut [u8], rva: u32, size: u32) {
+        let entry = NT_OFFSET + 24 + 112 + IMAGE_DIRECTORY_ENTRY_BASERELOC * 8;
+        put_u32(file, entry, rva);
+        put_u32(file, entry + 4, size);
+    }
+
+    #[test]
+    fn parses_sections() {
+        let file = pe_file(&[(0x1000, 0x200, 0x400, 0x200)], 0x600);
+        let image = PeImage::parse(&file).unwrap();
+        assert_eq!(image.image_base, 0x1000_0000);
+        assert_eq!(image.sections.len(), 1);
+        assert_eq!(image.sections[0].name, ".text");
+        assert_eq!(image.sections[0].virtual_address, 0x1000);
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let file = pe_file(&[(0x1000, 0x200, 0x400, 0x200)], 0x600);
+
+        let mut bad = file.clone();
+        bad[0] = b'X';
+        assert!(PeImage::parse(&bad).is_err());
+
+        let mut bad = file.clone();
+        put_u32(&mut bad, 0x3C, u32::MAX);
+        assert!(PeImage::parse(&bad).is_err());
+
+        let mut bad = file.clone();
+        bad[NT_OFFSET + 24] = 0;
+        assert!(PeImage::parse(&bad).is_err());
+
+        // Section with an end past the 32-bit address space
+        let bad = pe_file(&[(0xFFFF_F000, 0x2000, 0x400, 0x200)], 0x600);
+        assert!(PeImage::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let file = pe_file(&[(0x1000, 0x200, 0x400, 0x200)], 0x600);
+        for len in [
+            0,
+            1,
+            0x3E,
+            NT_OFFSET + 2,
+            NT_OFFSET + 30,
+            SECTION_TABLE + 20,
+        ] {
+            assert!(PeImage::parse(&file[..len]).is_err(), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn map_section_caps_sizes_to_the_file() {
+        let mut file = pe_file(&[(0x1000, 0xFFFF_0000, 0x400, 0x200)], 0x600);
+        file[0x400..0x600].fill(0xAA);
+        let image = PeImage::parse(&file).unwrap();
+        let mapped = image.map_section(&image.sections[0]);
+        assert_eq!(mapped.len(), file.len());
+        assert!(mapped[..0x200].iter().all(|b| *b == 0xAA));
+        assert!(mapped[0x200..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn map_section_handles_raw_data_past_the_end() {
+        let file = pe_file(&[(0x1000, 0x100, 0xFFFF_FF00, 0xFFFF_FFFF)], 0x600);
+        let image = PeImage::parse(&file).unwrap();
+        let mapped = image.map_section(&image.sections[0]);
+        assert_eq!(mapped, vec![0u8; 0x100]);
+    }
+
+    #[test]
+    fn applies_relocations() {
+        // Section at RVA 0x1000 mapped from 0x400, relocation block at 0x500 (RVA 0x1100)
+        let mut file = pe_file(&[(0x1000, 0x200, 0x400, 0x200)], 0x600);
+        file[0x410..0x418].copy_from_slice(&0x1000_2000u64.to_le_bytes());
+        put_u32(&mut file, 0x500, 0x1000);
+        put_u32(&mut file, 0x504, 10);
+        file[0x508..0x50A].copy_from_slice(&((IMAGE_REL_BASED_DIR64 << 12) | 0x10).to_le_bytes());
+        set_reloc_dir(&mut file, 0x1100, 10);
+
+        let image = PeImage::parse(&file).unwrap();
+        let mut mapped = image.map_section(&image.sections[0]);
+        image
+            .relocate_section(&image.sections[0], &mut mapped, 0x2000_0000)
+            .unwrap();
+        assert_eq!(read_u64(&mapped, 0x10).unwrap(), 0x2000_2000);
+    }
+
+    #[test]
+    fn rejects_relocations_out_of_range() {
+        let mut file = pe_file(&[(0x1000, 0x200, 0x400, 0x200)], 0x600);
+        put_u32(&mut file, 0x500, u32::MAX);
+        put_u32(&mut file, 0x504, 10);
+        file[0x508..0x50A].copy_from_slice(&((IMAGE_REL_BASED_DIR64 << 12) | 0x10).to_le_bytes());
+        set_reloc_dir(&mut file, 0x1100, 10);
+
+        let image = PeImage::parse(&file).unwrap();
+        let mut mapped = image.map_section(&image.sections[0]);
+        assert!(image
+            .relocate_section(&image.sections[0], &mut mapped, 0x2000_0000)
+            .is_err());
+    }
+
+    #[test]
+    fn rva_to_offset_does_not_overflow() {
+        let file = pe_file(&[(0x1000, 0x200, 0xFFFF_FFF0, 0x200)], 0x600);
+        let image = PeImage::parse(&file).unwrap();
+        assert_eq!(image.rva_to_offset(0x1008), Some(0xFFFF_FFF8));
+        assert_eq!(image.rva_to_offset(0x1100), None);
+    }
+}
@@ -0,0 +1,467 @@
+use crate::cached_process::process_handle;
+use crate::memflow_os_wrapper::MemflowOsWrapper;
+use crate::protection_filter::page_type_to_rwx;
+use crate::{parse_address, MEMFLOW_OS_TYPE, MEMFLOW_OS_TYPE_VAR};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, ANYS_TYPES, ANY_TABLE_TYPES, BYTES_TYPES, NONE_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Define the VirtToPhys Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.VirtToPhys",
+    "Translates a virtual address of a process to its physical address."
+)]
+pub struct MemflowVirtToPhysShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Virtual address to translate.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    // Output table with the translation
+    translation: AutoTableVar,
+}
+
+impl Default for MemflowVirtToPhysShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            translation: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowVirtToPhysShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {physical, page_size, page_type}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.translation = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let address_umem = parse_address(self.address.get())?;
+
+        shlog_debug!("Translating virtual address: 0x{:x}", address_umem);
+
+        // Not every process implementation exposes its page tables
+        let translator = process
+            .process()
+            .as_mut_impl_virtualtranslate()
+            .ok_or("Process does not support virtual address translation.")?;
+
+        let physical = translator
+            .virt_to_phys(Address::from(address_umem))
+            .map_err(|e| {
+                shlog_error!("Failed to translate address 0x{:x}: {}", address_umem, e);
+                "Address is not mapped in the process."
+            })?;
+
+        let physical_var: Var = (physical.address().to_umem() as i64).into();
+        let page_size_var: Var = (physical.page_size() as i64).into();
+        let page_type_var = Var::ephemeral_string(&page_type_to_rwx(physical.page_type()));
+
+        self.translation.0.clear();
+        self.translation
+            .0
+            .insert_fast_static("physical", &physical_var);
+        self.translation
+            .0
+            .insert_fast_static("page_size", &page_size_var);
+        self.translation
+            .0
+            .insert_fast_static("page_type", &page_type_var);
+
+        Ok(Some(self.translation.0 .0))
+    }
+}
+
+// Smallest non-zero PageMap granularity, one page
+const MIN_PAGE_MAP_GRANULARITY: i64 = 0x1000;
+
+// Maximum number of entries a PageMap returns
+const MAX_PAGE_MAP_ENTRIES: usize = 1 << 20;
+
+// Define the PageMap Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.PageMap",
+    "Lists the mapped pages of a process from its page tables, with their protection."
+)]
+pub struct MemflowPageMapShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Start", "Lowest address to map (default: no limit).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    start: ParamVar,
+
+    #[shard_param("End", "Address to stop mapping at, exclusive (default: no limit).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    end: ParamVar,
+
+    #[shard_param("Granularity", "Size of the entries in bytes, larger pages and ranges are split into entries of this size (default: 0x1000, at least 0x1000, 0 keeps the ranges as reported). At most 1048576 entries are returned.", [common_type::int, common_type::int_var])]
+    granularity: ParamVar,
+
+    // Output list of pages as sequence of tables
+    pages: AutoSeqVar,
+}
+
+impl Default for MemflowPageMapShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            start: ParamVar::default(),
+            end: ParamVar::default(),
+            granularity: ParamVar::new(0x1000.into()),
+            pages: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowPageMapShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {address, size, page_type}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.pages = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let start: umem = if self.start.get().is_none() {
+            0
+        } else {
+            parse_address(self.start.get())?
+        };
+        let end: umem = if self.end.get().is_none() {
+            umem::MAX
+        } else {
+            parse_address(self.end.get())?
+        };
+        if start > end {
+            return Err("Start must not be greater than End");
+        }
+        let granularity: i64 = self.granularity.get().as_ref().try_into()?;
+        if granularity != 0 && granularity < MIN_PAGE_MAP_GRANULARITY {
+            return Err("Granularity must be 0 or at least 0x1000");
+        }
+        let granularity = granularity as umem;
+
+        shlog_debug!(
+            "Getting page map from 0x{:x} to 0x{:x} (granularity: 0x{:x})",
+            start,
+            end,
+            granularity
+        );
+
+        // Not every process implementation exposes its page tables
+        let translator = process
+            .process()
+            .as_mut_impl_virtualtranslate()
+            .ok_or("Process does not support virtual address translation.")?;
+
+        // No gap merging, only contiguous pages with the same protection are reported together
+        let ranges =
+            translator.virt_page_map_range_vec(0, Address::from(start), Address::from(end));
+
+        self.pages.0.clear();
+
+        let mut entries = 0;
+        'ranges: for range in ranges {
+            let range_start = range.0.to_umem();
+            let range_size = range.1;
+            let page_type = Var::ephemeral_string(&page_type_to_rwx(range.2));
+
+            let step = if granularity == 0 {
+                range_size
+            } else {
+                granularity
+            };
+            let mut offset = 0;
+            while offset < range_size {
+                if entries >= MAX_PAGE_MAP_ENTRIES {
+                    shlog_debug!(
+                        "Page map stopped after {} entries at 0x{:x}",
+                        MAX_PAGE_MAP_ENTRIES,
+                        range_start + offset
+                    );
+                    break 'ranges;
+                }
+
+                let address: Var = ((range_start + offset) as i64).into();
+                let size: Var = (step.min(range_size - offset) as i64).into();
+
+                let mut entry = AutoTableVar::new();
+                entry.0.insert_fast_static("address", &address);
+                entry.0.insert_fast_static("size", &size);
+                entry.0.insert_fast_static("page_type", &page_type);
+                self.pages.0.emplace_table(entry);
+                entries += 1;
+
+                offset += step;
+            }
+        }
+
+        Ok(Some(self.pages.0 .0))
+    }
+}
+
+// Largest range a PhysRead reads at once
+const MAX_PHYS_READ_SIZE: i64 = 0x10_0000;
+
+// Define the PhysRead Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.PhysRead",
+    "Reads physical memory through the OS instance, bypassing virtual address translation."
+)]
+pub struct MemflowPhysReadShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to read physical memory from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("Address", "Physical address to read from.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Size", "Number of bytes to read, at most 1 MiB.", [common_type::int, common_type::int_var])]
+    size: ParamVar,
+
+    // Output buffer
+    output_buffer: ClonedVar,
+}
+
+impl Default for MemflowPhysReadShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            address: ParamVar::new(0.into()),
+            size: ParamVar::new(1.into()),
+            output_buffer: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowPhysReadShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &BYTES_TYPES // Outputs an array of bytes
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_buffer = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let address_umem = parse_address(self.address.get())?;
+        let size: i64 = self.size.get().as_ref().try_into()?;
+        if size <= 0 {
+            return Err("Size must be greater than 0");
+        }
+        if size > MAX_PHYS_READ_SIZE {
+            return Err("Size must be at most 1 MiB");
+        }
+
+        shlog_debug!(
+            "Reading physical memory at address: 0x{:x}, size: {} bytes",
+            address_umem,
+            size
+        );
+
+        // Not every OS layer gives access to the underlying physical memory
+        let phys =
+            os.0.as_mut_impl_physicalmemory()
+                .ok_or("OS does not expose physical memory.")?;
+
+        let mut buffer = vec![0u8; size as usize];
+        phys.phys_view()
+            .read_raw_into(Address::from(address_umem), &mut buffer)
+            .map_err(|e| {
+                shlog_error!("Failed to read physical memory: {}", e);
+                "Failed to read physical memory."
+            })?;
+
+        self.output_buffer = buffer.as_slice().into();
+        Ok(Some(self.output_buffer.0))
+    }
+}
+
+// Define the PhysWrite Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.PhysWrite",
+    "Writes physical memory through the OS instance, bypassing virtual address translation."
+)]
+pub struct MemflowPhysWriteShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to write physical memory to.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("Address", "Physical address to write to.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+}
+
+impl Default for MemflowPhysWriteShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            address: ParamVar::new(0.into()),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowPhysWriteShard {
+    fn input_types(&mut self) -> &Types {
+        &BYTES_TYPES // Takes bytes as input to write
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &NONE_TYPES // No output, just success/failure
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let address_umem = parse_address(self.address.get())?;
+
+        // Get data to write from input
+        let data: &[u8] = input.try_into()?;
+        if data.is_empty() {
+            return Err("No data to write");
+        }
+
+        shlog_debug!(
+            "Writing physical memory at address: 0x{:x}, size: {} bytes",
+            address_umem,
+            data.len()
+        );
+
+        // Not every OS layer gives access to the underlying physical memory
+        let phys =
+            os.0.as_mut_impl_physicalmemory()
+                .ok_or("OS does not expose physical memory.")?;
+
+        phys.phys_view()
+            .write_raw(Address::from(address_umem), data)
+            .map_err(|e| {
+                shlog_error!("Failed to write physical memory: {}", e);
+                "Failed to write physical memory."
+            })?;
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,582 @@
+use crate::cached_process::{process_handle, ProcessHandle};
+use crate::memflow_module_wrapper::MemflowModuleWrapper;
+use crate::protection_filter::protection_filter_matches;
+use crate::{
+    chunk_size_param, coalesce_regions, parse_address, process_pointer_size, read_pointer,
+    read_regions, split_region, DEFAULT_CHUNK_SIZE, MEMFLOW_MODULE_TYPE, MEMFLOW_MODULE_TYPE_VAR,
+};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, Context, ExposedTypes, InstanceData, ParamVar, SeqVar,
+    Type, Types, Var, ANYS_TYPES, INT_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+// Reason a pointer chain could not be followed
+pub enum PointerChainError {
+    ReadFailed(usize),
+    NullPointer(usize),
+}
+
+impl PointerChainError {
+    pub fn describe(&self) -> String {
+        match self {
+            PointerChainError::ReadFailed(level) => format!("read failed at level {}", level),
+            PointerChainError::NullPointer(level) => format!("null pointer at level {}", level),
+        }
+    }
+}
+
+// Helper function to collect an int sequence of offsets
+pub fn offsets_from_seq(seq: &SeqVar) -> std::result::Result<Vec<i64>, &'static str> {
+    let mut offsets = Vec::new();
+    for offset in seq.iter() {
+        let offset: i64 = offset.as_ref().try_into()?;
+        offsets.push(offset);
+    }
+    Ok(offsets)
+}
+
+// Helper function to follow a pointer chain: at each level the pointer at the current
+// address is dereferenced and the level's offset is added to it
+pub fn follow_pointer_chain(
+    process: &mut ProcessHandle,
+    base: umem,
+    offsets: &[i64],
+    pointer_size: usize,
+) -> std::result::Result<umem, PointerChainError> {
+    let mut current = base;
+    for (level, offset) in offsets.iter().enumerate() {
+        let pointer = read_pointer(process, current, pointer_size)
+            .map_err(|_| PointerChainError::ReadFailed(level))?;
+        if pointer == 0 {
+            return Err(PointerChainError::NullPointer(level));
+        }
+        current = pointer.wrapping_add(*offset as umem);
+    }
+    Ok(current)
+}
+
+// Helper function to read the pointer size override param
+pub fn pointer_size_param(
+    param: &ParamVar,
+    process: &ProcessInstanceArcBox<'static>,
+) -> std::result::Result<usize, &'static str> {
+    if param.get().is_none() {
+        return Ok(process_pointer_size(process));
+    }
+    let pointer_size: i64 = param.get().as_ref().try_into()?;
+    match pointer_size {
+        4 | 8 => Ok(pointer_size as usize),
+        _ => Err("PointerSize must be 4 or 8"),
+    }
+}
+
+// Define the ReadPointerChain Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ReadPointerChain",
+    "Follows a multi-level pointer chain and returns the final address."
+)]
+pub struct MemflowReadPointerChainShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Base", "Address of the first pointer in the chain.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    base: ParamVar,
+
+    #[shard_param("Offsets", "Offsets added after dereferencing each level.", [common_type::ints, common_type::ints_var])]
+    offsets: ParamVar,
+
+    #[shard_param("PointerSize", "Pointer width in bytes, 4 or 8 (default: from the process architecture).", [common_type::none, common_type::int, common_type::int_var])]
+    pointer_size: ParamVar,
+
+    // Last error, kept here so it can be returned by reference
+    error_message: String,
+}
+
+impl Default for MemflowReadPointerChainShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            base: ParamVar::new(0.into()),
+            offsets: ParamVar::default(),
+            pointer_size: ParamVar::default(),
+            error_message: String::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowReadPointerChainShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &INT_TYPES // Outputs the resolved address
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let base = parse_address(self.base.get())?;
+        let offsets = offsets_from_seq(self.offsets.get().as_seq()?)?;
+        let pointer_size = pointer_size_param(&self.pointer_size, process.process())?;
+
+        shlog_debug!(
+            "Following pointer chain from 0x{:x} with {} levels",
+            base,
+            offsets.len()
+        );
+
+        match follow_pointer_chain(&mut process, base, &offsets, pointer_size) {
+            Ok(address) => Ok(Some(Var::new_int(address as i64))),
+            Err(e) => {
+                self.error_message = e.describe();
+                Err(&self.error_message)
+            }
+        }
+    }
+}
+
+// Helper function to check whether an address lies in one of the sorted, merged regions
+fn in_regions(regions: &[(umem, usize)], address: umem) -> bool {
+    let index = regions.partition_point(|(start, _)| *start <= address);
+    index > 0 && {
+        let (start, size) = regions[index - 1];
+        address < start + size as umem
+    }
+}
+
+// Maximum number of stored pointers a PointerScan collects before it stops reading,
+// bounding the memory the candidate set takes up (16 bytes per pointer)
+const MAX_POINTER_CANDIDATES: usize = 1 << 23;
+
+// Helper function to collect the pointer-aligned values of a buffer that point into
+// mapped memory, as (value, slot address) pairs
+fn collect_pointers(
+    buffer: &[u8],
+    base_addr: umem,
+    pointer_size: usize,
+    mapped: &[(umem, usize)],
+) -> Vec<(umem, umem)> {
+    let align = pointer_size as umem;
+    let mut offset = ((align - base_addr % align) % align) as usize;
+    let mut pointers = Vec::new();
+    while offset + pointer_size <= buffer.len() {
+        let mut value = [0u8; 8];
+        value[..pointer_size].copy_from_slice(&buffer[offset..offset + pointer_size]);
+        let value = u64::from_le_bytes(value) as umem;
+        if value != 0 && in_regions(mapped, value) {
+            pointers.push((value, base_addr + offset as umem));
+        }
+        offset += pointer_size;
+    }
+    pointers
+}
+
+// Define the PointerScan Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.PointerScan",
+    "Searches pointer paths from static module memory to a target address."
+)]
+pub struct MemflowPointerScanShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Target", "Address the pointer paths should lead to.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    target: ParamVar,
+
+    #[shard_param("MaxDepth", "Maximum number of pointers in a path (default: 3).", [common_type::int, common_type::int_var])]
+    max_depth: ParamVar,
+
+    #[shard_param("MaxOffset", "Maximum offset added after dereferencing a pointer (default: 0x1000).", [common_type::int, common_type::int_var])]
+    max_offset: ParamVar,
+
+    #[shard_param("Module", "Name or names of the modules whose memory counts as a static base (default: all modules).", [common_type::none, common_type::string, common_type::string_var, common_type::strings, common_type::strings_var])]
+    module: ParamVar,
+
+    #[shard_param("Protection", "Memory protection of the regions searched for pointers (default: 'rw').", [common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("MaxResults", "Maximum number of paths to return, 0 for no limit (default: 1000).", [common_type::int, common_type::int_var])]
+    max_results: ParamVar,
+
+    #[shard_param("Timeout", "Time after which the search stops and returns the paths found so far, in milliseconds. Collecting the pointers to search uses at most half of it (default: 10000).", [common_type::int, common_type::int_var])]
+    timeout: ParamVar,
+
+    #[shard_param("PointerSize", "Pointer width in bytes, 4 or 8 (default: from the process architecture).", [common_type::none, common_type::int, common_type::int_var])]
+    pointer_size: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    // Output list of paths as sequence of tables
+    paths: AutoSeqVar,
+}
+
+impl Default for MemflowPointerScanShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            target: ParamVar::new(0.into()),
+            max_depth: ParamVar::new(3.into()),
+            max_offset: ParamVar::new(0x1000.into()),
+            module: ParamVar::default(),
+            protection: ParamVar::new(Var::ephemeral_string("rw")),
+            max_results: ParamVar::new(1000.into()),
+            timeout: ParamVar::new(10000.into()),
+            pointer_size: ParamVar::default(),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            paths: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowPointerScanShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of paths
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.paths = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let target = parse_address(self.target.get())?;
+        let max_depth: i64 = self.max_depth.get().as_ref().try_into()?;
+        let max_offset: i64 = self.max_offset.get().as_ref().try_into()?;
+        let protection_filter: &str = self.protection.get().as_ref().try_into()?;
+        let max_results: i64 = self.max_results.get().as_ref().try_into()?;
+        let timeout: i64 = self.timeout.get().as_ref().try_into()?;
+        if max_depth <= 0 {
+            return Err("MaxDepth must be greater than 0");
+        }
+        if max_offset < 0 || max_results < 0 || timeout < 0 {
+            return Err("MaxOffset, MaxResults and Timeout must not be negative");
+        }
+        let pointer_size = pointer_size_param(&self.pointer_size, process.process())?;
+        let overlap = pointer_size - 1;
+        let chunk_size = chunk_size_param(&self.chunk_size, overlap)?;
+        // Collecting pointers may use at most half of the timeout, so there is always time
+        // left to search the ones collected
+        let started = Instant::now();
+        let collect_deadline = started + Duration::from_millis(timeout as u64 / 2);
+        let deadline = started + Duration::from_millis(timeout as u64);
+
+        let module_names: Option<Vec<String>> = if self.module.get().is_none() {
+            None
+        } else if let Ok(name) = <&str>::try_from(self.module.get().as_ref()) {
+            Some(vec![name.to_string()])
+        } else {
+            let mut names = Vec::new();
+            for name in self.module.get().as_seq()?.iter() {
+                let name: &str = name.as_ref().try_into()?;
+                names.push(name.to_string());
+            }
+            Some(names)
+        };
+
+        // Memory of the whitelisted modules is where a path may start
+        let modules: Vec<ModuleInfo> = process
+            .process()
+            .module_list()
+            .map_err(|e| {
+                shlog_error!("Failed to list modules: {}", e);
+                "Failed to list modules."
+            })?
+            .into_iter()
+            .filter(|module| match &module_names {
+                Some(names) => names
+                    .iter()
+                    .any(|name| module.name.eq_ignore_ascii_case(name)),
+                None => true,
+            })
+            .collect();
+        if modules.is_empty() {
+            return Err("No module to start pointer paths from.");
+        }
+        let static_module = |slot: umem| {
+            modules.iter().find(|module| {
+                let base = module.base.to_umem();
+                slot >= base && slot < base + module.size as umem
+            })
+        };
+
+        // Collect every stored pointer into mapped memory, sorted by the pointed-to address
+        let maps = process.process().mapped_mem_vec(0);
+        let mapped = coalesce_regions(&maps);
+        let filtered_maps: Vec<_> = maps
+            .into_iter()
+            .filter(|map| protection_filter_matches(map.2, protection_filter))
+            .collect();
+        let regions: Vec<(umem, usize)> = coalesce_regions(&filtered_maps)
+            .into_iter()
+            .filter(|(_, size)| *size >= pointer_size)
+            .flat_map(|(address, size)| split_region(address, size, chunk_size, overlap))
+            .collect();
+
+        // Reading stops at its deadline, and once the candidate set is full
+        let mut pointers = Vec::new();
+        read_regions(&mut process, &regions, |base_addr, buffer| {
+            pointers.extend(collect_pointers(&buffer, base_addr, pointer_size, &mapped));
+            if Instant::now() >= collect_deadline {
+                shlog_debug!(
+                    "Pointer scan ran out of time collecting pointers, searching the ones found"
+                );
+                return false;
+            }
+            pointers.len() < MAX_POINTER_CANDIDATES
+        });
+        if pointers.len() >= MAX_POINTER_CANDIDATES {
+            shlog_debug!(
+                "Pointer scan stopped collecting after {} pointers, searching the ones found",
+                MAX_POINTER_CANDIDATES
+            );
+            pointers.truncate(MAX_POINTER_CANDIDATES);
+        }
+        pointers.sort_unstable();
+
+        shlog_debug!(
+            "Searching pointer paths to 0x{:x} through {} pointers, max depth {}",
+            target,
+            pointers.len(),
+            max_depth
+        );
+
+        // Breadth-first search backwards from the target. Each pending entry is an address
+        // with the offsets leading from it to the target; a pointer stored at `slot` whose
+        // value is at most MaxOffset below the address extends the path by one level.
+        let mut found = Vec::new();
+        let mut visited = HashSet::new();
+        let mut level: Vec<(umem, Vec<i64>)> = vec![(target, Vec::new())];
+        'search: for _ in 0..max_depth {
+            let mut next_level = Vec::new();
+            for (address, tail) in level {
+                let lowest = address.saturating_sub(max_offset as umem);
+                let start = pointers.partition_point(|(value, _)| *value < lowest);
+                for (value, slot) in pointers[start..]
+                    .iter()
+                    .take_while(|(value, _)| *value <= address)
+                {
+                    if max_results > 0 && found.len() >= max_results as usize {
+                        break 'search;
+                    }
+                    if Instant::now() >= deadline {
+                        shlog_debug!("Pointer scan timed out, returning partial results");
+                        break 'search;
+                    }
+
+                    let mut offsets = Vec::with_capacity(tail.len() + 1);
+                    offsets.push((address - value) as i64);
+                    offsets.extend_from_slice(&tail);
+
+                    if let Some(module) = static_module(*slot) {
+                        found.push((*slot, module, offsets));
+                    } else if visited.insert(*slot) {
+                        next_level.push((*slot, offsets));
+                    }
+                }
+            }
+            level = next_level;
+        }
+
+        self.paths.0.clear();
+        for (base, module, offsets) in found {
+            let base_var: Var = (base as i64).into();
+            let module_var = Var::ephemeral_string(&module.name);
+            let module_offset_var: Var = ((base - module.base.to_umem()) as i64).into();
+            let mut offsets_seq = AutoSeqVar::new();
+            for offset in offsets {
+                offsets_seq.0.push(&Var::new_int(offset));
+            }
+
+            let mut path = AutoTableVar::new();
+            path.0.insert_fast_static("base", &base_var);
+            path.0.insert_fast_static("module", &module_var);
+            path.0
+                .insert_fast_static("module_offset", &module_offset_var);
+            path.0.insert_fast_static("offsets", &offsets_seq.0 .0);
+            self.paths.0.emplace_table(path);
+        }
+
+        Ok(Some(self.paths.0 .0))
+    }
+}
+
+// Define the ResolvePointerPath Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ResolvePointerPath",
+    "Checks that a saved pointer path still resolves and returns the address it leads to."
+)]
+pub struct MemflowResolvePointerPathShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Base", "Address of the first pointer in the path, used when Module is not set.", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    base: ParamVar,
+
+    #[shard_param("Module", "Module, or module name, the path starts in. The first pointer is at its base plus ModuleOffset.", [common_type::none, common_type::string, common_type::string_var, *MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("ModuleOffset", "Offset of the first pointer from the module base (default: 0).", [common_type::int, common_type::int_var])]
+    module_offset: ParamVar,
+
+    #[shard_param("Offsets", "Offsets added after dereferencing each level.", [common_type::ints, common_type::ints_var])]
+    offsets: ParamVar,
+
+    #[shard_param("PointerSize", "Pointer width in bytes, 4 or 8 (default: from the process architecture).", [common_type::none, common_type::int, common_type::int_var])]
+    pointer_size: ParamVar,
+
+    // Last error, kept here so it can be returned by reference
+    error_message: String,
+}
+
+impl Default for MemflowResolvePointerPathShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            base: ParamVar::default(),
+            module: ParamVar::default(),
+            module_offset: ParamVar::new(0.into()),
+            offsets: ParamVar::default(),
+            pointer_size: ParamVar::default(),
+            error_message: String::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowResolvePointerPathShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &INT_TYPES // Outputs the resolved address
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get the address of the first pointer, either directly or relative to a module
+        let base: umem = if !self.module.get().is_none() {
+            let module_base = if let Ok(name) = <&str>::try_from(self.module.get().as_ref()) {
+                let module = process.process().module_by_name(name).map_err(|e| {
+                    shlog_error!("Failed to find module by name '{}': {}", name, e);
+                    "Module not found by name."
+                })?;
+                module.base.to_umem()
+            } else {
+                let module = unsafe {
+                    &*Var::from_ref_counted_object::<MemflowModuleWrapper>(
+                        self.module.get(),
+                        &*MEMFLOW_MODULE_TYPE,
+                    )?
+                };
+                module.0.base.to_umem()
+            };
+            let module_offset: i64 = self.module_offset.get().as_ref().try_into()?;
+            module_base.wrapping_add(module_offset as umem)
+        } else if !self.base.get().is_none() {
+            parse_address(self.base.get())?
+        } else {
+            return Err("Either Base or Module must be provided.");
+        };
+
+        let offsets = offsets_from_seq(self.offsets.get().as_seq()?)?;
+        let pointer_size = pointer_size_param(&self.pointer_size, process.process())?;
+
+        shlog_debug!(
+            "Resolving pointer path from 0x{:x} with {} levels",
+            base,
+            offsets.len()
+        );
+
+        match follow_pointer_chain(&mut process, base, &offsets, pointer_size) {
+            Ok(address) => Ok(Some(Var::new_int(address as i64))),
+            Err(e) => {
+                self.error_message = format!("Pointer path broken: {}", e.describe());
+                shlog_error!("{} (base 0x{:x})", self.error_message, base);
+                Err(&self.error_message)
+            }
+        }
+    }
+}
@@ -0,0 +1,819 @@
+use crate::cached_process::{process_handle, ProcessHandle};
+use crate::memflow_os_wrapper::MemflowOsWrapper;
+use crate::memflow_process_wrapper::MemflowProcessWrapper;
+use crate::{
+    parse_address, process_info_to_table, process_pointer_size, read_pointer, MEMFLOW_OS_TYPE,
+    MEMFLOW_OS_TYPE_VAR, MEMFLOW_PROCESS_TYPE,
+};
+
+use memflow::prelude::v1::*;
+use shards::core::suspend;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, WireState, ANYS_TYPES, ANY_TABLE_TYPES, BOOL_TYPES, NONE_TYPES,
+    STRING_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+use std::time::{Duration, Instant};
+
+// Upper bound for the size of an environment block we are willing to read
+const MAX_ENVIRONMENT_SIZE: usize = 0x10_0000;
+
+// Largest writable region searched for the PEB, the PEB and TEBs live in small regions
+const MAX_PEB_REGION_SIZE: umem = 0x10_0000;
+
+// Offsets of ImageBaseAddress and ProcessParameters in the PEB, and of Environment and
+// EnvironmentSize in RTL_USER_PROCESS_PARAMETERS, for the given pointer width
+fn peb_offsets(pointer_size: usize) -> (umem, umem, umem, umem) {
+    if pointer_size == 8 {
+        (0x10, 0x20, 0x80, 0x3f0)
+    } else {
+        (0x08, 0x10, 0x48, 0x290)
+    }
+}
+
+// Helper function to locate the PEB of a Windows process. The OS layer doesn't hand it
+// out, so look for the page-aligned block in a small writable region whose
+// ImageBaseAddress is the base of the primary module and whose ProcessParameters
+// pointer is set.
+fn find_peb(process: &mut ProcessHandle, pointer_size: usize) -> Option<umem> {
+    let image_base = process.process().primary_module().ok()?.base.to_umem();
+    let (image_base_offset, params_offset, _, _) = peb_offsets(pointer_size);
+
+    let maps = process.process().mapped_mem_vec(0);
+    for map in maps {
+        let (address, size) = (map.0.to_umem(), map.1.to_umem());
+        if !map.2.contains(PageType::WRITEABLE) || size > MAX_PEB_REGION_SIZE {
+            continue;
+        }
+
+        for page in (address..address + size).step_by(0x1000) {
+            let is_peb = read_pointer(process, page + image_base_offset, pointer_size)
+                == Ok(image_base)
+                && read_pointer(process, page + params_offset, pointer_size)
+                    .is_ok_and(|params| params != 0);
+            if is_peb {
+                return Some(page);
+            }
+        }
+    }
+
+    None
+}
+
+// Helper function to read the environment of a process running on this machine from
+// /proc. Only used when the local /proc entry of the pid runs the same executable, so
+// processes of a remote or virtualized target are never mixed up with local ones.
+fn read_proc_environ(info: &ProcessInfo) -> Option<Vec<u8>> {
+    let exe = std::fs::read_link(format!("/proc/{}/exe", info.pid)).ok()?;
+    if exe.to_string_lossy() != info.path.to_string() {
+        return None;
+    }
+    std::fs::read(format!("/proc/{}/environ", info.pid)).ok()
+}
+
+// Helper function to read the UTF-16 environment block the PEB points to
+fn read_peb_environment(
+    process: &mut ProcessHandle,
+    peb: umem,
+    pointer_size: usize,
+) -> std::result::Result<Vec<String>, &'static str> {
+    let (_, params_offset, env_offset, env_size_offset) = peb_offsets(pointer_size);
+
+    shlog_debug!("Reading process environment from PEB at 0x{:x}", peb);
+
+    let params = read_pointer(process, peb + params_offset, pointer_size)?;
+    if params == 0 {
+        return Err("Process parameters pointer is null.");
+    }
+
+    let env_address = read_pointer(process, params + env_offset, pointer_size)?;
+    if env_address == 0 {
+        return Err("Environment pointer is null.");
+    }
+
+    let env_size = read_pointer(process, params + env_size_offset, pointer_size)?;
+    let env_size = (env_size as usize).min(MAX_ENVIRONMENT_SIZE) & !1;
+
+    let mut buffer = vec![0u8; env_size];
+    process
+        .read_raw_into(env_address, &mut buffer)
+        .map_err(|e| {
+            shlog_error!("Failed to read environment block: {}", e);
+            "Failed to read environment block."
+        })?;
+
+    // The block is a sequence of null-terminated UTF-16 "NAME=VALUE" strings,
+    // terminated by an empty string
+    let units: Vec<u16> = buffer
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+
+    Ok(units
+        .split(|&u| u == 0)
+        .take_while(|entry| !entry.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect())
+}
+
+// Define the ProcessEnv Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessEnv",
+    "Reads the environment variables of a process, from its PEB on Windows or from /proc for processes of the local Linux machine. Outputs an empty table when neither is available."
+)]
+pub struct MemflowProcessEnvShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Peb", "Address of the process environment block (PEB), overriding the one found in the process memory (default: none).", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    peb: ParamVar,
+
+    // Output table of environment variables
+    environment: AutoTableVar,
+}
+
+impl Default for MemflowProcessEnvShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            peb: ParamVar::default(),
+            environment: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessEnvShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table of environment variables
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.environment = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        self.environment.0.clear();
+
+        let pointer_size = process_pointer_size(process.process());
+        let entries = if !self.peb.get().is_none() {
+            let peb = parse_address(self.peb.get())?;
+            read_peb_environment(&mut process, peb, pointer_size)?
+        } else if let Some(environ) = read_proc_environ(process.process().info()) {
+            // A sequence of null-terminated "NAME=VALUE" strings
+            environ
+                .split(|&b| b == 0)
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| String::from_utf8_lossy(entry).into_owned())
+                .collect()
+        } else if let Some(peb) = find_peb(&mut process, pointer_size) {
+            read_peb_environment(&mut process, peb, pointer_size)?
+        } else {
+            shlog_debug!(
+                "Process environment not available: no PEB found and no local /proc entry, returning an empty table"
+            );
+            Vec::new()
+        };
+
+        for entry in entries {
+            // Entries such as "=C:=C:\\" start with '=', so look for the separator after it
+            let separator = match entry.char_indices().skip(1).find(|&(_, c)| c == '=') {
+                Some((index, _)) => index,
+                None => continue,
+            };
+
+            let name = Var::ephemeral_string(&entry[..separator]);
+            let value = Var::ephemeral_string(&entry[separator + 1..]);
+            self.environment.0.insert_fast(name, &value);
+        }
+
+        Ok(Some(self.environment.0 .0))
+    }
+}
+
+// Define the WaitForProcess Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.WaitForProcess",
+    "Waits until a process with the given name appears and returns a handle to it."
+)]
+pub struct MemflowWaitForProcessShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to get the process from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("Name", "Process name to wait for.", [common_type::string, common_type::string_var])]
+    process_name: ParamVar,
+
+    #[shard_param("Timeout", "Maximum time to wait in milliseconds (0 waits forever).", [common_type::int, common_type::int_var])]
+    timeout: ParamVar,
+
+    #[shard_param("PollInterval", "Time between two looks at the process list in milliseconds (default: 100).", [common_type::int, common_type::int_var])]
+    poll_interval: ParamVar,
+
+    // Store the output Process object
+    output_process: ClonedVar,
+}
+
+impl Default for MemflowWaitForProcessShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            process_name: ParamVar::default(),
+            timeout: ParamVar::new(0.into()),
+            poll_interval: ParamVar::new(100.into()),
+            output_process: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowWaitForProcessShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_PROCESS_TYPES // Outputs our custom Process object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Process instance when the shard is cleaned up
+        self.output_process = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let name: &str = self.process_name.get().as_ref().try_into()?;
+        let timeout: i64 = self.timeout.get().as_ref().try_into()?;
+        if timeout < 0 {
+            return Err("Timeout must not be negative");
+        }
+        let poll_interval: i64 = self.poll_interval.get().as_ref().try_into()?;
+        if poll_interval <= 0 {
+            return Err("PollInterval must be greater than 0");
+        }
+
+        shlog_debug!("Waiting for process '{}' (timeout: {} ms)", name, timeout);
+
+        let deadline = Duration::from_millis(timeout as u64);
+        let start = Instant::now();
+
+        let process_instance = loop {
+            if let Ok(process) = os.0.process_by_name(name) {
+                break process;
+            }
+
+            if timeout != 0 && start.elapsed() >= deadline {
+                shlog_error!("Timed out waiting for process '{}'", name);
+                return Err("Timed out waiting for process.");
+            }
+
+            // Yield back to the scheduler, this also lets the flow cancel the wait
+            if suspend(context, poll_interval as f64 / 1000.0) != WireState::Continue {
+                return Err("Waiting for process was cancelled.");
+            }
+        };
+
+        // Create and return the process object
+        self.output_process = Var::new_ref_counted(
+            MemflowProcessWrapper(process_instance, 0),
+            &MEMFLOW_PROCESS_TYPE,
+        )
+        .into();
+        Ok(Some(self.output_process.0))
+    }
+}
+
+// Define the ProcessInfo Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessInfo",
+    "Returns the information memflow has about a process as a table."
+)]
+pub struct MemflowProcessInfoShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Output table of process information
+    info: AutoTableVar,
+}
+
+impl Default for MemflowProcessInfoShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            info: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessInfoShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table of process information
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.info = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        self.info = process_info_to_table(process.process().info());
+        Ok(Some(self.info.0 .0))
+    }
+}
+
+// Define the ProcessIsAlive Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessIsAlive",
+    "Checks whether a process is still running, so a stale handle can be detected before reading from it."
+)]
+pub struct MemflowProcessIsAliveShard {
+    #[shard_required]
+    required: ExposedTypes,
+}
+
+impl Default for MemflowProcessIsAliveShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessIsAliveShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &BOOL_TYPES // Outputs whether the process is alive
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let state = process.process().state();
+        shlog_debug!(
+            "Process {} state: {:?}",
+            process.process().info().pid,
+            state
+        );
+
+        Ok(Some(Var::new_bool(state == ProcessState::Alive)))
+    }
+}
+
+// Define the ProcessState Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessState",
+    "Returns the current state of a process as a string (e.g. \"Alive\", \"Dead(0)\" or \"Unknown\")."
+)]
+pub struct MemflowProcessStateShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Output state string
+    output_state: ClonedVar,
+}
+
+impl Default for MemflowProcessStateShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            output_state: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessStateShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &STRING_TYPES // Outputs the state string
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_state = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Same formatting as the "state" field of Memflow.ProcessInfo
+        let state = format!("{:?}", process.process().state());
+        self.output_state = Var::ephemeral_string(&state).into();
+        Ok(Some(self.output_state.0))
+    }
+}
+
+// Define the ProcessByPid Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessByPid",
+    "Creates a handle to the process with a specific PID from a Memflow OS instance."
+)]
+pub struct MemflowProcessByPidShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to get the process from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("Pid", "Process ID to open.", [common_type::int, common_type::int_var])]
+    pid: ParamVar,
+
+    // Store the output Process object
+    output_process: ClonedVar,
+}
+
+impl Default for MemflowProcessByPidShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            pid: ParamVar::default(),
+            output_process: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessByPidShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_PROCESS_TYPES // Outputs our custom Process object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Process instance when the shard is cleaned up
+        self.output_process = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let pid: i64 = self.pid.get().as_ref().try_into()?;
+        let pid = u32::try_from(pid).map_err(|_| "Pid is out of range")?;
+        shlog_debug!("Searching for process by PID: {}", pid);
+
+        let process_instance = os.0.process_by_pid(pid).map_err(|e| {
+            shlog_error!("Failed to find process by PID {}: {}", pid, e);
+            "Process not found by PID."
+        })?;
+
+        // Create and return the process object
+        self.output_process = Var::new_ref_counted(
+            MemflowProcessWrapper(process_instance, 0),
+            &MEMFLOW_PROCESS_TYPE,
+        )
+        .into();
+        Ok(Some(self.output_process.0))
+    }
+}
+
+// Define the ProcessByAddress Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessByAddress",
+    "Creates a handle to the process with a specific kernel object address from a Memflow OS instance."
+)]
+pub struct MemflowProcessByAddressShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to get the process from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("Address", "Kernel object address of the process, as reported in the 'address' field of Memflow.ProcessList.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    // Store the output Process object
+    output_process: ClonedVar,
+}
+
+impl Default for MemflowProcessByAddressShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            address: ParamVar::default(),
+            output_process: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessByAddressShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &crate::MEMFLOW_PROCESS_TYPES // Outputs our custom Process object
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Process instance when the shard is cleaned up
+        self.output_process = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let address = Address::from(parse_address(self.address.get())?);
+        shlog_debug!("Searching for process by address: {}", address);
+
+        let process_instance = os.0.process_by_address(address).map_err(|e| {
+            shlog_error!("Failed to find process by address {}: {}", address, e);
+            "Process not found by address."
+        })?;
+
+        // Create and return the process object
+        self.output_process = Var::new_ref_counted(
+            MemflowProcessWrapper(process_instance, 0),
+            &MEMFLOW_PROCESS_TYPE,
+        )
+        .into();
+        Ok(Some(self.output_process.0))
+    }
+}
+
+// Define the ProcessesByName Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ProcessesByName",
+    "Creates handles to every running process with a specific name from a Memflow OS instance."
+)]
+pub struct MemflowProcessesByNameShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Os", "The Memflow OS instance to get the processes from.", [*MEMFLOW_OS_TYPE, *MEMFLOW_OS_TYPE_VAR])]
+    os_instance: ParamVar,
+
+    #[shard_param("Name", "Process name to search for.", [common_type::string, common_type::string_var])]
+    process_name: ParamVar,
+
+    // Output sequence of Process objects
+    processes: AutoSeqVar,
+}
+
+impl Default for MemflowProcessesByNameShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            os_instance: ParamVar::new_named("memflow/default-os"),
+            process_name: ParamVar::default(),
+            processes: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowProcessesByNameShard {
+    fn input_types(&mut self) -> &Types {
+        &NONE_TYPES // Takes no input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of Process objects
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        // Drop the Process instances when the shard is cleaned up
+        self.processes = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        _input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the OS instance from parameter
+        let os_var = &self.os_instance.get();
+        let os = unsafe {
+            &mut *Var::from_ref_counted_object::<MemflowOsWrapper>(os_var, &*MEMFLOW_OS_TYPE)?
+        };
+
+        let name: &str = self.process_name.get().as_ref().try_into()?;
+        shlog_debug!("Searching for all processes named: {}", name);
+
+        let process_list = os.0.process_info_list().map_err(|e| {
+            shlog_error!("Failed to get process list: {}", e);
+            "Failed to get process list."
+        })?;
+
+        self.processes = AutoSeqVar::new();
+
+        // Like process_by_name, only consider processes that are not known to be dead
+        for info in process_list {
+            if &*info.name != name || matches!(info.state, ProcessState::Dead(_)) {
+                continue;
+            }
+
+            let pid = info.pid;
+            let process_instance = match os.0.process_by_info(info) {
+                Ok(process_instance) => process_instance,
+                Err(e) => {
+                    // The process may have exited since the list was taken
+                    shlog_debug!("Skipping process {} ({}): {}", pid, name, e);
+                    continue;
+                }
+            };
+
+            let process: ClonedVar = Var::new_ref_counted(
+                MemflowProcessWrapper(process_instance, 0),
+                &MEMFLOW_PROCESS_TYPE,
+            )
+            .into();
+            self.processes.0.push(&process.0);
+        }
+
+        shlog_debug!(
+            "Found {} processes named '{}'",
+            self.processes.0.len(),
+            name
+        );
+
+        Ok(Some(self.processes.0 .0))
+    }
+}
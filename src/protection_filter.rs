@@ -1,11 +1,16 @@
 use memflow::prelude::v1::*;
 
-// Helper function to convert PageType to rwx format string
+// Helper function to convert PageType to rwx format string.
+// The mapping is:
+// - r: the page is mapped with known flags, i.e. it is neither NONE nor UNKNOWN.
+//   Reserved/guard pages come back as NONE and are therefore not readable.
+// - w: the page is WRITEABLE.
+// - x: the page is readable and not NOEXEC.
 pub fn page_type_to_rwx(page_type: PageType) -> String {
     // Check for read/write/execute permissions
-    let has_read = true; // Assume readable if mapped
+    let has_read = !page_type.is_empty() && !page_type.contains(PageType::UNKNOWN);
     let has_write = page_type.contains(PageType::WRITEABLE);
-    let has_exec = !page_type.contains(PageType::NOEXEC);
+    let has_exec = has_read && !page_type.contains(PageType::NOEXEC);
 
     // Convert to rwx format string
     format!(
@@ -16,8 +21,22 @@ pub fn page_type_to_rwx(page_type: PageType) -> String {
     )
 }
 
-// Helper function to check if a protection filter matches a page type
+// Helper function to check if a protection filter matches a page type.
+// The filter is matched position by position against the "rwx" string:
+// 'r', 'w' or 'x' requires the permission, '-' requires it to be absent and
+// '*' or '?' matches either. Missing trailing positions match anything, so
+// "rw" behaves like "rw*".
 pub fn protection_filter_matches(page_type: PageType, filter: &str) -> bool {
     let rwx = page_type_to_rwx(page_type);
-    rwx.contains(filter)
+    if filter.len() > rwx.len() {
+        return false;
+    }
+
+    filter
+        .chars()
+        .zip(rwx.chars())
+        .all(|(wanted, actual)| match wanted {
+            '*' | '?' => true,
+            _ => wanted == actual,
+        })
 }
@@ -0,0 +1,241 @@
+// Streaming hashes used to fingerprint memory regions. They are implemented here
+// instead of pulling in hashing crates, since only these three digests are needed.
+
+// Hash algorithms that can be computed over a memory region
+pub enum RegionHasher {
+    Crc32(u32),
+    Fnv1a(u64),
+    Sha256(Sha256),
+}
+
+impl RegionHasher {
+    // Create a hasher from an algorithm name: "crc32", "fnv1a" or "sha256"
+    pub fn from_name(name: &str) -> Result<RegionHasher, &'static str> {
+        match name {
+            "crc32" => Ok(RegionHasher::Crc32(0xFFFF_FFFF)),
+            "fnv1a" => Ok(RegionHasher::Fnv1a(FNV1A_OFFSET_BASIS)),
+            "sha256" => Ok(RegionHasher::Sha256(Sha256::new())),
+            _ => Err("Unsupported hash algorithm, expected 'crc32', 'fnv1a' or 'sha256'"),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            RegionHasher::Crc32(crc) => {
+                for byte in data {
+                    *crc = CRC32_TABLE[((*crc ^ *byte as u32) & 0xFF) as usize] ^ (*crc >> 8);
+                }
+            }
+            RegionHasher::Fnv1a(hash) => {
+                for byte in data {
+                    *hash = (*hash ^ *byte as u64).wrapping_mul(FNV1A_PRIME);
+                }
+            }
+            RegionHasher::Sha256(sha) => sha.update(data),
+        }
+    }
+
+    // Finish the hash and format the digest as lowercase hex
+    pub fn finish_hex(self) -> String {
+        match self {
+            RegionHasher::Crc32(crc) => format!("{:08x}", !crc),
+            RegionHasher::Fnv1a(hash) => format!("{:016x}", hash),
+            RegionHasher::Sha256(sha) => {
+                sha.finish().iter().map(|b| format!("{:02x}", b)).collect()
+            }
+        }
+    }
+}
+
+// 64-bit FNV-1a parameters
+const FNV1A_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV1A_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Lookup table for the reflected CRC-32 (IEEE 802.3) polynomial
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+// SHA-256 round constants
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Incremental SHA-256 state
+pub struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    total_len: u64,
+}
+
+impl Sha256 {
+    fn new() -> Self {
+        Self {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: [0u8; 64],
+            block_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        while !data.is_empty() {
+            let take = (64 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&data[..take]);
+            self.block_len += take;
+            data = &data[take..];
+            if self.block_len == 64 {
+                let block = self.block;
+                self.compress(&block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        // Pad with a single 1 bit, zeros and the message length in bits
+        let bit_len = self.total_len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.block_len != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bit_len.to_be_bytes());
+
+        let mut digest = [0u8; 32];
+        for (chunk, word) in digest.chunks_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(name: &str, data: &[u8]) -> String {
+        let mut hasher = RegionHasher::from_name(name).unwrap();
+        hasher.update(data);
+        hasher.finish_hex()
+    }
+
+    #[test]
+    fn crc32_known_answers() {
+        assert_eq!(hash("crc32", b""), "00000000");
+        assert_eq!(hash("crc32", b"123456789"), "cbf43926");
+    }
+
+    #[test]
+    fn fnv1a_known_answers() {
+        assert_eq!(hash("fnv1a", b""), "cbf29ce484222325");
+        assert_eq!(hash("fnv1a", b"a"), "af63dc4c8601ec8c");
+        assert_eq!(hash("fnv1a", b"foobar"), "85944171f73967e8");
+    }
+
+    #[test]
+    fn sha256_known_answers() {
+        // NIST FIPS 180-2 examples
+        assert_eq!(
+            hash("sha256", b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash("sha256", b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash(
+                "sha256",
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            hash("sha256", &vec![b'a'; 1_000_000]),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn streamed_updates_match_one_update() {
+        // Region hashes are fed chunk by chunk, with chunks not aligned to SHA-256 blocks
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        for name in ["crc32", "fnv1a", "sha256"] {
+            let mut hasher = RegionHasher::from_name(name).unwrap();
+            for chunk in data.chunks(37) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finish_hex(), hash(name, &data));
+        }
+    }
+}
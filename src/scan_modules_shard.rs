@@ -0,0 +1,328 @@
+use crate::cached_process::process_handle;
+use crate::protection_filter::protection_filter_matches;
+use crate::value_type::Endian;
+use crate::{
+    chunk_size_param, clip_region, coalesce_regions, parse_pattern, parse_scan_value, scan_buffer,
+    scan_pattern, scan_regions, split_region, PatternElement, ScanValue, DEFAULT_CHUNK_SIZE,
+};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, Context, ExposedTypes, InstanceData, ParamVar, Type,
+    Types, Var, ANYS_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// What a ScanModules run looks for in each module
+enum ModuleScan {
+    Pattern(Vec<PatternElement>),
+    // The value, its alignment and byte order
+    Value(ScanValue, usize, Endian),
+}
+
+impl ModuleScan {
+    // Number of bytes a match spans
+    fn size(&self) -> usize {
+        match self {
+            ModuleScan::Pattern(pattern) => pattern.len(),
+            ModuleScan::Value(value, _, _) => value.size(),
+        }
+    }
+
+    fn scan(&self, buffer: &[u8], base_addr: umem) -> Vec<umem> {
+        match self {
+            ModuleScan::Pattern(pattern) => scan_pattern(buffer, pattern, base_addr),
+            ModuleScan::Value(value, alignment, endian) => {
+                scan_buffer(buffer, value, *alignment, base_addr, None, None, *endian)
+                    .into_iter()
+                    .map(|result| result.address)
+                    .collect()
+            }
+        }
+    }
+}
+
+// Helper function to clip scan regions to the sections of a module. Pieces that touch
+// are merged again, so a match can span two adjacent sections.
+fn section_regions(regions: &[(umem, usize)], sections: &[(umem, umem)]) -> Vec<(umem, usize)> {
+    let mut clipped: Vec<(umem, usize)> = sections
+        .iter()
+        .flat_map(|&(base, size)| {
+            regions.iter().filter_map(move |&(address, region_size)| {
+                clip_region(
+                    address,
+                    region_size as umem,
+                    base,
+                    base.saturating_add(size),
+                )
+            })
+        })
+        .collect();
+    clipped.sort_unstable();
+
+    let mut merged: Vec<(umem, usize)> = Vec::new();
+    for (address, size) in clipped {
+        match merged.last_mut() {
+            Some((last_address, last_size)) if *last_address + *last_size as umem >= address => {
+                let end = (address + size as umem).max(*last_address + *last_size as umem);
+                *last_size = (end - *last_address) as usize;
+            }
+            _ => merged.push((address, size)),
+        }
+    }
+    merged
+}
+
+// Define the ScanModules Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ScanModules",
+    "Runs a pattern or value scan over the sections of each loaded module of a process and groups the matches by module."
+)]
+pub struct MemflowScanModulesShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Pattern", "Byte pattern to scan for (e.g., '48 8B ? ? 89 7C'). Either Pattern or Value must be set.", [common_type::none, common_type::string, common_type::string_var])]
+    pattern: ParamVar,
+
+    #[shard_param("Value", "Value to scan for instead of a pattern, of type ValueType.", [common_type::none, common_type::any, common_type::any_var])]
+    value: ParamVar,
+
+    #[shard_param("ValueType", "Type of Value: 'int' (same as 'i64'), 'i8', 'u8', 'i16', 'u16', 'i32', 'u32', 'i64', 'u64', 'float', 'double', 'string', 'bytes', 'guid' (default: 'int').", [common_type::string, common_type::string_var])]
+    value_type: ParamVar,
+
+    #[shard_param("Alignment", "Memory alignment of Value (default: the value size for int, float and double values, 1 otherwise).", [common_type::none, common_type::int, common_type::int_var])]
+    alignment: ParamVar,
+
+    #[shard_param("Endian", "Byte order of int, float and double values: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+
+    #[shard_param("Protection", "Memory protection to filter by (e.g., 'r-x'). Scans every mapped part of the module's sections when not set.", [common_type::none, common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Threads", "Number of worker threads scanning the read memory (default: 1).", [common_type::int, common_type::int_var])]
+    threads: ParamVar,
+
+    // Output results
+    scan_results: AutoSeqVar,
+}
+
+impl Default for MemflowScanModulesShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            pattern: ParamVar::default(),
+            value: ParamVar::default(),
+            value_type: ParamVar::new(Var::ephemeral_string("int")),
+            alignment: ParamVar::default(),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+            protection: ParamVar::default(),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            threads: ParamVar::new(1.into()),
+            scan_results: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowScanModulesShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of per-module results
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.scan_results = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get what to scan for, a pattern or a value
+        let scan = match (self.pattern.get().is_none(), self.value.get().is_none()) {
+            (false, true) => {
+                let pattern_str: &str = self.pattern.get().as_ref().try_into()?;
+                let pattern = parse_pattern(pattern_str)?;
+                if pattern.is_empty() {
+                    return Err("Empty pattern");
+                }
+                ModuleScan::Pattern(pattern)
+            }
+            (true, false) => {
+                let value_type: &str = self.value_type.get().as_ref().try_into()?;
+                let endian_str: &str = self.endian.get().as_ref().try_into()?;
+                let endian = Endian::from_name(endian_str)?;
+                let value = parse_scan_value(value_type, self.value.get(), endian)?;
+                if value.size() == 0 {
+                    return Err("Empty value");
+                }
+                let alignment = if self.alignment.get().is_none() {
+                    match &value {
+                        ScanValue::Integer(..) | ScanValue::Float(_) | ScanValue::Double(_) => {
+                            value.size()
+                        }
+                        ScanValue::String(..) | ScanValue::Bytes(_) => 1,
+                    }
+                } else {
+                    let alignment: i64 = self.alignment.get().as_ref().try_into()?;
+                    alignment.max(1) as usize
+                };
+                ModuleScan::Value(value, alignment, endian)
+            }
+            (true, true) => return Err("Either Pattern or Value must be set"),
+            (false, false) => return Err("Pattern and Value can not be set together"),
+        };
+
+        let protection_filter = if self.protection.get().is_none() {
+            None
+        } else {
+            let prot_str: &str = self.protection.get().as_ref().try_into()?;
+            Some(prot_str)
+        };
+
+        let overlap = scan.size() - 1;
+        let chunk_size = chunk_size_param(&self.chunk_size, overlap)?;
+        let threads: i64 = self.threads.get().as_ref().try_into()?;
+
+        let module_list = process.process().module_list().map_err(|e| {
+            shlog_error!("Failed to get process module list: {}", e);
+            "Failed to get process module list."
+        })?;
+
+        // Only keep the mapped regions we are allowed to scan, merging adjacent ones so
+        // matches spanning a region boundary are found
+        let maps: Vec<_> = process
+            .process()
+            .mapped_mem_vec(0)
+            .into_iter()
+            .filter(|map| match protection_filter {
+                Some(prot_filter) => protection_filter_matches(map.2, prot_filter),
+                None => true,
+            })
+            .collect();
+        let regions = coalesce_regions(&maps);
+
+        shlog_debug!("Scanning {} modules", module_list.len());
+
+        self.scan_results.0.clear();
+
+        for module in module_list {
+            let module_base = module.base.to_umem();
+
+            // Scan the module's sections, or the whole module when it has none
+            let mut sections: Vec<(umem, umem)> = process
+                .process()
+                .module_section_list(&module)
+                .unwrap_or_else(|e| {
+                    shlog_debug!("No sections for module '{}': {}", module.name, e);
+                    Vec::new()
+                })
+                .into_iter()
+                .map(|section| (section.base.to_umem(), section.size))
+                .collect();
+            if sections.is_empty() {
+                sections.push((module_base, module.size));
+            }
+
+            // Split large regions into overlapping chunks so they are read a piece at a time
+            let module_regions: Vec<(umem, usize)> = section_regions(&regions, &sections)
+                .into_iter()
+                .filter(|(_, size)| *size >= scan.size())
+                .flat_map(|(address, size)| split_region(address, size, chunk_size, overlap))
+                .collect();
+
+            let mut addresses = scan_regions(
+                &mut process,
+                &module_regions,
+                threads.max(1) as usize,
+                |base_addr, buffer| scan.scan(buffer, base_addr),
+            );
+            if addresses.is_empty() {
+                continue;
+            }
+
+            // Matches in the overlap of two chunks are found in both
+            addresses.sort_unstable();
+            addresses.dedup();
+
+            let mut matches = AutoSeqVar::new();
+            for address in addresses {
+                let address_var: Var = (address as i64).into();
+                let offset_var: Var = ((address - module_base) as i64).into();
+
+                let mut match_entry = AutoTableVar::new();
+                match_entry.0.insert_fast_static("address", &address_var);
+                match_entry.0.insert_fast_static("offset", &offset_var);
+                matches.0.emplace_table(match_entry);
+            }
+
+            let name_var = Var::ephemeral_string(&module.name);
+            let base_var: Var = (module_base as i64).into();
+
+            let mut module_entry = AutoTableVar::new();
+            module_entry.0.insert_fast_static("module", &name_var);
+            module_entry.0.insert_fast_static("base", &base_var);
+            module_entry.0.insert_fast_static("matches", &matches.0 .0);
+
+            self.scan_results.0.emplace_table(module_entry);
+        }
+
+        Ok(Some(self.scan_results.0 .0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_regions_clips_to_sections() {
+        // One mapped region covering two sections with a gap between them
+        let regions = [(0x1000, 0x4000)];
+        let sections = [(0x1000, 0x1000), (0x3000, 0x800)];
+        assert_eq!(
+            section_regions(&regions, &sections),
+            vec![(0x1000, 0x1000), (0x3000, 0x800)]
+        );
+    }
+
+    #[test]
+    fn section_regions_merges_adjacent_sections() {
+        let regions = [(0x1000, 0x1800), (0x2800, 0x1000)];
+        let sections = [(0x2000, 0x1000), (0x1000, 0x1000)];
+        assert_eq!(section_regions(&regions, &sections), vec![(0x1000, 0x2000)]);
+    }
+
+    #[test]
+    fn section_regions_skips_unmapped_sections() {
+        let regions = [(0x1000, 0x1000)];
+        let sections = [(0x5000, 0x1000)];
+        assert!(section_regions(&regions, &sections).is_empty());
+    }
+}
@@ -0,0 +1,551 @@
+use crate::cached_process::{process_handle, ProcessHandle};
+use crate::memflow_module_wrapper::MemflowModuleWrapper;
+use crate::xref_scanner::{init_capstone, Arch, MAX_INSN_LEN};
+use crate::{
+    clip_region, coalesce_regions, parse_address, parse_pattern, scan_pattern,
+    scan_regions_limited, split_region, PatternElement, DEFAULT_CHUNK_SIZE, MEMFLOW_MODULE_TYPE,
+    MEMFLOW_MODULE_TYPE_VAR,
+};
+
+use capstone::arch::x86::X86OperandType;
+use capstone::arch::ArchDetail;
+use capstone::{Capstone, Insn};
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoTableVar, Context, ExposedTypes, InstanceData, ParamVar, Type, Types, Var,
+    ANY_TABLE_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Helper function to clip scan regions to [start, end) and split them into chunks of
+// `chunk_size` bytes, overlapping so a pattern of `pattern_len` bytes crossing a chunk
+// boundary is still found
+fn signature_regions(
+    regions: Vec<(umem, usize)>,
+    pattern_len: usize,
+    start: umem,
+    end: umem,
+    chunk_size: usize,
+) -> Vec<(umem, usize)> {
+    regions
+        .into_iter()
+        .filter_map(|(address, size)| clip_region(address, size as umem, start, end))
+        .filter(|(_, size)| *size >= pattern_len)
+        .flat_map(|(address, size)| split_region(address, size, chunk_size, pattern_len - 1))
+        .collect()
+}
+
+// Helper function to find the first match of a pattern within [start, end). Reading
+// stops at the first chunk with a match, chunks are read in address order.
+fn find_first_match(
+    process: &mut ProcessHandle,
+    pattern: &[PatternElement],
+    start: umem,
+    end: umem,
+) -> Option<umem> {
+    let regions = signature_regions(
+        coalesce_regions(&process.process().mapped_mem_vec(0)),
+        pattern.len(),
+        start,
+        end,
+        (DEFAULT_CHUNK_SIZE as usize).max(pattern.len()),
+    );
+
+    scan_regions_limited(process, &regions, 1, Some(1), |base_addr, buffer| {
+        scan_pattern(buffer, pattern, base_addr)
+    })
+    .first()
+    .copied()
+}
+
+// Define the FindSignature Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.FindSignature",
+    "Finds the first match of a signature in a module and optionally resolves a relative operand."
+)]
+pub struct MemflowFindSignatureShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Pattern", "Byte pattern to scan for (e.g., '48 8B 05 ? ? ? ?').", [common_type::string, common_type::string_var])]
+    pattern: ParamVar,
+
+    #[shard_param("Module", "The module to search in (default: the primary module of the process).", [common_type::none, *MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("OperandOffset", "Offset of a 32-bit relative operand within the match to resolve (optional).", [common_type::none, common_type::int, common_type::int_var])]
+    operand_offset: ParamVar,
+
+    #[shard_param("InstructionSize", "Size of the instruction the operand is relative to (default: OperandOffset + 4).", [common_type::none, common_type::int, common_type::int_var])]
+    instruction_size: ParamVar,
+
+    // Output result
+    result: AutoTableVar,
+}
+
+impl Default for MemflowFindSignatureShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            pattern: ParamVar::default(),
+            module: ParamVar::default(),
+            operand_offset: ParamVar::default(),
+            instruction_size: ParamVar::default(),
+            result: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowFindSignatureShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs a table describing the match
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.result = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let pattern_str: &str = self.pattern.get().as_ref().try_into()?;
+        let pattern = parse_pattern(pattern_str)?;
+        if pattern.is_empty() {
+            return Err("Empty pattern");
+        }
+
+        // Resolve the module to search in
+        let module_info = if self.module.get().is_none() {
+            process.process().primary_module().map_err(|e| {
+                shlog_error!("Failed to get primary module: {}", e);
+                "Failed to get primary module."
+            })?
+        } else {
+            let module = unsafe {
+                &*Var::from_ref_counted_object::<MemflowModuleWrapper>(
+                    self.module.get(),
+                    &*MEMFLOW_MODULE_TYPE,
+                )?
+            };
+            module.0.clone()
+        };
+
+        let module_base = module_info.base.to_umem();
+        let module_end = module_base + module_info.size;
+
+        shlog_debug!(
+            "Searching for signature '{}' in module {}",
+            pattern_str,
+            module_info.name
+        );
+
+        let address = find_first_match(&mut process, &pattern, module_base, module_end)
+            .ok_or("Signature not found.")?;
+
+        self.result.0.clear();
+
+        let address_var: Var = (address as i64).into();
+        let module_var = Var::ephemeral_string(&module_info.name);
+        let offset_var: Var = ((address - module_base) as i64).into();
+        self.result.0.insert_fast_static("address", &address_var);
+        self.result.0.insert_fast_static("module", &module_var);
+        self.result.0.insert_fast_static("offset", &offset_var);
+
+        // Resolve a relative operand such as the displacement of `lea rax, [rip+disp]`
+        if !self.operand_offset.get().is_none() {
+            let operand_offset: i64 = self.operand_offset.get().as_ref().try_into()?;
+            let instruction_size: i64 = if self.instruction_size.get().is_none() {
+                operand_offset + 4
+            } else {
+                self.instruction_size.get().as_ref().try_into()?
+            };
+
+            let mut displacement = [0u8; 4];
+            process
+                .read_raw_into(
+                    address.wrapping_add(operand_offset as umem),
+                    &mut displacement,
+                )
+                .map_err(|e| {
+                    shlog_error!("Failed to read relative operand: {}", e);
+                    "Failed to read relative operand."
+                })?;
+
+            let resolved = address
+                .wrapping_add(instruction_size as umem)
+                .wrapping_add(i32::from_le_bytes(displacement) as i64 as umem);
+
+            let resolved_var: Var = (resolved as i64).into();
+            self.result
+                .0
+                .insert_fast_static("resolved_target", &resolved_var);
+        }
+
+        Ok(Some(self.result.0 .0))
+    }
+}
+
+// Helper function to find the offsets of the bytes of an instruction that change when the
+// code is relocated or rebuilt: 32-bit and 64-bit displacements and immediates, including
+// relative branch targets. Byte-sized operands are usually stable struct offsets or
+// constants and are kept. The encoded operand is located by searching its value from the
+// end of the instruction, where x86 places displacements and immediates.
+fn relocatable_bytes(cs: &Capstone, insn: &Insn) -> Vec<usize> {
+    let bytes = insn.bytes();
+    let Ok(detail) = cs.insn_detail(insn) else {
+        return Vec::new();
+    };
+    let ArchDetail::X86Detail(x86) = detail.arch_detail() else {
+        return Vec::new();
+    };
+
+    let next_insn = insn.address().wrapping_add(bytes.len() as u64);
+    let mut encodings: Vec<Vec<u8>> = Vec::new();
+    for op in x86.operands() {
+        match op.op_type {
+            X86OperandType::Mem(mem) => {
+                encodings.push((mem.disp() as i32).to_le_bytes().to_vec());
+            }
+            X86OperandType::Imm(imm) => {
+                // Relative branches store the distance to the target, not the target
+                let relative = (imm as u64).wrapping_sub(next_insn) as i32;
+                encodings.push(relative.to_le_bytes().to_vec());
+                encodings.push((imm as i32).to_le_bytes().to_vec());
+                encodings.push(imm.to_le_bytes().to_vec());
+            }
+            _ => {}
+        }
+    }
+
+    let mut masked = Vec::new();
+    for encoding in encodings {
+        // The opcode itself is never part of an operand
+        let found = (1..=bytes.len().saturating_sub(encoding.len()))
+            .rev()
+            .find(|&offset| bytes[offset..offset + encoding.len()] == encoding[..]);
+        if let Some(offset) = found {
+            masked.extend(offset..offset + encoding.len());
+        }
+    }
+    masked
+}
+
+// Helper function to get the length of a pattern without its trailing wildcards, which
+// match anything and can't make the pattern more unique
+fn significant_len(pattern: &[PatternElement]) -> usize {
+    pattern
+        .iter()
+        .rposition(|element| matches!(element, PatternElement::Byte(_)))
+        .map_or(0, |last| last + 1)
+}
+
+// Helper function to format a pattern the way parse_pattern reads it
+fn format_pattern(pattern: &[PatternElement]) -> String {
+    pattern
+        .iter()
+        .map(|element| match element {
+            PatternElement::Byte(byte) => format!("{:02X}", byte),
+            PatternElement::Wildcard => "?".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Define the MakeSignature Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.MakeSignature",
+    "Creates the shortest byte pattern that uniquely identifies the code at an address within a module."
+)]
+pub struct MemflowMakeSignatureShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Module", "The module the signature must be unique in.", [*MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("Address", "Address of the code to create a signature for.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("MaxLength", "Maximum number of bytes in the signature (default: 64).", [common_type::int, common_type::int_var])]
+    max_length: ParamVar,
+
+    // Output result
+    result: AutoTableVar,
+}
+
+impl Default for MemflowMakeSignatureShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            module: ParamVar::default(),
+            address: ParamVar::new(0.into()),
+            max_length: ParamVar::new(64.into()),
+            result: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowMakeSignatureShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {pattern, matches}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.result = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get the module from parameter
+        let module = unsafe {
+            &*Var::from_ref_counted_object::<MemflowModuleWrapper>(
+                self.module.get(),
+                &*MEMFLOW_MODULE_TYPE,
+            )?
+        };
+        let module_base = module.0.base.to_umem();
+        let module_end = module_base + module.0.size as umem;
+
+        // Get parameters
+        let address = parse_address(self.address.get())?;
+        let max_length: i64 = self.max_length.get().as_ref().try_into()?;
+        if max_length <= 0 {
+            return Err("MaxLength must be greater than 0");
+        }
+        let max_length = max_length as usize;
+        if address < module_base || address >= module_end {
+            return Err("Address is not inside the module");
+        }
+
+        let proc_arch = process.process().info().proc_arch;
+        let arch = Arch::from_ident(proc_arch).ok_or_else(|| {
+            shlog_error!("Unsupported process architecture: {:?}", proc_arch);
+            "Unsupported process architecture."
+        })?;
+        let cs = init_capstone(arch).map_err(|e| {
+            shlog_error!("Failed to initialize disassembler: {}", e);
+            "Failed to initialize disassembler."
+        })?;
+
+        // Read the code the signature is made of, allowing the last instruction to run past
+        // MaxLength so it is decoded whole
+        let code_size = ((module_end - address) as usize).min(max_length + MAX_INSN_LEN);
+        let mut code = vec![0u8; code_size];
+        process.read_raw_into(address, &mut code).map_err(|e| {
+            shlog_error!("Failed to read code at 0x{:x}: {}", address, e);
+            "Failed to read memory from process."
+        })?;
+
+        // Read the module once, every candidate pattern is matched against all of it
+        let mut module_memory: Vec<(umem, Vec<u8>)> = Vec::new();
+        for (region_addr, region_size) in coalesce_regions(&process.process().mapped_mem_vec(0)) {
+            let Some((region_addr, region_size)) =
+                clip_region(region_addr, region_size as umem, module_base, module_end)
+            else {
+                continue;
+            };
+            let mut buffer = vec![0u8; region_size];
+            match process.read_raw_into(region_addr, &mut buffer) {
+                Ok(_) => module_memory.push((region_addr, buffer)),
+                Err(e) => {
+                    shlog_debug!("Failed to read memory region at 0x{:x}: {}", region_addr, e)
+                }
+            }
+        }
+
+        shlog_debug!(
+            "Creating signature for 0x{:x} in module {}",
+            address,
+            module.0.name
+        );
+
+        let instructions = cs.disasm_all(&code, address as u64).map_err(|e| {
+            shlog_error!("Failed to disassemble code at 0x{:x}: {}", address, e);
+            "Failed to disassemble code."
+        })?;
+
+        // Grow the pattern one instruction at a time until it only matches once
+        let mut pattern: Vec<PatternElement> = Vec::new();
+        let mut matches = 0;
+        for insn in instructions.iter() {
+            if pattern.len() + insn.bytes().len() > max_length {
+                break;
+            }
+
+            let masked = relocatable_bytes(&cs, insn);
+            for (offset, byte) in insn.bytes().iter().enumerate() {
+                if masked.contains(&offset) {
+                    pattern.push(PatternElement::Wildcard);
+                } else {
+                    pattern.push(PatternElement::Byte(*byte));
+                }
+            }
+
+            let length = significant_len(&pattern);
+            if length == 0 {
+                continue;
+            }
+
+            matches = module_memory
+                .iter()
+                .map(|(region_addr, buffer)| {
+                    scan_pattern(buffer, &pattern[..length], *region_addr).len()
+                })
+                .sum();
+            if matches == 1 {
+                break;
+            }
+        }
+
+        pattern.truncate(significant_len(&pattern));
+        if pattern.is_empty() {
+            return Err("Failed to disassemble code at the address.");
+        }
+        if matches != 1 {
+            shlog_debug!(
+                "No unique signature within {} bytes, best pattern matches {} times",
+                max_length,
+                matches
+            );
+        }
+
+        self.result.0.clear();
+
+        let pattern_var = Var::ephemeral_string(&format_pattern(&pattern));
+        let matches_var: Var = (matches as i64).into();
+        self.result.0.insert_fast_static("pattern", &pattern_var);
+        self.result.0.insert_fast_static("matches", &matches_var);
+
+        Ok(Some(self.result.0 .0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Scans the chunks of a single region of memory the way find_first_match does
+    fn first_match(
+        memory: &[u8],
+        base: umem,
+        pattern: &str,
+        start: umem,
+        end: umem,
+        chunk_size: usize,
+    ) -> Option<umem> {
+        let pattern = parse_pattern(pattern).unwrap();
+        signature_regions(
+            vec![(base, memory.len())],
+            pattern.len(),
+            start,
+            end,
+            chunk_size,
+        )
+        .into_iter()
+        .find_map(|(address, size)| {
+            let offset = (address - base) as usize;
+            scan_pattern(&memory[offset..offset + size], &pattern, address)
+                .first()
+                .copied()
+        })
+    }
+
+    fn memory_with(matches: &[usize]) -> Vec<u8> {
+        let mut memory = vec![0u8; 0x40];
+        for &offset in matches {
+            memory[offset..offset + 4].copy_from_slice(&[0x48, 0x8B, 0x05, 0x89]);
+        }
+        memory
+    }
+
+    #[test]
+    fn finds_match_crossing_chunk_boundary() {
+        let memory = memory_with(&[0x1e]);
+        assert_eq!(
+            first_match(&memory, 0x1000, "48 8B ? 89", 0, umem::MAX, 0x20),
+            Some(0x101e)
+        );
+    }
+
+    #[test]
+    fn returns_lowest_match() {
+        let memory = memory_with(&[0x30, 0x08]);
+        assert_eq!(
+            first_match(&memory, 0x1000, "48 8B ? 89", 0, umem::MAX, 0x10),
+            Some(0x1008)
+        );
+    }
+
+    #[test]
+    fn respects_search_bounds() {
+        let memory = memory_with(&[0x08, 0x30]);
+        // The first match starts before Start, the second ends past End
+        assert_eq!(
+            first_match(&memory, 0x1000, "48 8B ? 89", 0x1009, 0x1040, 0x20),
+            Some(0x1030)
+        );
+        assert_eq!(
+            first_match(&memory, 0x1000, "48 8B ? 89", 0x1009, 0x1033, 0x20),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_regions_smaller_than_pattern() {
+        let memory = memory_with(&[0x00]);
+        assert_eq!(
+            first_match(&memory, 0x1000, "48 8B ? 89", 0x1000, 0x1003, 0x20),
+            None
+        );
+    }
+}
@@ -0,0 +1,688 @@
+use crate::cached_process::{process_handle, ProcessHandle};
+use crate::protection_filter::protection_filter_matches;
+use crate::{
+    chunk_size_param, coalesce_regions, parse_address, scan_regions, scan_regions_limited,
+    split_region, DEFAULT_CHUNK_SIZE,
+};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, ANYS_TYPES, STRING_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+use regex::bytes::Regex;
+use std::collections::HashSet;
+
+// Size of each read while looking for a string terminator
+const STRING_READ_CHUNK: usize = 64;
+
+// Helper function to read a string of `unit_size` wide characters until a null unit.
+// Reads in small chunks so we don't touch memory past the terminator. Returns the
+// bytes before the terminator, truncated to `max_bytes` if no terminator was found.
+// Only fails if the very first chunk can't be read.
+fn read_terminated(
+    process: &mut ProcessHandle,
+    address: umem,
+    max_bytes: usize,
+    unit_size: usize,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    let mut result = Vec::new();
+    let mut chunk = [0u8; STRING_READ_CHUNK];
+
+    while result.len() < max_bytes {
+        let chunk_size = STRING_READ_CHUNK.min(max_bytes - result.len());
+        let chunk = &mut chunk[..chunk_size];
+        let chunk_address = address + result.len() as umem;
+
+        if let Err(e) = process.read_raw_into(chunk_address, chunk) {
+            if result.is_empty() {
+                shlog_error!("Failed to read string at 0x{:x}: {}", address, e);
+                return Err("Failed to read string from process.");
+            }
+            // The string runs into unreadable memory, return what we have
+            shlog_debug!("String read stopped at 0x{:x}: {}", chunk_address, e);
+            break;
+        }
+
+        // Chunk sizes are a multiple of the unit size, except possibly the last one
+        for unit in chunk.chunks(unit_size) {
+            if unit.len() == unit_size && unit.iter().all(|&b| b == 0) {
+                return Ok(result);
+            }
+            result.extend_from_slice(unit);
+        }
+    }
+
+    result.truncate(max_bytes);
+    Ok(result)
+}
+
+// Define the ReadString Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ReadString",
+    "Reads a null-terminated string from a specific address in a process."
+)]
+pub struct MemflowReadStringShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Memory address of the string.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("MaxLength", "Maximum number of bytes to read (default: 256).", [common_type::int, common_type::int_var])]
+    max_length: ParamVar,
+
+    // Output string
+    output_string: ClonedVar,
+}
+
+impl Default for MemflowReadStringShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            max_length: ParamVar::new(256.into()),
+            output_string: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowReadStringShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &STRING_TYPES // Outputs a string
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_string = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let address = parse_address(self.address.get())?;
+        let max_length: i64 = self.max_length.get().as_ref().try_into()?;
+        if max_length <= 0 {
+            return Err("MaxLength must be greater than 0");
+        }
+
+        shlog_debug!(
+            "Reading string at address: 0x{:x}, max length: {}",
+            address,
+            max_length
+        );
+
+        let bytes = read_terminated(&mut process, address, max_length as usize, 1)?;
+
+        let string = String::from_utf8_lossy(&bytes);
+        self.output_string = Var::ephemeral_string(&string).into();
+        Ok(Some(self.output_string.0))
+    }
+}
+
+// Define the ReadWideString Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ReadWideString",
+    "Reads a null-terminated UTF-16LE string from a specific address in a process."
+)]
+pub struct MemflowReadWideStringShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Memory address of the string.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("MaxLength", "Maximum number of UTF-16 code units to read (default: 256).", [common_type::int, common_type::int_var])]
+    max_length: ParamVar,
+
+    // Output string
+    output_string: ClonedVar,
+}
+
+impl Default for MemflowReadWideStringShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            max_length: ParamVar::new(256.into()),
+            output_string: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowReadWideStringShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &STRING_TYPES // Outputs a string
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_string = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let address = parse_address(self.address.get())?;
+        let max_length: i64 = self.max_length.get().as_ref().try_into()?;
+        if max_length <= 0 {
+            return Err("MaxLength must be greater than 0");
+        }
+
+        shlog_debug!(
+            "Reading wide string at address: 0x{:x}, max length: {}",
+            address,
+            max_length
+        );
+
+        let bytes = read_terminated(&mut process, address, max_length as usize * 2, 2)?;
+
+        // A trailing odd byte can't form a code unit and is dropped, unpaired
+        // surrogates are replaced by from_utf16_lossy
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+
+        let string = String::from_utf16_lossy(&units);
+        self.output_string = Var::ephemeral_string(&string).into();
+        Ok(Some(self.output_string.0))
+    }
+}
+
+// Printable characters that make up extracted strings: printable ASCII and tab
+fn is_printable(c: u8) -> bool {
+    (0x20..=0x7E).contains(&c) || c == b'\t'
+}
+
+// A run of printable characters found in memory
+struct StringRun {
+    address: umem,
+    byte_len: usize,
+    string: String,
+}
+
+// Helper function to find runs of printable characters in a buffer, as single bytes or
+// as UTF-16LE code units (printable ASCII followed by a zero byte) at even addresses.
+// Runs shorter than `min_length` are dropped unless they touch either end of the buffer,
+// since they may continue in the neighbouring chunk and are joined by `join_runs`.
+fn scan_strings(buffer: &[u8], base_addr: umem, min_length: usize, wide: bool) -> Vec<StringRun> {
+    let unit_size = if wide { 2 } else { 1 };
+    let units = buffer.len() / unit_size;
+    let printable = |index: usize| {
+        let unit = &buffer[index * unit_size..(index + 1) * unit_size];
+        is_printable(unit[0]) && (!wide || unit[1] == 0)
+    };
+
+    let mut runs = Vec::new();
+    let mut index = 0;
+    while index < units {
+        if !printable(index) {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < units && printable(index) {
+            index += 1;
+        }
+
+        if index - start >= min_length || start == 0 || index == units {
+            let string = (start..index)
+                .map(|unit| buffer[unit * unit_size] as char)
+                .collect();
+            runs.push(StringRun {
+                address: base_addr + (start * unit_size) as umem,
+                byte_len: (index - start) * unit_size,
+                string,
+            });
+        }
+    }
+    runs
+}
+
+// Helper function to join runs that were split at a chunk boundary and drop the runs
+// that are still shorter than `min_length` afterwards. Runs are in address order, and
+// two runs from the same chunk are never adjacent, so adjacent runs always belong together.
+fn join_runs(runs: Vec<StringRun>, min_length: usize, wide: bool) -> Vec<StringRun> {
+    let mut joined: Vec<StringRun> = Vec::new();
+    for run in runs {
+        match joined.last_mut() {
+            Some(last) if last.address + last.byte_len as umem == run.address => {
+                last.byte_len += run.byte_len;
+                last.string.push_str(&run.string);
+            }
+            _ => joined.push(run),
+        }
+    }
+
+    let unit_size = if wide { 2 } else { 1 };
+    joined.retain(|run| run.byte_len / unit_size >= min_length);
+    joined
+}
+
+// Define the FindStrings Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.FindStrings",
+    "Extracts printable strings from the readable memory of a process, like running strings over it."
+)]
+pub struct MemflowFindStringsShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("MinLength", "Minimum number of characters in a string (default: 4).", [common_type::int, common_type::int_var])]
+    min_length: ParamVar,
+
+    #[shard_param("Encoding", "Character encoding of the strings: 'ascii' or 'utf16' (default: 'ascii').", [common_type::string, common_type::string_var])]
+    encoding: ParamVar,
+
+    #[shard_param("Protection", "Memory protection to filter by (default: 'r', any readable memory).", [common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Threads", "Number of worker threads scanning the read memory (default: 1).", [common_type::int, common_type::int_var])]
+    threads: ParamVar,
+
+    // Output list of strings as sequence of tables
+    strings: AutoSeqVar,
+}
+
+impl Default for MemflowFindStringsShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            min_length: ParamVar::new(4.into()),
+            encoding: ParamVar::new(Var::ephemeral_string("ascii")),
+            protection: ParamVar::new(Var::ephemeral_string("r")),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            threads: ParamVar::new(1.into()),
+            strings: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowFindStringsShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {address, string}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.strings = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let min_length: i64 = self.min_length.get().as_ref().try_into()?;
+        if min_length <= 0 {
+            return Err("MinLength must be greater than 0");
+        }
+        let min_length = min_length as usize;
+        let encoding: &str = self.encoding.get().as_ref().try_into()?;
+        let wide = match encoding {
+            "ascii" => false,
+            "utf16" => true,
+            _ => return Err("Unsupported encoding, expected 'ascii' or 'utf16'"),
+        };
+        let protection_filter: &str = self.protection.get().as_ref().try_into()?;
+        let threads: i64 = self.threads.get().as_ref().try_into()?;
+
+        // Chunks don't overlap, strings crossing a boundary are joined afterwards.
+        // Wide strings are only looked for at even addresses, so chunks must stay even.
+        let chunk_size = if wide {
+            chunk_size_param(&self.chunk_size, 1)? & !1
+        } else {
+            chunk_size_param(&self.chunk_size, 0)?
+        };
+
+        // Get memory maps with filtering by protection
+        let maps = process.process().mapped_mem_vec(0);
+        let filtered_maps: Vec<_> = maps
+            .into_iter()
+            .filter(|map| protection_filter_matches(map.2, protection_filter))
+            .collect();
+
+        shlog_debug!(
+            "Extracting {} strings from {} memory regions",
+            encoding,
+            filtered_maps.len()
+        );
+
+        let regions: Vec<(umem, usize)> = coalesce_regions(&filtered_maps)
+            .into_iter()
+            .flat_map(|(address, size)| split_region(address, size, chunk_size, 0))
+            .collect();
+
+        let runs = scan_regions(
+            &mut process,
+            &regions,
+            threads.max(1) as usize,
+            |base_addr, buffer| scan_strings(buffer, base_addr, min_length, wide),
+        );
+        let strings = join_runs(runs, min_length, wide);
+
+        shlog_debug!("Found {} strings", strings.len());
+
+        self.strings.0.clear();
+        for run in strings {
+            let address_var: Var = (run.address as i64).into();
+            let string_var = Var::ephemeral_string(&run.string);
+
+            let mut entry = AutoTableVar::new();
+            entry.0.insert_fast_static("address", &address_var);
+            entry.0.insert_fast_static("string", &string_var);
+            self.strings.0.emplace_table(entry);
+        }
+
+        Ok(Some(self.strings.0 .0))
+    }
+}
+
+// Number of bytes shared by consecutive chunks of a region scanned with a regex.
+// Matches starting in a chunk may run up to this far into the next one.
+const REGEX_CHUNK_OVERLAP: usize = 4096;
+
+// A regex match found in memory
+struct RegexMatch {
+    address: umem,
+    text: String,
+}
+
+// Helper function to run a regex over a buffer. UTF-16LE text is matched on one byte per
+// code unit (units outside ASCII become 0xFF, which never matches as text) and mapped back
+// to its address. Only matches starting before `report_end` are returned, the rest are
+// found again by the next, overlapping chunk. Empty matches are skipped.
+fn scan_regex(
+    buffer: &[u8],
+    base_addr: umem,
+    regex: &Regex,
+    wide: bool,
+    report_end: usize,
+) -> Vec<RegexMatch> {
+    if !wide {
+        return regex
+            .find_iter(buffer)
+            .take_while(|found| found.start() < report_end)
+            .filter(|found| !found.is_empty())
+            .map(|found| RegexMatch {
+                address: base_addr + found.start() as umem,
+                text: String::from_utf8_lossy(found.as_bytes()).into_owned(),
+            })
+            .collect();
+    }
+
+    let units: Vec<u16> = buffer
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+    let narrow: Vec<u8> = units
+        .iter()
+        .map(|&unit| if unit < 0x80 { unit as u8 } else { 0xFF })
+        .collect();
+
+    regex
+        .find_iter(&narrow)
+        .take_while(|found| found.start() * 2 < report_end)
+        .filter(|found| !found.is_empty())
+        .map(|found| RegexMatch {
+            address: base_addr + (found.start() * 2) as umem,
+            text: String::from_utf16_lossy(&units[found.range()]),
+        })
+        .collect()
+}
+
+// Define the RegexScan Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.RegexScan",
+    "Scans the readable memory of a process for text matching a regular expression."
+)]
+pub struct MemflowRegexScanShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Pattern", "Regular expression to search for (e.g., '[\\w.+-]+@[\\w-]+\\.[\\w.]+').", [common_type::string, common_type::string_var])]
+    pattern: ParamVar,
+
+    #[shard_param("Encoding", "Character encoding of the text: 'ascii' or 'utf16' (default: 'ascii').", [common_type::string, common_type::string_var])]
+    encoding: ParamVar,
+
+    #[shard_param("Protection", "Memory protection to filter by (default: 'r', any readable memory).", [common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("Limit", "Maximum number of matches to return, lowest addresses first. The scan stops once it is reached (default: 0, no limit).", [common_type::int, common_type::int_var])]
+    limit: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Threads", "Number of worker threads scanning the read memory (default: 1).", [common_type::int, common_type::int_var])]
+    threads: ParamVar,
+
+    // Compiled regex, kept until Pattern changes
+    regex: Option<(String, Regex)>,
+
+    // Output list of matches as sequence of tables
+    matches: AutoSeqVar,
+}
+
+impl Default for MemflowRegexScanShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            pattern: ParamVar::default(),
+            encoding: ParamVar::new(Var::ephemeral_string("ascii")),
+            protection: ParamVar::new(Var::ephemeral_string("r")),
+            limit: ParamVar::new(0.into()),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            threads: ParamVar::new(1.into()),
+            regex: None,
+            matches: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowRegexScanShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of {address, match}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.regex = None;
+        self.matches = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let pattern: &str = self.pattern.get().as_ref().try_into()?;
+        let encoding: &str = self.encoding.get().as_ref().try_into()?;
+        let wide = match encoding {
+            "ascii" => false,
+            "utf16" => true,
+            _ => return Err("Unsupported encoding, expected 'ascii' or 'utf16'"),
+        };
+        let protection_filter: &str = self.protection.get().as_ref().try_into()?;
+        let limit: i64 = self.limit.get().as_ref().try_into()?;
+        if limit < 0 {
+            return Err("Limit must not be negative");
+        }
+        let limit = (limit > 0).then_some(limit as usize);
+        let threads: i64 = self.threads.get().as_ref().try_into()?;
+
+        // Wide text is only looked for at even addresses, so chunks must stay even
+        let chunk_size = if wide {
+            chunk_size_param(&self.chunk_size, REGEX_CHUNK_OVERLAP + 1)? & !1
+        } else {
+            chunk_size_param(&self.chunk_size, REGEX_CHUNK_OVERLAP)?
+        };
+
+        // Compiling a regex is expensive, only do it again when the pattern changed
+        if !matches!(&self.regex, Some((compiled, _)) if compiled == pattern) {
+            let regex = Regex::new(pattern).map_err(|e| {
+                shlog_error!("Invalid regex '{}': {}", pattern, e);
+                "Invalid regex pattern."
+            })?;
+            self.regex = Some((pattern.to_string(), regex));
+        }
+        let regex = &self.regex.as_ref().unwrap().1;
+
+        // Get memory maps with filtering by protection
+        let maps = process.process().mapped_mem_vec(0);
+        let filtered_maps: Vec<_> = maps
+            .into_iter()
+            .filter(|map| protection_filter_matches(map.2, protection_filter))
+            .collect();
+
+        shlog_debug!(
+            "Scanning {} memory regions for regex '{}'",
+            filtered_maps.len(),
+            pattern
+        );
+
+        // Every match start belongs to exactly one chunk: the overlap at the end of a chunk
+        // is left to the next one, unless the chunk ends its region
+        let merged = coalesce_regions(&filtered_maps);
+        let region_ends: HashSet<umem> = merged
+            .iter()
+            .map(|(address, size)| address + *size as umem)
+            .collect();
+        let regions: Vec<(umem, usize)> = merged
+            .into_iter()
+            .flat_map(|(address, size)| {
+                split_region(address, size, chunk_size, REGEX_CHUNK_OVERLAP)
+            })
+            .collect();
+
+        let found = scan_regions_limited(
+            &mut process,
+            &regions,
+            threads.max(1) as usize,
+            limit,
+            |base_addr, buffer| {
+                let report_end = if region_ends.contains(&(base_addr + buffer.len() as umem)) {
+                    buffer.len()
+                } else {
+                    chunk_size - REGEX_CHUNK_OVERLAP
+                };
+                scan_regex(buffer, base_addr, regex, wide, report_end)
+            },
+        );
+
+        shlog_debug!("Found {} regex matches", found.len());
+
+        self.matches.0.clear();
+        for found in found {
+            let address_var: Var = (found.address as i64).into();
+            let match_var = Var::ephemeral_string(&found.text);
+
+            let mut entry = AutoTableVar::new();
+            entry.0.insert_fast_static("address", &address_var);
+            entry.0.insert_fast_static("match", &match_var);
+            self.matches.0.emplace_table(entry);
+        }
+
+        Ok(Some(self.matches.0 .0))
+    }
+}
@@ -0,0 +1,726 @@
+use crate::cached_process::process_handle;
+use crate::value_type::{Endian, ValueType};
+use crate::{
+    parse_address, MEMFLOW_CACHED_PROCESS_TYPE, MEMFLOW_CACHED_PROCESS_TYPE_VAR,
+    MEMFLOW_PROCESS_TYPE, MEMFLOW_PROCESS_TYPE_VAR,
+};
+
+use memflow::prelude::v1::*;
+use shards::shard::Shard;
+use shards::types::{
+    common_type, AutoSeqVar, AutoTableVar, ClonedVar, Context, ExposedTypes, InstanceData,
+    ParamVar, Type, Types, Var, ANYS_TYPES, ANY_TABLE_TYPES, ANY_TYPES, INT_TYPES,
+};
+use shards::{shlog_debug, shlog_error};
+
+// Largest memory span read at once by ReadStruct and ReadArray
+const MAX_READ_SPAN: usize = 64 * 1024 * 1024;
+
+// Helper function to get the byte span covering struct fields given as (offset, size)
+// pairs. Returns the offset the span starts at and its length, which is capped to
+// MAX_READ_SPAN.
+fn field_span(
+    fields: impl Iterator<Item = (i64, usize)>,
+) -> std::result::Result<(i64, usize), &'static str> {
+    let mut span_start = i64::MAX;
+    let mut span_end = i64::MIN;
+    for (offset, size) in fields {
+        let end = offset
+            .checked_add(size as i64)
+            .ok_or("Struct field offset out of range")?;
+        span_start = span_start.min(offset);
+        span_end = span_end.max(end);
+    }
+    if span_start > span_end {
+        return Ok((0, 0));
+    }
+
+    let span = span_end
+        .checked_sub(span_start)
+        .ok_or("Struct fields span too many bytes")?;
+    if span as u64 > MAX_READ_SPAN as u64 {
+        return Err("Struct fields span too many bytes");
+    }
+    Ok((span_start, span as usize))
+}
+
+// Define the ReadValue Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ReadValue",
+    "Reads a typed scalar value from a specific address in a process."
+)]
+pub struct MemflowReadValueShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Memory address to read from.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("ValueType", "Type of value to read: 'i8', 'u8', 'i16', 'u16', 'i32', 'u32', 'i64', 'u64', 'f32', 'f64'.", [common_type::string, common_type::string_var])]
+    value_type: ParamVar,
+
+    #[shard_param("Endian", "Byte order of the value: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+
+    // Output value
+    output_value: ClonedVar,
+}
+
+impl Default for MemflowReadValueShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            value_type: ParamVar::new(Var::ephemeral_string("i32")),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+            output_value: ClonedVar::default(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowReadValueShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TYPES // Outputs an int or a float
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_value = ClonedVar::default();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let address_umem = parse_address(self.address.get())?;
+        let value_type_str: &str = self.value_type.get().as_ref().try_into()?;
+        let value_type = ValueType::from_name(value_type_str)?;
+        let endian_str: &str = self.endian.get().as_ref().try_into()?;
+        let endian = Endian::from_name(endian_str)?;
+
+        shlog_debug!(
+            "Reading {} value at address: 0x{:x}",
+            value_type_str,
+            address_umem
+        );
+
+        // Read exactly the size of the value
+        let mut buffer = [0u8; 8];
+        let buffer = &mut buffer[..value_type.size()];
+        process.read_raw_into(address_umem, buffer).map_err(|e| {
+            shlog_error!("Failed to read memory: {}", e);
+            "Failed to read memory from process."
+        })?;
+
+        let value = value_type
+            .decode_var(buffer, endian)
+            .ok_or("Failed to decode value.")?;
+
+        self.output_value = value.into();
+        Ok(Some(self.output_value.0))
+    }
+}
+
+// Define the WriteValue Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.WriteValue",
+    "Writes a typed scalar value to a specific address in a process."
+)]
+pub struct MemflowWriteValueShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Process", "The plain or cached Memflow Process instance to write to.", [*MEMFLOW_PROCESS_TYPE, *MEMFLOW_PROCESS_TYPE_VAR, *MEMFLOW_CACHED_PROCESS_TYPE, *MEMFLOW_CACHED_PROCESS_TYPE_VAR])]
+    process_instance: ParamVar,
+
+    #[shard_param("Address", "Memory address to write to.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("ValueType", "Type of value to write: 'i8', 'u8', 'i16', 'u16', 'i32', 'u32', 'i64', 'u64', 'f32', 'f64'.", [common_type::string, common_type::string_var])]
+    value_type: ParamVar,
+
+    #[shard_param("Endian", "Byte order of the value: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+}
+
+impl Default for MemflowWriteValueShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            process_instance: ParamVar::default(),
+            address: ParamVar::new(0.into()),
+            value_type: ParamVar::new(Var::ephemeral_string("i32")),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowWriteValueShard {
+    fn input_types(&mut self) -> &Types {
+        &ANY_TYPES // Takes the int or float value to write as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &INT_TYPES // Outputs the number of bytes written
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from parameter
+        let mut process = process_handle(self.process_instance.get())?;
+
+        // Get parameters
+        let address_umem = parse_address(self.address.get())?;
+        let value_type_str: &str = self.value_type.get().as_ref().try_into()?;
+        let value_type = ValueType::from_name(value_type_str)?;
+        let endian_str: &str = self.endian.get().as_ref().try_into()?;
+        let endian = Endian::from_name(endian_str)?;
+
+        // Encode the input at the requested width
+        let data = value_type.encode_var(input, endian)?;
+
+        shlog_debug!(
+            "Writing {} value at address: 0x{:x}",
+            value_type_str,
+            address_umem
+        );
+
+        process.write_raw(address_umem, &data).map_err(|e| {
+            shlog_error!("Failed to write memory: {}", e);
+            "Failed to write memory to process."
+        })?;
+
+        Ok(Some(Var::new_int(data.len() as i64)))
+    }
+}
+
+// Define the BatchReadValues Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.BatchReadValues",
+    "Reads many typed scalar values from a process in a single batched operation."
+)]
+pub struct MemflowBatchReadValuesShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Reads", "Table or sequence of reads with 'address' and 'type' fields, and an optional 'endian' field. Values are returned in a table under the same keys, or in a sequence in the same order.", [common_type::any_table, common_type::any_table_var, common_type::anys, common_type::anys_var])]
+    reads: ParamVar,
+
+    #[shard_param("Endian", "Byte order of values without an 'endian' field: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+
+    // Output values, as a table or a sequence depending on Reads
+    output_table: AutoTableVar,
+    output_seq: AutoSeqVar,
+}
+
+impl Default for MemflowBatchReadValuesShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            reads: ParamVar::default(),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+            output_table: AutoTableVar::new(),
+            output_seq: AutoSeqVar::new(),
+        }
+    }
+}
+
+// Helper function to get the address, value type and byte order of a typed read entry
+fn value_read_entry(
+    entry: &Var,
+    default_endian: Endian,
+) -> std::result::Result<(umem, ValueType, Endian), &'static str> {
+    let read_table = entry.as_table()?;
+
+    let address_var = read_table
+        .get(Var::ephemeral_string("address"))
+        .ok_or("Missing 'address' field in read entry")?;
+    let type_var = read_table
+        .get(Var::ephemeral_string("type"))
+        .ok_or("Missing 'type' field in read entry")?;
+
+    let address = parse_address(&address_var)?;
+    let type_str: &str = type_var.as_ref().try_into()?;
+    let value_type = ValueType::from_name(type_str)?;
+    let endian = match read_table.get(Var::ephemeral_string("endian")) {
+        Some(endian_var) => {
+            let endian_str: &str = endian_var.as_ref().try_into()?;
+            Endian::from_name(endian_str)?
+        }
+        None => default_endian,
+    };
+
+    Ok((address, value_type, endian))
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowBatchReadValuesShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TYPES // Outputs a table or a sequence of ints and floats
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_table = AutoTableVar::new();
+        self.output_seq = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let endian_str: &str = self.endian.get().as_ref().try_into()?;
+        let default_endian = Endian::from_name(endian_str)?;
+
+        struct ValueRead {
+            key: Var,
+            address: umem,
+            value_type: ValueType,
+            endian: Endian,
+            buffer: [u8; 8],
+        }
+
+        // Collect all reads, keyed when they come from a table
+        let reads_var = self.reads.get();
+        let keyed = reads_var.as_table().is_ok();
+        let mut reads = Vec::new();
+        if keyed {
+            let reads_table = reads_var.as_table()?;
+            for (key, entry) in reads_table.iter() {
+                let (address, value_type, endian) = value_read_entry(&entry, default_endian)?;
+                reads.push(ValueRead {
+                    key,
+                    address,
+                    value_type,
+                    endian,
+                    buffer: [0u8; 8],
+                });
+            }
+        } else {
+            for entry in reads_var.as_seq()?.iter() {
+                let (address, value_type, endian) = value_read_entry(&entry, default_endian)?;
+                reads.push(ValueRead {
+                    key: Var::default(),
+                    address,
+                    value_type,
+                    endian,
+                    buffer: [0u8; 8],
+                });
+            }
+        }
+
+        shlog_debug!("Batch reading {} values", reads.len());
+
+        if process.is_cached() {
+            // Cached reads are served page by page from the cache
+            for read in &mut reads {
+                let size = read.value_type.size();
+                process
+                    .read_raw_into(read.address, &mut read.buffer[..size])
+                    .map_err(|e| {
+                        shlog_error!("Failed to read memory at 0x{:x}: {}", read.address, e);
+                        "Failed to read memory from process."
+                    })?;
+            }
+        } else {
+            let mut batcher = process.process().batcher();
+            for read in &mut reads {
+                let size = read.value_type.size();
+                batcher.read_raw_into(Address::from(read.address), &mut read.buffer[..size]);
+            }
+            batcher.commit_rw().map_err(|e| {
+                shlog_error!("Failed to execute batch value read: {}", e);
+                "Failed to read memory from process."
+            })?;
+        }
+
+        self.output_table.0.clear();
+        self.output_seq.0.clear();
+        for read in reads {
+            let value = read
+                .value_type
+                .decode_var(&read.buffer, read.endian)
+                .ok_or("Failed to decode value.")?;
+            if keyed {
+                self.output_table.0.insert_fast(read.key, &value);
+            } else {
+                self.output_seq.0.push(&value);
+            }
+        }
+
+        if keyed {
+            Ok(Some(self.output_table.0 .0))
+        } else {
+            Ok(Some(self.output_seq.0 .0))
+        }
+    }
+}
+
+// Define the ReadStruct Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ReadStruct",
+    "Reads several typed fields at offsets from a base address with a single read."
+)]
+pub struct MemflowReadStructShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Base", "Base address of the struct.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    base: ParamVar,
+
+    #[shard_param("Fields", "Table mapping field names to {offset, type} tables, with an optional 'endian' field.", [common_type::any_table, common_type::any_table_var])]
+    fields: ParamVar,
+
+    #[shard_param("Endian", "Byte order of fields without an 'endian' field: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+
+    // Output table of field values
+    output_fields: AutoTableVar,
+}
+
+impl Default for MemflowReadStructShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            base: ParamVar::new(0.into()),
+            fields: ParamVar::default(),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+            output_fields: AutoTableVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowReadStructShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANY_TABLE_TYPES // Outputs {name: value}
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_fields = AutoTableVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        let base = parse_address(self.base.get())?;
+        let endian_str: &str = self.endian.get().as_ref().try_into()?;
+        let default_endian = Endian::from_name(endian_str)?;
+
+        // Collect the fields and the byte span covering all of them
+        let fields_table = self.fields.get().as_table()?;
+        let mut fields = Vec::new();
+        for (name, field) in fields_table.iter() {
+            let field_table = field.as_table()?;
+            let offset_var = field_table
+                .get(Var::ephemeral_string("offset"))
+                .ok_or("Missing 'offset' field in struct field")?;
+            let type_var = field_table
+                .get(Var::ephemeral_string("type"))
+                .ok_or("Missing 'type' field in struct field")?;
+
+            let offset: i64 = offset_var.as_ref().try_into()?;
+            let type_str: &str = type_var.as_ref().try_into()?;
+            let value_type = ValueType::from_name(type_str)?;
+            let endian = match field_table.get(Var::ephemeral_string("endian")) {
+                Some(endian_var) => {
+                    let endian_str: &str = endian_var.as_ref().try_into()?;
+                    Endian::from_name(endian_str)?
+                }
+                None => default_endian,
+            };
+
+            fields.push((name, offset, value_type, endian));
+        }
+
+        self.output_fields.0.clear();
+        if fields.is_empty() {
+            return Ok(Some(self.output_fields.0 .0));
+        }
+
+        let (span_start, span) = field_span(
+            fields
+                .iter()
+                .map(|(_, offset, value_type, _)| (*offset, value_type.size())),
+        )?;
+        let span_address = base.wrapping_add(span_start as umem);
+
+        shlog_debug!(
+            "Reading struct with {} fields at address: 0x{:x}, span: {} bytes",
+            fields.len(),
+            span_address,
+            span
+        );
+
+        // Read the whole span once and decode every field from it
+        let mut buffer = vec![0u8; span];
+        process
+            .read_raw_into(span_address, &mut buffer)
+            .map_err(|e| {
+                shlog_error!("Failed to read memory: {}", e);
+                "Failed to read memory from process."
+            })?;
+
+        for (name, offset, value_type, endian) in fields {
+            let start = (offset - span_start) as usize;
+            let value = value_type
+                .decode_var(&buffer[start..], endian)
+                .ok_or("Failed to decode value.")?;
+            self.output_fields.0.insert_fast(name, &value);
+        }
+
+        Ok(Some(self.output_fields.0 .0))
+    }
+}
+
+// Define the ReadArray Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.ReadArray",
+    "Reads an array of typed scalar values, optionally spaced by a stride, with a single read."
+)]
+pub struct MemflowReadArrayShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Base", "Address of the first element.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    base: ParamVar,
+
+    #[shard_param("Type", "Type of the elements: 'i8', 'u8', 'i16', 'u16', 'i32', 'u32', 'i64', 'u64', 'f32', 'f64'.", [common_type::string, common_type::string_var])]
+    value_type: ParamVar,
+
+    #[shard_param("Count", "Number of elements to read.", [common_type::int, common_type::int_var])]
+    count: ParamVar,
+
+    #[shard_param("Stride", "Distance between elements in bytes (default: the element size).", [common_type::none, common_type::int, common_type::int_var])]
+    stride: ParamVar,
+
+    #[shard_param("Endian", "Byte order of the elements: 'native', 'little', 'big' (default: 'native').", [common_type::string, common_type::string_var])]
+    endian: ParamVar,
+
+    // Output sequence of values
+    output_values: AutoSeqVar,
+}
+
+impl Default for MemflowReadArrayShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            base: ParamVar::new(0.into()),
+            value_type: ParamVar::new(Var::ephemeral_string("i32")),
+            count: ParamVar::new(1.into()),
+            stride: ParamVar::default(),
+            endian: ParamVar::new(Var::ephemeral_string("native")),
+            output_values: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowReadArrayShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of ints or floats
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.output_values = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let base_umem = parse_address(self.base.get())?;
+        let value_type_str: &str = self.value_type.get().as_ref().try_into()?;
+        let value_type = ValueType::from_name(value_type_str)?;
+        let endian_str: &str = self.endian.get().as_ref().try_into()?;
+        let endian = Endian::from_name(endian_str)?;
+        let count: i64 = self.count.get().as_ref().try_into()?;
+        let stride: i64 = if self.stride.get().is_none() {
+            value_type.size() as i64
+        } else {
+            self.stride.get().as_ref().try_into()?
+        };
+
+        if count <= 0 {
+            return Err("Count must be greater than 0");
+        }
+        if stride <= 0 {
+            return Err("Stride must be greater than 0");
+        }
+
+        // The last element only needs its own size, not a full stride
+        let count = count as usize;
+        let stride = stride as usize;
+        let size = stride
+            .checked_mul(count - 1)
+            .and_then(|size| size.checked_add(value_type.size()))
+            .filter(|size| *size <= MAX_READ_SPAN)
+            .ok_or("Array is too large to read at once")?;
+
+        shlog_debug!(
+            "Reading {} {} values at address: 0x{:x}, stride: {} bytes",
+            count,
+            value_type_str,
+            base_umem,
+            stride
+        );
+
+        let mut buffer = vec![0u8; size];
+        process.read_raw_into(base_umem, &mut buffer).map_err(|e| {
+            shlog_error!("Failed to read memory: {}", e);
+            "Failed to read memory from process."
+        })?;
+
+        self.output_values.0.clear();
+        for index in 0..count {
+            let value = value_type
+                .decode_var(&buffer[index * stride..], endian)
+                .ok_or("Failed to decode value.")?;
+            self.output_values.0.push(&value);
+        }
+
+        Ok(Some(self.output_values.0 .0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_span_covers_all_fields() {
+        let fields = [(8, 4), (-4, 4), (0, 8)];
+        assert_eq!(field_span(fields.into_iter()), Ok((-4, 16)));
+    }
+
+    #[test]
+    fn field_span_of_no_fields_is_empty() {
+        assert_eq!(field_span(std::iter::empty()), Ok((0, 0)));
+    }
+
+    #[test]
+    fn field_span_rejects_overflowing_offsets() {
+        assert!(field_span([(i64::MAX - 2, 8)].into_iter()).is_err());
+        assert!(field_span([(i64::MIN, 1), (i64::MAX - 8, 8)].into_iter()).is_err());
+    }
+
+    #[test]
+    fn field_span_rejects_huge_spans() {
+        let fields = [(0, 1), (MAX_READ_SPAN as i64, 1)];
+        assert!(field_span(fields.into_iter()).is_err());
+        let fields = [(0, 1), (MAX_READ_SPAN as i64 - 1, 1)];
+        assert_eq!(field_span(fields.into_iter()), Ok((0, MAX_READ_SPAN)));
+    }
+}
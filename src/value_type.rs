@@ -0,0 +1,201 @@
+use shards::types::Var;
+use std::cmp::Ordering;
+
+// Scalar value types that can be read from or written to memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+// Byte order used to decode and encode values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endian {
+    Native,
+    Little,
+    Big,
+}
+
+impl Endian {
+    // Parse an endianness name: "native", "little" or "big"
+    pub fn from_name(name: &str) -> Result<Endian, &'static str> {
+        match name {
+            "native" => Ok(Endian::Native),
+            "little" => Ok(Endian::Little),
+            "big" => Ok(Endian::Big),
+            _ => Err("Unsupported endianness"),
+        }
+    }
+
+    // Whether values in this byte order have to be reversed to be native
+    fn needs_swap(&self) -> bool {
+        match self {
+            Endian::Native => false,
+            Endian::Little => cfg!(target_endian = "big"),
+            Endian::Big => cfg!(target_endian = "little"),
+        }
+    }
+}
+
+// Helper function to take the first N bytes of a slice as a native-endian array
+fn take_bytes<const N: usize>(bytes: &[u8], endian: Endian) -> Option<[u8; N]> {
+    let mut array: [u8; N] = bytes.get(..N)?.try_into().ok()?;
+    if endian.needs_swap() {
+        array.reverse();
+    }
+    Some(array)
+}
+
+// Helper function to turn native-endian bytes into the requested byte order
+fn put_bytes<const N: usize>(mut array: [u8; N], endian: Endian) -> Vec<u8> {
+    if endian.needs_swap() {
+        array.reverse();
+    }
+    array.to_vec()
+}
+
+impl ValueType {
+    // Parse a value type name such as "u32" or "f64"
+    pub fn from_name(name: &str) -> Result<ValueType, &'static str> {
+        match name {
+            "i8" => Ok(ValueType::I8),
+            "u8" => Ok(ValueType::U8),
+            "i16" => Ok(ValueType::I16),
+            "u16" => Ok(ValueType::U16),
+            "i32" => Ok(ValueType::I32),
+            "u32" => Ok(ValueType::U32),
+            "i64" => Ok(ValueType::I64),
+            "u64" => Ok(ValueType::U64),
+            "f32" => Ok(ValueType::F32),
+            "f64" => Ok(ValueType::F64),
+            _ => Err("Unsupported value type"),
+        }
+    }
+
+    // Size of the value in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            ValueType::I8 | ValueType::U8 => 1,
+            ValueType::I16 | ValueType::U16 => 2,
+            ValueType::I32 | ValueType::U32 | ValueType::F32 => 4,
+            ValueType::I64 | ValueType::U64 | ValueType::F64 => 8,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, ValueType::F32 | ValueType::F64)
+    }
+
+    // Decode an integer value from the start of `bytes`.
+    // Unsigned 64-bit values are reinterpreted as i64 since Shards ints are signed.
+    pub fn decode_int(&self, bytes: &[u8], endian: Endian) -> Option<i64> {
+        Some(match self {
+            ValueType::I8 => i8::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::U8 => u8::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::I16 => i16::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::U16 => u16::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::I32 => i32::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::U32 => u32::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::I64 => i64::from_ne_bytes(take_bytes(bytes, endian)?),
+            ValueType::U64 => u64::from_ne_bytes(take_bytes(bytes, endian)?) as i64,
+            ValueType::F32 | ValueType::F64 => return None,
+        })
+    }
+
+    // Order two integers decoded with `decode_int`. U64 values are compared as unsigned,
+    // since the ones at or above 2^63 come back negative.
+    pub fn cmp_int(&self, a: i64, b: i64) -> Ordering {
+        match self {
+            ValueType::U64 => (a as u64).cmp(&(b as u64)),
+            _ => a.cmp(&b),
+        }
+    }
+
+    // Decode a floating point value from the start of `bytes`
+    pub fn decode_float(&self, bytes: &[u8], endian: Endian) -> Option<f64> {
+        Some(match self {
+            ValueType::F32 => f32::from_ne_bytes(take_bytes(bytes, endian)?) as f64,
+            ValueType::F64 => f64::from_ne_bytes(take_bytes(bytes, endian)?),
+            _ => return None,
+        })
+    }
+
+    // Decode the value at the start of `bytes` into an int or float Var
+    pub fn decode_var(&self, bytes: &[u8], endian: Endian) -> Option<Var> {
+        if self.is_float() {
+            self.decode_float(bytes, endian).map(Var::new_float)
+        } else {
+            self.decode_int(bytes, endian).map(Var::new_int)
+        }
+    }
+
+    // Encode an int or float Var into bytes of the exact width of this type.
+    // Integers that don't fit the requested width are rejected instead of truncated,
+    // except for u64 which accepts any i64 bit pattern.
+    pub fn encode_var(&self, value: &Var, endian: Endian) -> Result<Vec<u8>, &'static str> {
+        if self.is_float() {
+            let float_value: f64 = match TryInto::<f64>::try_into(value) {
+                Ok(v) => v,
+                Err(_) => TryInto::<i64>::try_into(value)? as f64,
+            };
+            return Ok(match self {
+                ValueType::F32 => put_bytes((float_value as f32).to_ne_bytes(), endian),
+                _ => put_bytes(float_value.to_ne_bytes(), endian),
+            });
+        }
+
+        let int_value: i64 = value
+            .try_into()
+            .map_err(|_| "Integer value types require an int input")?;
+        let out_of_range = "Value does not fit in the requested value type";
+        Ok(match self {
+            ValueType::I8 => put_bytes(
+                i8::try_from(int_value)
+                    .map_err(|_| out_of_range)?
+                    .to_ne_bytes(),
+                endian,
+            ),
+            ValueType::U8 => put_bytes(
+                u8::try_from(int_value)
+                    .map_err(|_| out_of_range)?
+                    .to_ne_bytes(),
+                endian,
+            ),
+            ValueType::I16 => put_bytes(
+                i16::try_from(int_value)
+                    .map_err(|_| out_of_range)?
+                    .to_ne_bytes(),
+                endian,
+            ),
+            ValueType::U16 => put_bytes(
+                u16::try_from(int_value)
+                    .map_err(|_| out_of_range)?
+                    .to_ne_bytes(),
+                endian,
+            ),
+            ValueType::I32 => put_bytes(
+                i32::try_from(int_value)
+                    .map_err(|_| out_of_range)?
+                    .to_ne_bytes(),
+                endian,
+            ),
+            ValueType::U32 => put_bytes(
+                u32::try_from(int_value)
+                    .map_err(|_| out_of_range)?
+                    .to_ne_bytes(),
+                endian,
+            ),
+            ValueType::I64 => put_bytes(int_value.to_ne_bytes(), endian),
+            ValueType::U64 => put_bytes((int_value as u64).to_ne_bytes(), endian),
+            ValueType::F32 | ValueType::F64 => unreachable!(),
+        })
+    }
+}
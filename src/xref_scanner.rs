@@ -1,5 +1,9 @@
-use capstone::{prelude::*, Capstone, Insn};
+use crate::cached_process::ProcessHandle;
+
+use capstone::arch::x86::{X86OpMem, X86Reg};
+use capstone::{prelude::*, Capstone, Insn, RegId};
 use memflow::prelude::v1::*;
+use std::collections::VecDeque;
 use std::result;
 
 // Define reference types
@@ -18,6 +22,42 @@ pub enum Arch {
     X86_64,
 }
 
+impl Arch {
+    // Map a memflow architecture to the disassembler architecture, if supported
+    pub fn from_ident(ident: ArchitectureIdent) -> Option<Arch> {
+        match ident {
+            ArchitectureIdent::X86(32, _) => Some(Arch::X86_32),
+            ArchitectureIdent::X86(64, _) => Some(Arch::X86_64),
+            _ => None,
+        }
+    }
+
+    // Parse an architecture name as used by shard params
+    pub fn from_name(name: &str) -> result::Result<Arch, &'static str> {
+        match name {
+            "x86" | "x86_32" => Ok(Arch::X86_32),
+            "x64" | "x86_64" => Ok(Arch::X86_64),
+            _ => Err("Unsupported architecture, expected 'x86' or 'x64'"),
+        }
+    }
+
+    // Size of a pointer in bytes
+    pub fn pointer_size(&self) -> usize {
+        match self {
+            Arch::X86_32 => 4,
+            Arch::X86_64 => 8,
+        }
+    }
+
+    // Truncate an address to the address width of the architecture
+    pub fn wrap_address(&self, address: u64) -> u64 {
+        match self {
+            Arch::X86_32 => address & 0xFFFF_FFFF,
+            Arch::X86_64 => address,
+        }
+    }
+}
+
 impl XrefType {
     pub fn to_string(&self) -> &'static str {
         match self {
@@ -37,6 +77,39 @@ pub struct XrefResult {
     pub context: Vec<String>, // Surrounding instructions for context
 }
 
+// Number of bytes shared by consecutive chunks of a region scanned for references.
+// A chunk reports the references in its first half of the overlap and leaves the ones in
+// the second half to the next chunk, so every reported reference has a full context
+// decode window before it and room for the instructions after it.
+pub const XREF_CHUNK_OVERLAP: usize = 2 * CONTEXT_DECODE_WINDOW;
+
+// Largest number of context instructions kept before and after a reference. The
+// instructions after it always fit in the half of the chunk overlap that follows it.
+pub const MAX_CONTEXT_INSTRUCTIONS: usize = 64;
+
+// Helper function to get the addresses whose references a chunk of a region reports.
+// Chunks split their overlap in the middle: references in its second half are left to
+// the next chunk, which has the whole context window before them.
+pub fn owned_chunk_range(
+    chunk_addr: u64,
+    chunk_len: usize,
+    region_addr: u64,
+    region_end: u64,
+) -> (u64, u64) {
+    let chunk_end = chunk_addr + chunk_len as u64;
+    let owned_start = if chunk_addr == region_addr {
+        region_addr
+    } else {
+        chunk_addr + (XREF_CHUNK_OVERLAP / 2) as u64
+    };
+    let owned_end = if chunk_end == region_end {
+        region_end
+    } else {
+        chunk_end - (XREF_CHUNK_OVERLAP / 2) as u64
+    };
+    (owned_start, owned_end)
+}
+
 // Helper function to initialize Capstone for the appropriate architecture
 pub fn init_capstone(arch: Arch) -> result::Result<Capstone, capstone::Error> {
     match arch {
@@ -61,13 +134,37 @@ pub fn init_capstone(arch: Arch) -> result::Result<Capstone, capstone::Error> {
     }
 }
 
-// Helper function to check if an instruction references a target address
+// Helper function to compute the address of the pointer slot read by a memory operand.
+// Only RIP-relative and absolute operands can be resolved without executing code,
+// anything involving other registers yields None.
+fn pointer_slot(insn: &Insn, mem: &X86OpMem, arch: Arch) -> Option<u64> {
+    if mem.index() != RegId::INVALID_REG {
+        return None;
+    }
+
+    let disp = mem.disp() as u64;
+    if mem.base() == RegId(X86Reg::X86_REG_RIP as u16) {
+        // RIP-relative operands are relative to the end of the instruction
+        let next_insn = insn.address().wrapping_add(insn.bytes().len() as u64);
+        Some(next_insn.wrapping_add(disp))
+    } else if mem.base() == RegId::INVALID_REG {
+        Some(arch.wrap_address(disp))
+    } else {
+        None
+    }
+}
+
+// Helper function to check if an instruction references a target address.
+// `read_pointer` reads a pointer from the process and is used to follow indirect
+// calls and jumps through memory.
 pub fn is_reference_to(
     insn: &Insn,
     target_addr: u64,
     include_jumps: bool,
     include_indirect: bool,
+    arch: Arch,
     cs: &Capstone,
+    read_pointer: &mut dyn FnMut(u64) -> Option<u64>,
 ) -> Option<XrefType> {
     // Get instruction details
     let detail = match cs.insn_detail(insn) {
@@ -101,7 +198,7 @@ pub fn is_reference_to(
         match op.op_type {
             capstone::arch::x86::X86OperandType::Imm(imm) => {
                 // For direct calls/jumps, the immediate value is the target
-                if imm as u64 == target_addr {
+                if arch.wrap_address(imm as u64) == target_addr {
                     if is_call {
                         return Some(XrefType::Call);
                     } else if is_jump {
@@ -113,7 +210,9 @@ pub fn is_reference_to(
                 // E8/E9 + 5 + imm = target_addr
                 let insn_addr = insn.address();
                 let insn_size = insn.bytes().len() as u64;
-                let calculated_target = insn_addr.wrapping_add(insn_size).wrapping_add(imm as u64);
+                // Relative targets wrap at the address width (32 bits for x86)
+                let calculated_target =
+                    arch.wrap_address(insn_addr.wrapping_add(insn_size).wrapping_add(imm as u64));
 
                 if calculated_target == target_addr {
                     if is_call {
@@ -124,11 +223,13 @@ pub fn is_reference_to(
                 }
             }
             capstone::arch::x86::X86OperandType::Mem(mem) => {
-                // For indirect calls/jumps through memory
-                if include_indirect {
-                    // This is a simplified check - in a real implementation,
-                    // we would need to read the memory at this location to see if it contains our target
-                    if mem.disp() as u64 == target_addr {
+                // For indirect calls/jumps through memory, the target is the pointer
+                // stored in the slot the operand reads
+                if include_indirect && (is_call || is_jump) {
+                    let target = pointer_slot(insn, &mem, arch)
+                        .and_then(|slot| read_pointer(slot))
+                        .map(|pointer| arch.wrap_address(pointer));
+                    if target == Some(target_addr) {
                         return Some(XrefType::Indirect);
                     }
                 }
@@ -140,83 +241,148 @@ pub fn is_reference_to(
     None
 }
 
-// Helper function to get context instructions around a reference
+// Helper function to format a single instruction as a line of text.
+// When no format template is given the default fixed-width layout is used.
+// Templates may use the {address}, {bytes}, {mnemonic} and {op_str} placeholders.
+pub fn format_instruction(insn: &Insn, format: Option<&str>) -> String {
+    let addr_str = format!("0x{:x}", insn.address());
+    let bytes_str = insn
+        .bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mnemonic = insn.mnemonic().unwrap_or("");
+    let op_str = insn.op_str().unwrap_or("");
+
+    match format {
+        Some(template) => template
+            .replace("{address}", &addr_str)
+            .replace("{bytes}", &bytes_str)
+            .replace("{mnemonic}", mnemonic)
+            .replace("{op_str}", op_str),
+        None => format!(
+            "{:<10} {:<20} {:<8} {}",
+            addr_str, bytes_str, mnemonic, op_str
+        ),
+    }
+}
+
+// Longest possible x86 instruction in bytes
+pub const MAX_INSN_LEN: usize = 15;
+
+// Farthest distance before a reference that decoding for its context starts at
+pub const CONTEXT_DECODE_WINDOW: usize = 0x1000;
+
+// Helper function to find a known instruction boundary at most CONTEXT_DECODE_WINDOW bytes
+// before `insn_offset`: the first byte after the last int3 padding (two or more 0xCC
+// bytes, which compilers put between functions), or else the start of the window, which
+// is the start of the buffer for references near it.
+fn context_decode_start(buffer: &[u8], insn_offset: usize) -> usize {
+    let window_start = insn_offset.saturating_sub(CONTEXT_DECODE_WINDOW);
+    (window_start + 2..insn_offset)
+        .rev()
+        .find(|&offset| {
+            buffer[offset - 1] == 0xCC && buffer[offset - 2] == 0xCC && buffer[offset] != 0xCC
+        })
+        .unwrap_or(window_start)
+}
+
+// Helper function to get context instructions around a reference.
+// `insn_offset` is the buffer offset of the referencing instruction. The result holds
+// up to `context_count` instructions before it, the instruction itself and up to
+// `context_count` instructions after it. Instructions before the reference are found
+// by decoding forward from a known boundary (see `context_decode_start`) and keeping
+// the last `context_count` of them. Bytes that don't decode are skipped one at a time,
+// the decoding falls back in step with the code within a few instructions.
 pub fn get_instruction_context(
     buffer: &[u8],
-    ref_offset: usize,
+    insn_offset: usize,
     context_count: usize,
     base_addr: u64,
+    format: Option<&str>,
     cs: &Capstone,
 ) -> Vec<String> {
     let mut context = Vec::new();
 
-    // Determine the range to disassemble for context
-    // This is a simplified approach - in a real implementation, we would need to
-    // be more careful about instruction boundaries
-    let start_offset = if ref_offset > context_count * 15 {
-        ref_offset - context_count * 15
-    } else {
-        0
-    };
+    // Instructions before the reference, as (offset, length)
+    let mut before: VecDeque<(usize, usize)> = VecDeque::with_capacity(context_count + 1);
+    let mut offset = context_decode_start(buffer, insn_offset);
+    while context_count > 0 && offset < insn_offset {
+        let decoded = match cs.disasm_all(&buffer[offset..insn_offset], base_addr + offset as u64) {
+            Ok(insns) => {
+                let mut decoded = 0;
+                for insn in insns.iter() {
+                    before.push_back((offset + decoded, insn.bytes().len()));
+                    if before.len() > context_count {
+                        before.pop_front();
+                    }
+                    decoded += insn.bytes().len();
+                }
+                decoded
+            }
+            Err(_) => 0,
+        };
+        offset += decoded.max(1);
+    }
 
-    let end_offset = if ref_offset + 15 + context_count * 15 < buffer.len() {
-        ref_offset + 15 + context_count * 15
-    } else {
-        buffer.len()
-    };
+    for (offset, len) in before {
+        if let Ok(insns) =
+            cs.disasm_count(&buffer[offset..offset + len], base_addr + offset as u64, 1)
+        {
+            for insn in insns.iter() {
+                context.push(format_instruction(&insn, format));
+            }
+        }
+    }
 
-    // Disassemble the context range
-    if let Ok(insns) = cs.disasm_all(
-        &buffer[start_offset..end_offset],
-        base_addr + start_offset as u64,
+    // The reference itself and the instructions after it
+    if let Ok(insns) = cs.disasm_count(
+        &buffer[insn_offset..],
+        base_addr + insn_offset as u64,
+        context_count + 1,
     ) {
         for insn in insns.iter() {
-            let addr_str = format!("0x{:x}", insn.address());
-            let bytes_str = insn
-                .bytes()
-                .iter()
-                .map(|b| format!("{:02x}", b))
-                .collect::<Vec<String>>()
-                .join(" ");
-            let mnemonic = insn.mnemonic().unwrap_or("");
-            let op_str = insn.op_str().unwrap_or("");
-
-            let formatted = format!(
-                "{:<10} {:<20} {:<8} {}",
-                addr_str, bytes_str, mnemonic, op_str
-            );
-            context.push(formatted);
+            context.push(format_instruction(&insn, format));
         }
     }
 
     context
 }
 
-// Helper function to scan a memory region for references to a target address
+// Helper function to scan a memory region for references to a target address.
+// The Capstone instance comes from `init_capstone` and is shared by all regions of a
+// scan, it must have detail mode enabled for `is_reference_to` to work.
 pub fn scan_region_for_xrefs(
-    process: &mut ProcessInstanceArcBox<'_>,
+    process: &mut ProcessHandle,
     region_addr: Address,
     region_size: usize,
     target_addr: u64,
     include_jumps: bool,
     include_indirect: bool,
     context_count: usize,
+    context_format: Option<&str>,
     arch: Arch,
+    cs: &Capstone,
 ) -> Vec<XrefResult> {
     let mut results = Vec::new();
 
-    // Initialize Capstone
-    let cs = match init_capstone(arch) {
-        Ok(cs) => cs,
-        Err(_) => return results,
-    };
-
     // Read the memory region
     let mut buffer = vec![0u8; region_size];
-    if let Err(_) = process.read_raw_into(region_addr, &mut buffer) {
+    if let Err(_) = process.read_raw_into(region_addr.to_umem(), &mut buffer) {
         return results;
     }
 
+    // Reads the pointer stored at a slot, for resolving indirect references
+    let pointer_size = arch.pointer_size();
+    let mut read_pointer = |slot: u64| {
+        let mut pointer = [0u8; 8];
+        process
+            .read_raw_into(slot, &mut pointer[..pointer_size])
+            .ok()?;
+        Some(u64::from_le_bytes(pointer))
+    };
+
     // First pass: use pattern scanning to find potential call/jump instructions
     // E8 (call), E9 (jmp), FF15 (call [mem]), etc.
     let potential_offsets = find_potential_call_offsets(&buffer, include_jumps, include_indirect);
@@ -245,16 +411,23 @@ pub fn scan_region_for_xrefs(
 
                 if insn_start as usize <= offset && offset < insn_end as usize {
                     // Check if this instruction references our target
-                    if let Some(xref_type) =
-                        is_reference_to(&insn, target_addr, include_jumps, include_indirect, &cs)
-                    {
+                    if let Some(xref_type) = is_reference_to(
+                        &insn,
+                        target_addr,
+                        include_jumps,
+                        include_indirect,
+                        arch,
+                        cs,
+                        &mut read_pointer,
+                    ) {
                         // Get context instructions
                         let context = get_instruction_context(
                             &buffer,
-                            offset,
+                            insn_start as usize,
                             context_count,
                             region_addr.to_umem(),
-                            &cs,
+                            context_format,
+                            cs,
                         );
 
                         // Create result
@@ -303,10 +476,13 @@ fn find_potential_call_offsets(
         }
     }
 
-    // Look for indirect call (FF 15) instructions if requested
+    // Look for indirect call (FF 15) instructions if requested,
+    // and indirect jump (FF 25) instructions when jumps are included too
     if include_indirect {
         for i in 0..buffer.len().saturating_sub(6) {
-            if buffer[i] == 0xFF && buffer[i + 1] == 0x15 {
+            if buffer[i] == 0xFF
+                && (buffer[i + 1] == 0x15 || (include_jumps && buffer[i + 1] == 0x25))
+            {
                 offsets.push(i);
             }
         }
@@ -314,3 +490,155 @@ fn find_potential_call_offsets(
 
     offsets
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Decodes the single instruction in `code` at `address` and checks it against `target`
+    fn reference(code: &[u8], address: u64, target: u64, arch: Arch) -> Option<XrefType> {
+        let cs = init_capstone(arch).unwrap();
+        let insns = cs.disasm_count(code, address, 1).unwrap();
+        let insn = insns.iter().next().unwrap();
+        is_reference_to(&insn, target, true, true, arch, &cs, &mut |slot| {
+            (slot == 0x0040_3000).then_some(0x0040_2000)
+        })
+    }
+
+    #[test]
+    fn resolves_32_bit_relative_call() {
+        // call 0x402000
+        let code = [0xE8, 0xFB, 0x0F, 0x00, 0x00];
+        assert!(matches!(
+            reference(&code, 0x0040_1000, 0x0040_2000, Arch::X86_32),
+            Some(XrefType::Call)
+        ));
+        assert!(reference(&code, 0x0040_1000, 0x0040_2001, Arch::X86_32).is_none());
+    }
+
+    #[test]
+    fn resolves_32_bit_relative_call_across_wraparound() {
+        // call 0x1000 from the top of the 32-bit address space
+        let code = [0xE8, 0xFB, 0x1F, 0x00, 0x00];
+        assert!(matches!(
+            reference(&code, 0xFFFF_F000, 0x1000, Arch::X86_32),
+            Some(XrefType::Call)
+        ));
+        assert!(reference(&code, 0xFFFF_F000, 0x1_0000_1000, Arch::X86_32).is_none());
+    }
+
+    #[test]
+    fn resolves_32_bit_relative_jump_backwards_across_wraparound() {
+        // jmp 0xfffff000 from the bottom of the 32-bit address space
+        let code = [0xE9, 0xFB, 0xEF, 0xFF, 0xFF];
+        assert!(matches!(
+            reference(&code, 0x0000_0000, 0xFFFF_F000, Arch::X86_32),
+            Some(XrefType::Jump)
+        ));
+    }
+
+    #[test]
+    fn resolves_32_bit_indirect_call() {
+        // call dword ptr [0x403000], the slot holds 0x402000
+        let code = [0xFF, 0x15, 0x00, 0x30, 0x40, 0x00];
+        assert!(matches!(
+            reference(&code, 0x0040_1000, 0x0040_2000, Arch::X86_32),
+            Some(XrefType::Indirect)
+        ));
+    }
+
+    // A function after int3 padding: push rbp; mov rbp, rsp; sub rsp, 0x20; call; ret
+    const FUNCTION: [u8; 18] = [
+        0xCC, 0xCC, 0x55, 0x48, 0x89, 0xE5, 0x48, 0x83, 0xEC, 0x20, 0xE8, 0x00, 0x00, 0x00, 0x00,
+        0xC3, 0xCC, 0xCC,
+    ];
+
+    fn context(buffer: &[u8], insn_offset: usize, count: usize) -> Vec<String> {
+        let cs = init_capstone(Arch::X86_64).unwrap();
+        get_instruction_context(
+            buffer,
+            insn_offset,
+            count,
+            0x1000,
+            Some("{address} {mnemonic}"),
+            &cs,
+        )
+    }
+
+    #[test]
+    fn context_decodes_forward_from_padding() {
+        assert_eq!(context_decode_start(&FUNCTION, 10), 2);
+
+        // Bytes before the padding that would decode into the function are never used
+        let mut buffer = vec![0x48, 0xB8, 0x55, 0x48, 0x89, 0xE5, 0x48, 0x83];
+        buffer.extend_from_slice(&FUNCTION);
+        assert_eq!(
+            context(&buffer, 18, 2),
+            vec![
+                "0x100b mov",
+                "0x100e sub",
+                "0x1012 call",
+                "0x1017 ret",
+                "0x1018 int3"
+            ]
+        );
+    }
+
+    #[test]
+    fn context_keeps_last_instructions_before_reference() {
+        assert_eq!(
+            context(&FUNCTION, 10, 1),
+            vec!["0x1006 sub", "0x100a call", "0x100f ret"]
+        );
+        assert_eq!(
+            context(&FUNCTION, 10, 5),
+            vec![
+                "0x1002 push",
+                "0x1003 mov",
+                "0x1006 sub",
+                "0x100a call",
+                "0x100f ret",
+                "0x1010 int3",
+                "0x1011 int3",
+            ]
+        );
+    }
+
+    #[test]
+    fn context_starts_at_buffer_start_without_padding() {
+        let buffer = [0x90, 0x90, 0xE8, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(context_decode_start(&buffer, 2), 0);
+        assert_eq!(
+            context(&buffer, 2, 4),
+            vec!["0x1000 nop", "0x1001 nop", "0x1002 call"]
+        );
+        assert_eq!(context(&buffer, 2, 0), vec!["0x1002 call"]);
+    }
+
+    #[test]
+    fn owned_chunk_ranges_tile_the_region_with_full_context() {
+        let region_addr = 0x40_0000;
+        let region_size = 0x1_0000;
+        let region_end = region_addr + region_size as u64;
+
+        let mut next = region_addr;
+        for (chunk_addr, chunk_len) in
+            crate::split_region(region_addr, region_size, 0x4000, XREF_CHUNK_OVERLAP)
+        {
+            let (owned_start, owned_end) =
+                owned_chunk_range(chunk_addr, chunk_len, region_addr, region_end);
+            assert_eq!(owned_start, next);
+            next = owned_end;
+
+            if chunk_addr != region_addr {
+                assert!(owned_start - chunk_addr >= CONTEXT_DECODE_WINDOW as u64);
+            }
+            let chunk_end = chunk_addr + chunk_len as u64;
+            if chunk_end != region_end {
+                let after = (MAX_CONTEXT_INSTRUCTIONS + 1) * MAX_INSN_LEN;
+                assert!(chunk_end - owned_end >= after as u64);
+            }
+        }
+        assert_eq!(next, region_end);
+    }
+}
@@ -1,15 +1,25 @@
-use crate::memflow_process_wrapper::MemflowProcessWrapper;
+use crate::cached_process::process_handle;
+use crate::memflow_module_wrapper::MemflowModuleWrapper;
+use crate::module_shards::find_export;
 use crate::protection_filter::protection_filter_matches;
-use crate::xref_scanner::{scan_region_for_xrefs, Arch};
-use crate::MEMFLOW_PROCESS_TYPE;
+use crate::xref_scanner::{
+    init_capstone, owned_chunk_range, scan_region_for_xrefs, Arch, XrefType,
+    MAX_CONTEXT_INSTRUCTIONS, XREF_CHUNK_OVERLAP,
+};
+use crate::{
+    chunk_size_param, coalesce_regions, insert_address, parse_address, scan_regions, split_region,
+    DEFAULT_CHUNK_SIZE, MEMFLOW_MODULE_TYPE, MEMFLOW_MODULE_TYPE_VAR,
+};
 
 use memflow::prelude::v1::*;
 use shards::shard::Shard;
-use shards::shlog_debug;
 use shards::types::{
     common_type, AutoSeqVar, AutoTableVar, Context, ExposedTypes, InstanceData, ParamVar, Type,
     Types, Var, ANYS_TYPES,
 };
+use shards::{shlog_debug, shlog_error};
+
+use std::collections::HashSet;
 
 // Define the FunctionXref Shard
 #[derive(shards::shard)]
@@ -22,21 +32,36 @@ pub struct MemflowFunctionXrefShard {
     required: ExposedTypes,
 
     // Parameters
-    #[shard_param("FunctionAddress", "Address of the target function.", [common_type::int, common_type::int_var])]
+    #[shard_param("FunctionAddress", "Address of the target function. Takes precedence over FunctionName.", [common_type::none, common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
     function_address: ParamVar,
 
+    #[shard_param("Module", "Module exporting FunctionName (default: the primary module of the process).", [common_type::none, *MEMFLOW_MODULE_TYPE, *MEMFLOW_MODULE_TYPE_VAR])]
+    module: ParamVar,
+
+    #[shard_param("FunctionName", "Name of an exported target function, used when FunctionAddress is not set.", [common_type::none, common_type::string, common_type::string_var])]
+    function_name: ParamVar,
+
     #[shard_param("IncludeJumps", "Whether to include jumps in addition to calls.", [common_type::bool, common_type::bool_var])]
     include_jumps: ParamVar,
 
     #[shard_param("IncludeIndirect", "Whether to include indirect references.", [common_type::bool, common_type::bool_var])]
     include_indirect: ParamVar,
 
-    #[shard_param("ContextInstructions", "Number of context instructions to include.", [common_type::int, common_type::int_var])]
+    #[shard_param("ContextInstructions", "Number of context instructions to include before and after each reference, at most 64 (default: 2).", [common_type::int, common_type::int_var])]
     context_instructions: ParamVar,
 
     #[shard_param("Protection", "Memory protection to filter by (default: 'r-x').", [common_type::string, common_type::string_var])]
     protection: ParamVar,
 
+    #[shard_param("ContextFormat", "Template for context instruction lines using {address}, {bytes}, {mnemonic} and {op_str} (default: fixed-width columns with all fields).", [common_type::none, common_type::string, common_type::string_var])]
+    context_format: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Limit", "Maximum number of references to return, lowest addresses first (default: no limit).", [common_type::none, common_type::int, common_type::int_var])]
+    limit: ParamVar,
+
     // Output results
     xref_results: AutoSeqVar,
 }
@@ -46,10 +71,15 @@ impl Default for MemflowFunctionXrefShard {
         Self {
             required: ExposedTypes::new(),
             function_address: ParamVar::default(),
+            module: ParamVar::default(),
+            function_name: ParamVar::default(),
             include_jumps: ParamVar::new(false.into()),
             include_indirect: ParamVar::new(false.into()),
             context_instructions: ParamVar::new(2.into()),
             protection: ParamVar::new(Var::ephemeral_string("r-x")),
+            context_format: ParamVar::default(),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            limit: ParamVar::default(),
             xref_results: AutoSeqVar::new(),
         }
     }
@@ -58,7 +88,7 @@ impl Default for MemflowFunctionXrefShard {
 #[shards::shard_impl]
 impl Shard for MemflowFunctionXrefShard {
     fn input_types(&mut self) -> &Types {
-        &crate::MEMFLOW_PROCESS_TYPES // Takes process as input
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
     }
 
     fn output_types(&mut self) -> &Types {
@@ -86,20 +116,62 @@ impl Shard for MemflowFunctionXrefShard {
         _context: &Context,
         input: &Var,
     ) -> std::result::Result<Option<Var>, &str> {
-        // Get the Process instance from input
-        let process = unsafe {
-            &mut *Var::from_ref_counted_object::<MemflowProcessWrapper>(
-                input,
-                &*MEMFLOW_PROCESS_TYPE,
-            )?
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get the target function, either directly or by resolving its export name
+        let target_addr = if !self.function_address.get().is_none() {
+            if !self.function_name.get().is_none() {
+                shlog_debug!(
+                    "Both FunctionAddress and FunctionName are set, using FunctionAddress"
+                );
+            }
+            parse_address(self.function_address.get())?
+        } else if !self.function_name.get().is_none() {
+            let function_name: &str = self.function_name.get().as_ref().try_into()?;
+            let module_info = if self.module.get().is_none() {
+                process.process().primary_module().map_err(|e| {
+                    shlog_error!("Failed to get primary module: {}", e);
+                    "Failed to get primary module."
+                })?
+            } else {
+                let module = unsafe {
+                    &*Var::from_ref_counted_object::<MemflowModuleWrapper>(
+                        self.module.get(),
+                        &*MEMFLOW_MODULE_TYPE,
+                    )?
+                };
+                module.0.clone()
+            };
+            find_export(process.process(), &module_info, function_name, true)?
+        } else {
+            return Err("Either FunctionAddress or FunctionName must be provided.");
         };
 
         // Get parameters
-        let target_addr: i64 = self.function_address.get().as_ref().try_into()?;
         let include_jumps: bool = self.include_jumps.get().as_ref().try_into()?;
         let include_indirect: bool = self.include_indirect.get().as_ref().try_into()?;
         let context_count: i64 = self.context_instructions.get().as_ref().try_into()?;
+        if context_count < 0 {
+            return Err("ContextInstructions must not be negative");
+        }
+        let context_count = (context_count as usize).min(MAX_CONTEXT_INSTRUCTIONS);
         let protection_filter: &str = self.protection.get().as_ref().try_into()?;
+        let context_format: Option<&str> = if self.context_format.get().is_none() {
+            None
+        } else {
+            Some(self.context_format.get().as_ref().try_into()?)
+        };
+        let chunk_size = chunk_size_param(&self.chunk_size, XREF_CHUNK_OVERLAP)?;
+        let limit = if self.limit.get().is_none() {
+            None
+        } else {
+            let limit: i64 = self.limit.get().as_ref().try_into()?;
+            if limit < 0 {
+                return Err("Limit must not be negative");
+            }
+            Some(limit as usize)
+        };
 
         shlog_debug!(
             "Scanning for XREFs to function at 0x{:x}, include_jumps={}, include_indirect={}",
@@ -109,7 +181,7 @@ impl Shard for MemflowFunctionXrefShard {
         );
 
         // Get memory maps with filtering for executable regions
-        let maps = process.0.mapped_mem_vec(0);
+        let maps = process.process().mapped_mem_vec(0);
         let filtered_maps: Vec<_> = maps
             .into_iter()
             .filter(|map| {
@@ -122,13 +194,26 @@ impl Shard for MemflowFunctionXrefShard {
 
         self.xref_results.0.clear();
 
-        // Get the architecture of the process
-        // Default to X86_64 for simplicity
-        let arch = Arch::X86_64;
+        // Get the architecture of the process so 32-bit code is disassembled correctly
+        let proc_arch = process.process().info().proc_arch;
+        let arch = Arch::from_ident(proc_arch).ok_or_else(|| {
+            shlog_error!(
+                "Unsupported process architecture for xref scanning: {:?}",
+                proc_arch
+            );
+            "Unsupported process architecture."
+        })?;
+
+        // Build the disassembler once for the whole scan, it is expensive to create
+        let cs = init_capstone(arch).map_err(|e| {
+            shlog_error!("Failed to initialize disassembler: {}", e);
+            "Failed to initialize disassembler."
+        })?;
 
         // Scan each memory region for references
+        let mut xrefs = Vec::new();
         for map in filtered_maps {
-            let base_addr = map.0;
+            let base_addr = map.0.to_umem();
             let size = map.1.to_umem() as usize;
 
             // Skip regions that are too small
@@ -136,52 +221,257 @@ impl Shard for MemflowFunctionXrefShard {
                 continue;
             }
 
-            shlog_debug!(
-                "Scanning region at 0x{:x} with size {}",
-                base_addr.to_umem(),
-                size
-            );
+            shlog_debug!("Scanning region at 0x{:x} with size {}", base_addr, size);
+
+            let region_end = base_addr + size as umem;
+            for (chunk_addr, chunk_len) in
+                split_region(base_addr, size, chunk_size, XREF_CHUNK_OVERLAP)
+            {
+                let (owned_start, owned_end) =
+                    owned_chunk_range(chunk_addr, chunk_len, base_addr, region_end);
+
+                // Scan the chunk for references
+                xrefs.extend(
+                    scan_region_for_xrefs(
+                        &mut process,
+                        Address::from(chunk_addr),
+                        chunk_len,
+                        target_addr,
+                        include_jumps,
+                        include_indirect,
+                        context_count,
+                        context_format,
+                        arch,
+                        &cs,
+                    )
+                    .into_iter()
+                    .filter(|xref| xref.address >= owned_start && xref.address < owned_end),
+                );
+            }
+        }
 
-            // Scan the region for references
-            let xrefs = scan_region_for_xrefs(
-                &mut process.0,
-                base_addr,
-                size,
-                target_addr as u64,
-                include_jumps,
-                include_indirect,
-                context_count as usize,
-                arch,
-            );
+        // The same instruction can be decoded from several candidate offsets,
+        // keep one result per instruction and report them in address order
+        let mut seen = HashSet::new();
+        xrefs.retain(|xref| seen.insert(xref.address));
+        xrefs.sort_by_key(|xref| xref.address);
+        if let Some(limit) = limit {
+            xrefs.truncate(limit);
+        }
 
-            // Add results to output
-            for xref in xrefs {
-                let mut result_entry = AutoTableVar::new();
-
-                // Add basic information
-                let address_var: Var = (xref.address as i64).into();
-                let type_var = Var::ephemeral_string(xref.xref_type.to_string());
-                let instruction_var = Var::ephemeral_string(&xref.instruction);
-
-                result_entry.0.insert_fast_static("address", &address_var);
-                result_entry.0.insert_fast_static("type", &type_var);
-                result_entry
-                    .0
-                    .insert_fast_static("instruction", &instruction_var);
-
-                // Add context instructions
-                let mut context_seq = AutoSeqVar::new();
-                for (_i, ctx_insn) in xref.context.iter().enumerate() {
-                    let ctx_var = Var::ephemeral_string(ctx_insn);
-                    context_seq.0.push(&ctx_var);
-                }
-
-                result_entry
-                    .0
-                    .insert_fast_static("context", &context_seq.0 .0);
-
-                self.xref_results.0.emplace_table(result_entry);
+        // Add results to output
+        for xref in xrefs {
+            let mut result_entry = AutoTableVar::new();
+
+            // Add basic information
+            let type_var = Var::ephemeral_string(xref.xref_type.to_string());
+            let instruction_var = Var::ephemeral_string(&xref.instruction);
+
+            insert_address(&mut result_entry, xref.address as umem);
+            result_entry.0.insert_fast_static("type", &type_var);
+            result_entry
+                .0
+                .insert_fast_static("instruction", &instruction_var);
+
+            // Add context instructions
+            let mut context_seq = AutoSeqVar::new();
+            for (_i, ctx_insn) in xref.context.iter().enumerate() {
+                let ctx_var = Var::ephemeral_string(ctx_insn);
+                context_seq.0.push(&ctx_var);
             }
+
+            result_entry
+                .0
+                .insert_fast_static("context", &context_seq.0 .0);
+
+            self.xref_results.0.emplace_table(result_entry);
+        }
+
+        Ok(Some(self.xref_results.0 .0))
+    }
+}
+
+// Helper function to find pointer-sized little-endian values equal to `target` in a buffer.
+// With `aligned`, only offsets that are a multiple of the pointer size in the address space
+// are considered, which is where compilers place vtable slots and global pointers.
+fn scan_pointers(
+    buffer: &[u8],
+    base_addr: umem,
+    target: u64,
+    pointer_size: usize,
+    aligned: bool,
+) -> Vec<umem> {
+    let needle = &target.to_le_bytes()[..pointer_size];
+    let (first_offset, step) = if aligned {
+        let align = pointer_size as umem;
+        (((align - base_addr % align) % align) as usize, pointer_size)
+    } else {
+        (0, 1)
+    };
+
+    let mut found = Vec::new();
+    let mut offset = first_offset;
+    while offset + pointer_size <= buffer.len() {
+        if &buffer[offset..offset + pointer_size] == needle {
+            found.push(base_addr + offset as umem);
+        }
+        offset += step;
+    }
+    found
+}
+
+// Define the DataXref Shard
+#[derive(shards::shard)]
+#[shard_info(
+    "Memflow.DataXref",
+    "Scans memory for stored pointers to an address, such as vtable slots and global function pointers."
+)]
+pub struct MemflowDataXrefShard {
+    #[shard_required]
+    required: ExposedTypes,
+
+    // Parameters
+    #[shard_param("Address", "Target address the pointers should point to.", [common_type::int, common_type::int_var, common_type::string, common_type::string_var])]
+    address: ParamVar,
+
+    #[shard_param("Protection", "Memory protection to filter by (default: 'r', any readable memory).", [common_type::string, common_type::string_var])]
+    protection: ParamVar,
+
+    #[shard_param("Aligned", "Only report pointers stored at pointer-aligned addresses (default: true).", [common_type::bool, common_type::bool_var])]
+    aligned: ParamVar,
+
+    #[shard_param("ChunkSize", "Number of bytes read at once from large memory regions (default: 4 MiB).", [common_type::int, common_type::int_var])]
+    chunk_size: ParamVar,
+
+    #[shard_param("Threads", "Number of worker threads scanning the read memory (default: 1).", [common_type::int, common_type::int_var])]
+    threads: ParamVar,
+
+    #[shard_param("Limit", "Maximum number of references to return, lowest addresses first (default: no limit).", [common_type::none, common_type::int, common_type::int_var])]
+    limit: ParamVar,
+
+    // Output results
+    xref_results: AutoSeqVar,
+}
+
+impl Default for MemflowDataXrefShard {
+    fn default() -> Self {
+        Self {
+            required: ExposedTypes::new(),
+            address: ParamVar::new(0.into()),
+            protection: ParamVar::new(Var::ephemeral_string("r")),
+            aligned: ParamVar::new(true.into()),
+            chunk_size: ParamVar::new(DEFAULT_CHUNK_SIZE.into()),
+            threads: ParamVar::new(1.into()),
+            limit: ParamVar::default(),
+            xref_results: AutoSeqVar::new(),
+        }
+    }
+}
+
+#[shards::shard_impl]
+impl Shard for MemflowDataXrefShard {
+    fn input_types(&mut self) -> &Types {
+        &crate::MEMFLOW_ANY_PROCESS_TYPES // Takes a plain or cached process as input
+    }
+
+    fn output_types(&mut self) -> &Types {
+        &ANYS_TYPES // Outputs a sequence of results
+    }
+
+    fn compose(&mut self, data: &InstanceData) -> std::result::Result<Type, &str> {
+        self.compose_helper(data)?;
+        Ok(self.output_types()[0])
+    }
+
+    fn warmup(&mut self, ctx: &Context) -> std::result::Result<(), &str> {
+        self.warmup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn cleanup(&mut self, ctx: Option<&Context>) -> std::result::Result<(), &str> {
+        self.xref_results = AutoSeqVar::new();
+        self.cleanup_helper(ctx)?;
+        Ok(())
+    }
+
+    fn activate(
+        &mut self,
+        _context: &Context,
+        input: &Var,
+    ) -> std::result::Result<Option<Var>, &str> {
+        // Get the plain or cached Process instance from input
+        let mut process = process_handle(input)?;
+
+        // Get parameters
+        let target_addr = parse_address(self.address.get())?;
+        let protection_filter: &str = self.protection.get().as_ref().try_into()?;
+        let aligned: bool = self.aligned.get().as_ref().try_into()?;
+        let threads: i64 = self.threads.get().as_ref().try_into()?;
+        let limit = if self.limit.get().is_none() {
+            None
+        } else {
+            let limit: i64 = self.limit.get().as_ref().try_into()?;
+            if limit < 0 {
+                return Err("Limit must not be negative");
+            }
+            Some(limit as usize)
+        };
+
+        // Stored pointers are as wide as the process' address space
+        let proc_arch = process.process().info().proc_arch;
+        let arch = Arch::from_ident(proc_arch).ok_or_else(|| {
+            shlog_error!(
+                "Unsupported process architecture for xref scanning: {:?}",
+                proc_arch
+            );
+            "Unsupported process architecture."
+        })?;
+        let pointer_size = arch.pointer_size();
+        let target = arch.wrap_address(target_addr);
+
+        shlog_debug!(
+            "Scanning for {}-byte pointers to 0x{:x}",
+            pointer_size,
+            target
+        );
+
+        // Get memory maps with filtering by protection
+        let maps = process.process().mapped_mem_vec(0);
+        let filtered_maps: Vec<_> = maps
+            .into_iter()
+            .filter(|map| protection_filter_matches(map.2, protection_filter))
+            .collect();
+
+        shlog_debug!("Filtered to {} memory regions", filtered_maps.len());
+
+        // Split the merged regions into chunks overlapping by less than a pointer,
+        // so every stored pointer lies in exactly one chunk
+        let overlap = pointer_size - 1;
+        let chunk_size = chunk_size_param(&self.chunk_size, overlap)?;
+        let regions: Vec<(umem, usize)> = coalesce_regions(&filtered_maps)
+            .into_iter()
+            .filter(|(_, size)| *size >= pointer_size)
+            .flat_map(|(address, size)| split_region(address, size, chunk_size, overlap))
+            .collect();
+
+        let mut xrefs = scan_regions(
+            &mut process,
+            &regions,
+            threads.max(1) as usize,
+            |base_addr, buffer| scan_pointers(buffer, base_addr, target, pointer_size, aligned),
+        );
+        xrefs.sort_unstable();
+        if let Some(limit) = limit {
+            xrefs.truncate(limit);
+        }
+
+        self.xref_results.0.clear();
+        let type_var = Var::ephemeral_string(XrefType::DataRef.to_string());
+        for xref in xrefs {
+            let mut result_entry = AutoTableVar::new();
+            insert_address(&mut result_entry, xref);
+            result_entry.0.insert_fast_static("type", &type_var);
+            self.xref_results.0.emplace_table(result_entry);
         }
 
         Ok(Some(self.xref_results.0 .0))